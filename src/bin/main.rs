@@ -20,6 +20,7 @@ struct Main {
 #[argh(subcommand)]
 enum Commands {
     Run(Run),
+    Lib(Lib),
     Libs(Libs),
 }
 
@@ -29,7 +30,6 @@ enum Commands {
     description = "List libraries for the selected backend",
     subcommand
 )]
-
 struct Libs {
     #[argh(
         option,
@@ -40,6 +40,98 @@ struct Libs {
     backend: Backend,
 }
 
+#[derive(Debug, FromArgs)]
+#[argh(
+    name = "lib",
+    description = "compile a futhark program into a C library",
+    subcommand
+)]
+struct Lib {
+    #[argh(positional, description = "futhark input file")]
+    input: std::path::PathBuf,
+
+    #[argh(
+        option,
+        short = 'o',
+        description = "output file, defaults to lib<name>.a or lib<name>.so"
+    )]
+    output: Option<std::path::PathBuf>,
+
+    #[argh(switch, description = "build a shared library")]
+    shared: bool,
+
+    #[argh(option, description = "soname of the shared library")]
+    soname: Option<String>,
+
+    #[argh(
+        option,
+        description = "add a directory to the runtime library search path"
+    )]
+    rpath: Vec<std::path::PathBuf>,
+
+    #[argh(
+        option,
+        description = "optimization level for the C compiler: 0, 1, 2, 3, s or z"
+    )]
+    opt_level: Option<String>,
+
+    #[argh(option, description = "extra flags to pass to the C compiler")]
+    cflag: Vec<String>,
+
+    #[argh(
+        option,
+        description = "ispc target, can be used more than once to build for multiple targets"
+    )]
+    ispc_target: Vec<String>,
+
+    #[argh(
+        option,
+        description = "generate bindings for the library, the output language is detected from the file extension"
+    )]
+    bindings: Option<std::path::PathBuf>,
+
+    #[argh(
+        option,
+        default = "Backend::C",
+        from_str_fn(parse_backend),
+        description = "futhark backend: c, cuda, opencl, multicore"
+    )]
+    backend: Backend,
+
+    #[argh(option, description = "path to futhark compiler")]
+    compiler: Option<String>,
+
+    #[argh(
+        option,
+        long = "futhark-arg",
+        short = 'f',
+        description = "arguments to be passed to the futhark compiler"
+    )]
+    futhark_args: Vec<String>,
+
+    #[argh(
+        switch,
+        long = "pkg-sync",
+        description = "run `futhark pkg sync` first when the packages in futhark.pkg are missing or changed"
+    )]
+    pkg_sync: bool,
+
+    #[argh(switch, description = "ignore `unsafe` in the futhark code")]
+    safe: bool,
+
+    #[argh(switch, description = "treat futhark warnings as errors")]
+    werror: bool,
+
+    #[argh(switch, description = "disable futhark warnings")]
+    no_warnings: bool,
+
+    #[argh(
+        option,
+        description = "treat a function as an additional entry point, can be used more than once"
+    )]
+    entry_point: Vec<String>,
+}
+
 #[derive(Debug, FromArgs)]
 #[argh(name = "run", description = "generate bindings", subcommand)]
 struct Run {
@@ -60,6 +152,12 @@ struct Run {
     #[argh(option, description = "path to futhark compiler")]
     compiler: Option<String>,
 
+    #[argh(
+        option,
+        description = "output language, instead of detecting it from the output file extension: rust, ocaml, go, zig, julia, haskell, node, csharp, lua, c or the name of an external futhark-bindgen-<lang> executable"
+    )]
+    lang: Option<String>,
+
     #[argh(
         option,
         long = "futhark-arg",
@@ -67,6 +165,48 @@ struct Run {
         description = "arguments to be passed to the futhark compiler"
     )]
     futhark_args: Vec<String>,
+
+    #[argh(
+        switch,
+        long = "pkg-sync",
+        description = "run `futhark pkg sync` first when the packages in futhark.pkg are missing or changed"
+    )]
+    pkg_sync: bool,
+
+    #[argh(switch, description = "ignore `unsafe` in the futhark code")]
+    safe: bool,
+
+    #[argh(switch, description = "treat futhark warnings as errors")]
+    werror: bool,
+
+    #[argh(switch, description = "disable futhark warnings")]
+    no_warnings: bool,
+
+    #[argh(
+        option,
+        description = "treat a function as an additional entry point, can be used more than once"
+    )]
+    entry_point: Vec<String>,
+
+    #[argh(
+        switch,
+        description = "also build a futhark server executable named <input>-server next to the output, Rust output is a client for it instead of FFI bindings"
+    )]
+    server: bool,
+
+    #[argh(
+        switch,
+        long = "async",
+        description = "generate async entry points (Rust only)"
+    )]
+    async_entry_points: bool,
+
+    #[argh(
+        switch,
+        long = "pending",
+        description = "return pending values from entry points (Rust only)"
+    )]
+    pending_results: bool,
 }
 
 fn main() -> Result<(), Error> {
@@ -80,16 +220,94 @@ fn main() -> Result<(), Error> {
             let out_dir = args.output.parent().unwrap().canonicalize().unwrap();
             let mut compiler = Compiler::new(args.backend, &args.input)
                 .with_extra_args(args.futhark_args)
+                .with_pkg_sync(args.pkg_sync)
+                .with_safe(args.safe)
+                .with_werror(args.werror)
+                .with_warnings(!args.no_warnings)
+                .with_server(args.server)
                 .with_output_dir(out_dir);
             if let Some(exe) = args.compiler {
                 compiler = compiler.with_executable_name(exe);
             }
+            for name in args.entry_point {
+                compiler = compiler.with_entry_point(name);
+            }
             let pkg = compiler.compile()?;
-            let mut config = Config::new(args.output)?;
-            let mut gen = config.detect().expect("Unable to detect output language");
+            pkg.check_output(&args.output)?;
+            let mut config = Config::new(args.output)?
+                .with_async_entry_points(args.async_entry_points)
+                .with_pending_results(args.pending_results)
+                .with_server_client(args.server);
+            let mut gen = match &args.lang {
+                Some(lang) => config.lang(lang),
+                None => config.detect().expect("Unable to detect output language"),
+            };
             gen.generate(&pkg, &mut config)?;
         }
+        Commands::Lib(args) => {
+            let mut output = args.output.unwrap_or_else(|| {
+                let name = args.input.file_stem().unwrap().to_string_lossy();
+                if args.shared {
+                    format!(
+                        "{}{name}{}",
+                        std::env::consts::DLL_PREFIX,
+                        std::env::consts::DLL_SUFFIX
+                    )
+                } else {
+                    format!("lib{name}.a")
+                }
+                .into()
+            });
+            if output.is_relative() {
+                output = std::path::PathBuf::from(".").join(output);
+            }
+            let out_dir = output.parent().unwrap().canonicalize().unwrap();
+            let mut compiler = Compiler::new(args.backend, &args.input)
+                .with_extra_args(args.futhark_args)
+                .with_pkg_sync(args.pkg_sync)
+                .with_safe(args.safe)
+                .with_werror(args.werror)
+                .with_warnings(!args.no_warnings)
+                .with_output_dir(out_dir);
+            if let Some(exe) = args.compiler {
+                compiler = compiler.with_executable_name(exe);
+            }
+            for name in args.entry_point {
+                compiler = compiler.with_entry_point(name);
+            }
+            let pkg = compiler.compile()?;
+
+            let mut build_config = BuildConfig::new().with_shared(args.shared);
+            if let Some(soname) = args.soname {
+                build_config = build_config.with_soname(soname);
+            }
+            if let Some(level) = args.opt_level {
+                build_config = build_config.with_opt_level(level);
+            }
+            for dir in args.rpath {
+                build_config = build_config.with_rpath(dir);
+            }
+            for flag in args.cflag {
+                build_config = build_config.with_flag(flag);
+            }
+            for target in args.ispc_target {
+                build_config = build_config.with_ispc_target(target);
+            }
+            pkg.build_library(&output, &build_config)?;
+
+            if let Some(bindings) = args.bindings {
+                pkg.check_output(&bindings)?;
+                let mut config = Config::new(bindings)?;
+                let mut gen = config.detect().expect("Unable to detect output language");
+                gen.generate(&pkg, &mut config)?;
+            }
+        }
         Commands::Libs(args) => {
+            if let Ok(sdk) = Sdk::find(args.backend) {
+                sdk.lib_dirs
+                    .iter()
+                    .for_each(|x| print!("-L{} ", x.display()));
+            }
             args.backend
                 .required_c_libs()
                 .iter()
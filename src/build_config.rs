@@ -0,0 +1,242 @@
+use crate::target::Target;
+
+/// Options used to compile the C code generated by Futhark
+///
+/// The C code is compiled into a static archive unless [`BuildConfig::with_shared`] is used.
+///
+/// By default the optimization level and debug info are selected using the `PROFILE` environment
+/// variable set by cargo: `-O0` with debug info for `debug` and `-O3` without debug info
+/// otherwise.
+#[derive(Debug, Clone, Default)]
+pub struct BuildConfig {
+    opt_level: Option<String>,
+    debug: Option<bool>,
+    flags: Vec<String>,
+    defines: Vec<(String, Option<String>)>,
+    include_dirs: Vec<std::path::PathBuf>,
+    env_defaults: bool,
+    warnings: bool,
+    shared: bool,
+    soname: Option<String>,
+    rpaths: Vec<std::path::PathBuf>,
+    ispc_targets: Vec<String>,
+    ispc_opt_level: Option<String>,
+    ispc_flags: Vec<String>,
+}
+
+fn profile_is_debug() -> bool {
+    matches!(std::env::var("PROFILE"), Ok(profile) if profile == "debug")
+}
+
+impl BuildConfig {
+    /// Create a new `BuildConfig` with the default options
+    pub fn new() -> BuildConfig {
+        BuildConfig::default()
+    }
+
+    /// Set the optimization level: `0`, `1`, `2`, `3`, `s` or `z`
+    pub fn with_opt_level(mut self, level: impl Into<String>) -> Self {
+        self.opt_level = Some(level.into());
+        self
+    }
+
+    /// Enable or disable debug info
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = Some(debug);
+        self
+    }
+
+    /// Add a flag to pass to the C compiler, for example `-march=native` or `-fsanitize=address`
+    pub fn with_flag(mut self, flag: impl Into<String>) -> Self {
+        self.flags.push(flag.into());
+        self
+    }
+
+    /// Define a preprocessor macro, `-D{name}` or `-D{name}={value}`
+    pub fn with_define(mut self, name: impl Into<String>, value: Option<&str>) -> Self {
+        self.defines.push((name.into(), value.map(String::from)));
+        self
+    }
+
+    /// Add a directory to the include path
+    pub fn with_include_dir(mut self, dir: impl AsRef<std::path::Path>) -> Self {
+        self.include_dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// When enabled the optimization level and debug info are not set unless they're configured
+    /// explicitly, instead `cc` selects them using `OPT_LEVEL`, `DEBUG` and `CFLAGS`
+    pub fn with_env_defaults(mut self, enable: bool) -> Self {
+        self.env_defaults = enable;
+        self
+    }
+
+    /// Enable or disable C compiler warnings, warnings are disabled by default
+    pub fn with_warnings(mut self, enable: bool) -> Self {
+        self.warnings = enable;
+        self
+    }
+
+    /// Build a shared library instead of a static archive
+    pub fn with_shared(mut self, enable: bool) -> Self {
+        self.shared = enable;
+        self
+    }
+
+    /// Set the soname (`install_name` on macOS) of a shared library
+    pub fn with_soname(mut self, soname: impl Into<String>) -> Self {
+        self.soname = Some(soname.into());
+        self
+    }
+
+    /// Add a directory to the runtime search path of a shared library
+    pub fn with_rpath(mut self, dir: impl AsRef<std::path::Path>) -> Self {
+        self.rpaths.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Add an ISPC target, for example `avx2-i32x8`, when more than one target is added `ispc`
+    /// generates code for each of them and selects one at runtime. Defaults to `host`, or a
+    /// baseline target for the architecture when cross-compiling
+    pub fn with_ispc_target(mut self, target: impl Into<String>) -> Self {
+        self.ispc_targets.push(target.into());
+        self
+    }
+
+    /// Set the ISPC optimization level: `0`, `1`, `2` or `3`, by default it's derived from the C
+    /// optimization level
+    pub fn with_ispc_opt_level(mut self, level: impl Into<String>) -> Self {
+        self.ispc_opt_level = Some(level.into());
+        self
+    }
+
+    /// Add a flag to pass to `ispc`
+    pub fn with_ispc_flag(mut self, flag: impl Into<String>) -> Self {
+        self.ispc_flags.push(flag.into());
+        self
+    }
+
+    /// Returns true when building a shared library
+    pub fn is_shared(&self) -> bool {
+        self.shared
+    }
+
+    /// Get the configured optimization level, `None` if it's left to `cc`
+    pub fn opt_level(&self) -> Option<String> {
+        match &self.opt_level {
+            Some(level) => Some(level.clone()),
+            None if self.env_defaults => None,
+            None if profile_is_debug() => Some("0".to_string()),
+            None => Some("3".to_string()),
+        }
+    }
+
+    /// Get the debug info setting, `None` if it's left to `cc`
+    pub fn debug(&self) -> Option<bool> {
+        match self.debug {
+            Some(debug) => Some(debug),
+            None if self.env_defaults => None,
+            None => Some(profile_is_debug()),
+        }
+    }
+
+    #[cfg(feature = "build")]
+    pub(crate) fn apply(&self, build: &mut cc::Build) {
+        if let Some(level) = self.opt_level() {
+            build.opt_level_str(&level);
+        }
+
+        if let Some(debug) = self.debug() {
+            build.debug(debug);
+        }
+
+        for (name, value) in &self.defines {
+            build.define(name, value.as_deref());
+        }
+
+        for dir in &self.include_dirs {
+            build.include(dir);
+        }
+
+        for flag in &self.flags {
+            build.flag(flag);
+        }
+
+        build.warnings(self.warnings).extra_warnings(self.warnings);
+    }
+
+    /// Arguments passed to the C compiler when it's called directly
+    pub(crate) fn cc_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(level) = self.opt_level() {
+            args.push(format!("-O{level}"));
+        }
+
+        if self.debug() == Some(true) {
+            args.push("-g".to_string());
+        }
+
+        for (name, value) in &self.defines {
+            match value {
+                Some(value) => args.push(format!("-D{name}={value}")),
+                None => args.push(format!("-D{name}")),
+            }
+        }
+
+        for dir in &self.include_dirs {
+            args.push(format!("-I{}", dir.display()));
+        }
+
+        args.extend(self.flags.iter().cloned());
+
+        if !self.warnings {
+            args.push("-w".to_string());
+        }
+        args
+    }
+
+    /// Arguments passed to the linker for the soname and rpath of a shared library
+    pub(crate) fn link_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(soname) = &self.soname {
+            if Target::current().is_macos() {
+                args.push(format!("-Wl,-install_name,{soname}"));
+            } else {
+                args.push(format!("-Wl,-soname,{soname}"));
+            }
+        }
+
+        for dir in &self.rpaths {
+            args.push(format!("-Wl,-rpath,{}", dir.display()));
+        }
+        args
+    }
+
+    /// Arguments passed to `ispc` for the targets, optimization level, debug info and extra flags
+    pub(crate) fn ispc_args(&self) -> Vec<String> {
+        let target = Target::current();
+        let targets = if self.ispc_targets.is_empty() {
+            target.ispc_target().to_string()
+        } else {
+            self.ispc_targets.join(",")
+        };
+
+        let opt_level = match &self.ispc_opt_level {
+            Some(level) => level.as_str(),
+            None => match self.opt_level().as_deref() {
+                Some("0") => "0",
+                Some("1") | Some("s") | Some("z") => "1",
+                Some("2") => "2",
+                _ => "3",
+            },
+        };
+
+        let mut args = vec![format!("--target={targets}"), format!("-O{opt_level}")];
+        args.extend(target.ispc_args());
+        if self.debug() == Some(true) {
+            args.push("-g".to_string());
+        }
+        args.extend(self.ispc_flags.iter().cloned());
+        args
+    }
+}
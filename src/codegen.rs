@@ -0,0 +1,417 @@
+//! Building blocks for code generators
+//!
+//! [`Types`] resolves the types and entry points in a [`Manifest`] into a language-neutral model.
+//! It is computed once when a [`Package`] is created and is available using [`Package::types`],
+//! the built-in generators use it to look up types and it can be used in the same way by
+//! third-party [`Generate`](crate::Generate) implementations.
+
+use crate::manifest::ElemType;
+use crate::*;
+
+/// Convert the first character of `s` to uppercase
+pub fn first_uppercase(s: &str) -> String {
+    let mut s = s.to_string();
+    if let Some(r) = s.get_mut(0..1) {
+        r.make_ascii_uppercase();
+    }
+    s
+}
+
+/// Get the struct name from a C pointer type, `struct futhark_f32_1d *` becomes `futhark_f32_1d`
+pub fn convert_struct_name(s: &str) -> &str {
+    s.strip_prefix("struct")
+        .unwrap()
+        .strip_suffix('*')
+        .unwrap()
+        .strip_prefix(|x: char| x.is_ascii_whitespace())
+        .unwrap()
+        .strip_suffix(|x: char| x.is_ascii_whitespace())
+        .unwrap()
+}
+
+/// Returns true if `t` is the name of a Futhark array type, for example `[]f32`
+pub fn type_is_array(t: &str) -> bool {
+    t.starts_with('[')
+}
+
+/// Returns true if `t` is the name of a Futhark opaque type, any type that isn't a scalar or an
+/// array
+pub fn type_is_opaque(t: &str) -> bool {
+    !type_is_array(t) && ElemType::from_name(t).is_none()
+}
+
+/// Array type
+#[derive(Clone, Debug)]
+pub struct ArrayType {
+    /// Futhark type name, for example `[][]f32`
+    pub name: String,
+
+    /// Name of the C struct, for example `futhark_f32_2d`
+    pub c_name: String,
+
+    /// Element type
+    pub elemtype: ElemType,
+
+    /// Number of dimensions
+    pub rank: usize,
+
+    /// Array functions
+    pub ops: manifest::ArrayOps,
+}
+
+impl ArrayType {
+    /// Language-neutral type name, for example `ArrayF32D2`
+    pub fn type_name(&self) -> String {
+        format!(
+            "Array{}D{}",
+            first_uppercase(self.elemtype.to_str()),
+            self.rank
+        )
+    }
+}
+
+/// Opaque type, records and sum types are also opaque types
+#[derive(Clone, Debug)]
+pub struct OpaqueType {
+    /// Futhark type name
+    pub name: String,
+
+    /// Name of the C struct, for example `futhark_opaque_point`
+    pub c_name: String,
+
+    /// Opaque value functions
+    pub ops: manifest::OpaqueOps,
+}
+
+impl OpaqueType {
+    /// Name of the C struct without the `futhark_opaque_` prefix
+    pub fn base_name(&self) -> &str {
+        self.c_name
+            .strip_prefix("futhark_opaque_")
+            .unwrap_or(&self.c_name)
+    }
+
+    /// Language-neutral type name, the base name with the first letter in uppercase. Names that
+    /// wouldn't be valid identifiers in most languages are prefixed with `Type`
+    pub fn type_name(&self) -> String {
+        let name = first_uppercase(self.base_name());
+        if name.chars().next().unwrap().is_numeric() || self.name.contains(' ') {
+            format!("Type{name}")
+        } else {
+            name
+        }
+    }
+}
+
+/// Record field
+#[derive(Clone, Debug)]
+pub struct Field {
+    /// Field name
+    pub name: String,
+
+    /// Function used to get the value of the field
+    pub project: String,
+
+    /// Field type
+    pub r#type: Type,
+}
+
+/// Record type
+#[derive(Clone, Debug)]
+pub struct RecordType {
+    pub opaque: OpaqueType,
+
+    /// Function used to create a new record
+    pub new: String,
+
+    pub fields: Vec<Field>,
+}
+
+/// Sum type variant
+#[derive(Clone, Debug)]
+pub struct Variant {
+    /// Variant name
+    pub name: String,
+
+    /// Function used to create a value with this variant
+    pub construct: String,
+
+    /// Function used to get the payload of this variant
+    pub destruct: String,
+
+    /// Payload types
+    pub payload: Vec<Type>,
+}
+
+/// Sum type
+#[derive(Clone, Debug)]
+pub struct SumType {
+    pub opaque: OpaqueType,
+
+    /// Function returning the index of the active variant
+    pub variant: String,
+
+    pub variants: Vec<Variant>,
+}
+
+/// Resolved type
+#[derive(Clone, Debug)]
+pub enum Type {
+    Scalar(ElemType),
+    Array(ArrayType),
+    Record(RecordType),
+    Sum(SumType),
+    Opaque(OpaqueType),
+}
+
+impl Type {
+    /// Futhark type name
+    pub fn name(&self) -> &str {
+        match self {
+            Type::Scalar(t) => t.to_str(),
+            Type::Array(a) => &a.name,
+            Type::Record(r) => &r.opaque.name,
+            Type::Sum(s) => &s.opaque.name,
+            Type::Opaque(o) => &o.name,
+        }
+    }
+
+    /// C type used by the Futhark C API, arrays and opaque types are pointers
+    pub fn c_type(&self) -> String {
+        match self {
+            Type::Scalar(t) => t.c_type().to_string(),
+            _ => format!("struct {} *", self.c_name().unwrap()),
+        }
+    }
+
+    /// Name of the C struct for arrays and opaque types, `None` for scalars
+    pub fn c_name(&self) -> Option<&str> {
+        match self {
+            Type::Scalar(_) => None,
+            Type::Array(a) => Some(&a.c_name),
+            Type::Record(RecordType { opaque, .. })
+            | Type::Sum(SumType { opaque, .. })
+            | Type::Opaque(opaque) => Some(&opaque.c_name),
+        }
+    }
+
+    /// Language-neutral type name for arrays and opaque types, `None` for scalars
+    pub fn type_name(&self) -> Option<String> {
+        match self {
+            Type::Scalar(_) => None,
+            Type::Array(a) => Some(a.type_name()),
+            _ => self.opaque().map(|o| o.type_name()),
+        }
+    }
+
+    /// Name of the function used to free values of this type, `None` for scalars
+    pub fn free_fn(&self) -> Option<&str> {
+        match self {
+            Type::Scalar(_) => None,
+            Type::Array(a) => Some(&a.ops.free),
+            _ => self.opaque().map(|o| o.ops.free.as_str()),
+        }
+    }
+
+    /// Get the opaque type information for records, sum types and opaque types
+    pub fn opaque(&self) -> Option<&OpaqueType> {
+        match self {
+            Type::Record(RecordType { opaque, .. })
+            | Type::Sum(SumType { opaque, .. })
+            | Type::Opaque(opaque) => Some(opaque),
+            _ => None,
+        }
+    }
+
+    /// Returns true for scalar types
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, Type::Scalar(_))
+    }
+
+    /// Returns true for array types
+    pub fn is_array(&self) -> bool {
+        matches!(self, Type::Array(_))
+    }
+
+    /// Returns true for records, sum types and opaque types
+    pub fn is_opaque(&self) -> bool {
+        self.opaque().is_some()
+    }
+}
+
+/// Entry point input or output
+#[derive(Clone, Debug)]
+pub struct Param {
+    /// Input name, outputs are unnamed
+    pub name: Option<String>,
+
+    pub r#type: Type,
+
+    /// Set when the value is consumed by the entry point
+    pub unique: bool,
+}
+
+/// Entry point
+#[derive(Clone, Debug)]
+pub struct Entry {
+    /// Entry point name
+    pub name: String,
+
+    /// C function name
+    pub cfun: String,
+
+    pub inputs: Vec<Param>,
+    pub outputs: Vec<Param>,
+}
+
+/// Resolved types and entry points
+#[derive(Clone, Debug)]
+pub struct Types {
+    types: BTreeMap<String, Type>,
+    entries: BTreeMap<String, Entry>,
+}
+
+fn resolve(manifest: &Manifest, name: &str) -> Result<Type, Error> {
+    if let Some(t) = ElemType::from_name(name) {
+        return Ok(Type::Scalar(t));
+    }
+
+    let t = match manifest.types.get(name) {
+        Some(manifest::Type::Array(a)) => Type::Array(ArrayType {
+            name: name.to_string(),
+            c_name: convert_struct_name(&a.ctype).to_string(),
+            elemtype: a.elemtype,
+            rank: a.rank as usize,
+            ops: a.ops.clone(),
+        }),
+        Some(manifest::Type::Opaque(ty)) => {
+            let opaque = OpaqueType {
+                name: name.to_string(),
+                c_name: convert_struct_name(&ty.ctype).to_string(),
+                ops: ty.ops.clone(),
+            };
+            if let Some(record) = &ty.record {
+                Type::Record(RecordType {
+                    opaque,
+                    new: record.new.clone(),
+                    fields: record
+                        .fields
+                        .iter()
+                        .map(|field| {
+                            Ok(Field {
+                                name: field.name.clone(),
+                                project: field.project.clone(),
+                                r#type: resolve(manifest, &field.r#type)?,
+                            })
+                        })
+                        .collect::<Result<_, Error>>()?,
+                })
+            } else if let Some(sum) = &ty.sum {
+                Type::Sum(SumType {
+                    opaque,
+                    variant: sum.variant.clone(),
+                    variants: sum
+                        .variants
+                        .iter()
+                        .map(|variant| {
+                            Ok(Variant {
+                                name: variant.name.clone(),
+                                construct: variant.construct.clone(),
+                                destruct: variant.destruct.clone(),
+                                payload: variant
+                                    .payload
+                                    .iter()
+                                    .map(|t| resolve(manifest, t))
+                                    .collect::<Result<_, Error>>()?,
+                            })
+                        })
+                        .collect::<Result<_, Error>>()?,
+                })
+            } else {
+                Type::Opaque(opaque)
+            }
+        }
+        None => return Err(Error::UnknownType(name.to_string())),
+    };
+    Ok(t)
+}
+
+impl Types {
+    /// Resolve all types and entry points in a manifest, fails if the manifest refers to a type
+    /// that it doesn't define
+    pub fn new(manifest: &Manifest) -> Result<Types, Error> {
+        let types = manifest
+            .types
+            .keys()
+            .map(|name| Ok((name.clone(), resolve(manifest, name)?)))
+            .collect::<Result<_, Error>>()?;
+
+        let entries = manifest
+            .entry_points
+            .iter()
+            .map(|(name, entry)| {
+                let entry = Entry {
+                    name: name.clone(),
+                    cfun: entry.cfun.clone(),
+                    inputs: entry
+                        .inputs
+                        .iter()
+                        .map(|input| {
+                            Ok(Param {
+                                name: Some(input.name.clone()),
+                                r#type: resolve(manifest, &input.r#type)?,
+                                unique: input.unique,
+                            })
+                        })
+                        .collect::<Result<_, Error>>()?,
+                    outputs: entry
+                        .outputs
+                        .iter()
+                        .map(|output| {
+                            Ok(Param {
+                                name: None,
+                                r#type: resolve(manifest, &output.r#type)?,
+                                unique: output.unique,
+                            })
+                        })
+                        .collect::<Result<_, Error>>()?,
+                };
+                Ok((name.clone(), entry))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Types { types, entries })
+    }
+
+    /// Look up a type by its Futhark name, scalar types are resolved as [`Type::Scalar`]
+    ///
+    /// Returns `None` if the type isn't a scalar and doesn't exist in the manifest
+    pub fn get(&self, name: &str) -> Option<Type> {
+        match self.types.get(name) {
+            Some(t) => Some(t.clone()),
+            None => ElemType::from_name(name).map(Type::Scalar),
+        }
+    }
+
+    /// Look up a type used by the manifest these types were resolved from, `Types::new` checks
+    /// that all of them exist
+    pub(crate) fn resolved(&self, name: &str) -> Type {
+        self.get(name)
+            .expect("the manifest types are checked by Types::new")
+    }
+
+    /// Iterate over all array and opaque types, sorted by Futhark name
+    pub fn iter(&self) -> impl Iterator<Item = &Type> {
+        self.types.values()
+    }
+
+    /// Look up an entry point by name
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries.get(name)
+    }
+
+    /// Iterate over all entry points, sorted by name
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.values()
+    }
+}
@@ -8,6 +8,75 @@ pub struct Compiler {
     src: std::path::PathBuf,
     extra_args: Vec<String>,
     output_dir: std::path::PathBuf,
+    pkg_sync: bool,
+    safe: bool,
+    werror: bool,
+    warnings: bool,
+    entry_points: Vec<String>,
+    server: bool,
+}
+
+/// Find `futhark.pkg` in `dir` or one of its ancestors
+fn find_pkg_file(dir: &std::path::Path) -> Option<std::path::PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join("futhark.pkg"))
+        .find(|path| path.is_file())
+}
+
+/// A package listed in the `require` section of `futhark.pkg`
+#[derive(Debug, PartialEq)]
+struct Requirement {
+    /// Package path, for example `github.com/diku-dk/sorts`
+    path: String,
+
+    /// Version and commit hash, for example `0.4.1 #5b6f7a...`
+    version: String,
+}
+
+/// Get the packages listed in the `require` section of `futhark.pkg`
+fn required_packages(pkg_file: &std::path::Path) -> Result<Vec<Requirement>, Error> {
+    let contents = std::fs::read_to_string(pkg_file)?;
+    let mut packages = Vec::new();
+    let mut in_require = false;
+    for line in contents.lines() {
+        let line = line.split("--").next().unwrap().trim();
+        if line.starts_with("require") && line.ends_with('{') {
+            in_require = true;
+        } else if line == "}" {
+            in_require = false;
+        } else if in_require {
+            let mut fields = line.split_whitespace();
+            if let Some(path) = fields.next() {
+                packages.push(Requirement {
+                    path: path.to_string(),
+                    version: fields.collect::<Vec<_>>().join(" "),
+                });
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// File in `lib/` recording the requirements from the last successful `futhark pkg sync`, the
+/// packages in `lib/` don't include their versions
+const SYNC_STAMP: &str = ".futhark-bindgen-sync";
+
+fn sync_stamp(packages: &[Requirement]) -> String {
+    packages
+        .iter()
+        .map(|package| format!("{} {}\n", package.path, package.version))
+        .collect()
+}
+
+/// Print a warning, using `cargo:warning` when running in a build script
+fn warn(msg: &str) {
+    if std::env::var_os("OUT_DIR").is_some() {
+        for line in msg.lines() {
+            println!("cargo:warning={line}");
+        }
+    } else {
+        eprintln!("warning: {msg}");
+    }
 }
 
 impl Compiler {
@@ -25,6 +94,12 @@ impl Compiler {
                 .unwrap()
                 .to_path_buf(),
             backend,
+            pkg_sync: false,
+            safe: false,
+            werror: false,
+            warnings: true,
+            entry_points: Vec::new(),
+            server: false,
         }
     }
 
@@ -35,7 +110,40 @@ impl Compiler {
         self
     }
 
-    /// Supply additional arguments to be passed to the `futhark` executable
+    /// Ignore `unsafe` in the Futhark code and keep all bounds checks: `--safe`
+    pub fn with_safe(mut self, enable: bool) -> Self {
+        self.safe = enable;
+        self
+    }
+
+    /// Treat warnings as errors: `--Werror`
+    pub fn with_werror(mut self, enable: bool) -> Self {
+        self.werror = enable;
+        self
+    }
+
+    /// Enable or disable compiler warnings, warnings are enabled by default: `-w`
+    pub fn with_warnings(mut self, enable: bool) -> Self {
+        self.warnings = enable;
+        self
+    }
+
+    /// Treat a function as an additional entry point: `--entry-point`
+    pub fn with_entry_point(mut self, name: impl Into<String>) -> Self {
+        self.entry_points.push(name.into());
+        self
+    }
+
+    /// Also build a server executable using `--server`, it's written to the output directory as
+    /// `<name>-server` and stored in [`Package::server`]. The library is still compiled because
+    /// the manifest is needed to generate bindings
+    pub fn with_server(mut self, enable: bool) -> Self {
+        self.server = enable;
+        self
+    }
+
+    /// Supply additional arguments to be passed to the `futhark` executable, these are used
+    /// for any flags that don't have a typed option
     pub fn with_extra_args(mut self, args: Vec<String>) -> Self {
         self.extra_args = args;
         self
@@ -47,20 +155,138 @@ impl Compiler {
         self
     }
 
+    /// Run `futhark pkg sync` before compiling when the packages listed in `futhark.pkg` are
+    /// missing from `lib/` or their versions have changed, see [`Compiler::sync_packages`]
+    pub fn with_pkg_sync(mut self, enable: bool) -> Self {
+        self.pkg_sync = enable;
+        self
+    }
+
+    /// Download the packages required by the source file using `futhark pkg sync`
+    ///
+    /// `futhark.pkg` is searched for in the directory containing the source file and its
+    /// ancestors, nothing is done when it can't be found. The requirements are recorded in
+    /// `lib/.futhark-bindgen-sync` after a successful sync, when all of the packages are in
+    /// `lib/` and the versions in `futhark.pkg` match the recorded ones `futhark pkg sync` isn't
+    /// run. If it fails, for example because the network isn't available, the vendored packages
+    /// are used with a warning as long as all of them exist.
+    pub fn sync_packages(&self) -> Result<(), Error> {
+        let src = self.src.canonicalize()?;
+        let pkg_file = match find_pkg_file(src.parent().unwrap()) {
+            Some(pkg_file) => pkg_file,
+            None => return Ok(()),
+        };
+        let root = pkg_file.parent().unwrap();
+        let packages = required_packages(&pkg_file)?;
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        let lib = root.join("lib");
+        let stamp_file = lib.join(SYNC_STAMP);
+        let stamp = sync_stamp(&packages);
+        let vendored = packages
+            .iter()
+            .all(|package| lib.join(&package.path).is_dir());
+        if vendored && std::fs::read_to_string(&stamp_file).ok().as_ref() == Some(&stamp) {
+            return Ok(());
+        }
+
+        let output = std::process::Command::new(&self.exe)
+            .args(["pkg", "sync"])
+            .current_dir(root)
+            .output()
+            .map_err(|e| Error::PackageSync(format!("unable to run {}: {e}", self.exe)))?;
+        let msg = format!(
+            "futhark pkg sync failed in {}\n{}{}",
+            root.display(),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        if output.status.success() {
+            std::fs::write(&stamp_file, stamp)?;
+        } else if vendored {
+            warn(&format!(
+                "{}\nusing the packages in {}, which may not match the versions in {}",
+                msg.trim_end(),
+                lib.display(),
+                pkg_file.display()
+            ));
+        } else {
+            return Err(Error::PackageSync(msg));
+        }
+        Ok(())
+    }
+
+    /// Get the version of the compiler by running `futhark --version`
+    pub fn version(&self) -> Result<Version, Error> {
+        let output = std::process::Command::new(&self.exe)
+            .arg("--version")
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::CompilationFailed);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout.lines().next().unwrap_or_default().parse::<Version>()
+    }
+
     /// Compile the package
     ///
     /// This will generate a C file, C header file and manifest
     pub fn compile(&self) -> Result<Package, Error> {
+        if self.pkg_sync {
+            self.sync_packages()?;
+        }
+
         // Create -o argument
-        let output = &self
-            .output_dir
-            .join(self.src.with_extension("").file_name().unwrap());
+        let name = self.src.with_extension("");
+        let name = name.file_name().unwrap().to_string_lossy();
+        let output = &self.output_dir.join(name.as_ref());
+        self.run("--lib", output)?;
+
+        // Load manifest after successful compilation
+        let manifest = Manifest::parse_file(output.with_extension("json"))?;
+        let c_file = output.with_extension("c");
+        let h_file = output.with_extension("h");
+        let mut pkg = Package::new(manifest, c_file, h_file, &self.src)?;
+
+        if self.server {
+            let server = self.output_dir.join(format!("{name}-server"));
+            self.run("--server", &server)?;
+            pkg.server = Some(server);
+        }
+        Ok(pkg)
+    }
 
+    /// Arguments for the typed options, passed before the extra arguments
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.safe {
+            args.push("--safe".to_string());
+        }
+
+        if self.werror {
+            args.push("--Werror".to_string());
+        }
+
+        if !self.warnings {
+            args.push("-w".to_string());
+        }
+
+        for name in &self.entry_points {
+            args.push(format!("--entry-point={name}"));
+        }
+        args
+    }
+
+    fn run(&self, mode: &str, output: &std::path::Path) -> Result<(), Error> {
         let ok = std::process::Command::new(&self.exe)
             .arg(self.backend.to_str())
+            .args(self.args())
             .args(&self.extra_args)
             .args(["-o", &output.to_string_lossy()])
-            .arg("--lib")
+            .arg(mode)
             .arg(&self.src)
             .status()?
             .success();
@@ -68,16 +294,61 @@ impl Compiler {
         if !ok {
             return Err(Error::CompilationFailed);
         }
+        Ok(())
+    }
+}
 
-        // Load manifest after successful compilation
-        let manifest = Manifest::parse_file(output.with_extension("json"))?;
-        let c_file = output.with_extension("c");
-        let h_file = output.with_extension("h");
-        Ok(Package {
-            manifest,
-            c_file,
-            h_file,
-            src: self.src.clone(),
-        })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_required_packages() {
+        let dir = std::env::temp_dir().join(format!("futhark-bindgen-pkg-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pkg_file = dir.join("futhark.pkg");
+        std::fs::write(
+            &pkg_file,
+            "package github.com/user/app\n\n\
+             require {\n\
+             \x20 github.com/diku-dk/sorts 0.4.1 #2b6c6b3  -- sorting\n\
+             \x20 -- github.com/diku-dk/linalg 0.4.0 #abc\n\
+             \n\
+             \x20 github.com/diku-dk/segmented   0.3.0 #d5b4bc0\n\
+             }\n",
+        )
+        .unwrap();
+        let packages = required_packages(&pkg_file).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            packages,
+            vec![
+                Requirement {
+                    path: "github.com/diku-dk/sorts".to_string(),
+                    version: "0.4.1 #2b6c6b3".to_string(),
+                },
+                Requirement {
+                    path: "github.com/diku-dk/segmented".to_string(),
+                    version: "0.3.0 #d5b4bc0".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            sync_stamp(&packages),
+            "github.com/diku-dk/sorts 0.4.1 #2b6c6b3\ngithub.com/diku-dk/segmented 0.3.0 #d5b4bc0\n"
+        );
+    }
+
+    #[test]
+    fn no_required_packages() {
+        let dir =
+            std::env::temp_dir().join(format!("futhark-bindgen-nopkg-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pkg_file = dir.join("futhark.pkg");
+        std::fs::write(&pkg_file, "package github.com/user/app\n\nrequire {\n}\n").unwrap();
+        let packages = required_packages(&pkg_file).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(packages.is_empty());
     }
 }
@@ -0,0 +1,882 @@
+use crate::manifest::ElemType;
+use crate::*;
+
+/// Elements of a [`Value`], in row-major order
+#[derive(Debug, Clone, PartialEq)]
+pub enum Values {
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+
+    /// `f16` values are stored using their bit representation
+    F16(Vec<u16>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    Bool(Vec<bool>),
+}
+
+/// Scalar or array value in the Futhark data format, used by `futhark test`, `futhark bench`
+/// and server mode
+#[derive(Debug, Clone, PartialEq)]
+pub struct Value {
+    shape: Vec<i64>,
+    values: Values,
+}
+
+macro_rules! values_from {
+    ($($variant:ident: $t:ty),*) => {
+        $(
+            impl From<Vec<$t>> for Values {
+                fn from(v: Vec<$t>) -> Values {
+                    Values::$variant(v)
+                }
+            }
+        )*
+    };
+}
+
+values_from!(
+    I8: i8,
+    I16: i16,
+    I32: i32,
+    I64: i64,
+    U8: u8,
+    U16: u16,
+    U32: u32,
+    U64: u64,
+    F32: f32,
+    F64: f64,
+    Bool: bool
+);
+
+fn data_error(msg: impl Into<String>) -> Error {
+    Error::Data(msg.into())
+}
+
+/// Number of elements in an array with the given shape, `None` when a dimension is negative or
+/// the number of elements overflows
+fn elements(shape: &[i64]) -> Option<usize> {
+    shape
+        .iter()
+        .try_fold(1usize, |n, d| n.checked_mul(usize::try_from(*d).ok()?))
+}
+
+fn extend_le<T: Copy, const N: usize>(out: &mut Vec<u8>, values: &[T], f: fn(T) -> [u8; N]) {
+    for x in values {
+        out.extend_from_slice(&f(*x));
+    }
+}
+
+fn decode_le<T, const N: usize>(bytes: &[u8], f: fn([u8; N]) -> T) -> Vec<T> {
+    bytes
+        .chunks_exact(N)
+        .map(|b| f(b.try_into().unwrap()))
+        .collect()
+}
+
+/// Convert the bit representation of an `f16` value to `f32`
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits >> 15) as u32) << 31;
+    let exp = ((bits >> 10) & 0x1f) as u32;
+    let mant = (bits & 0x3ff) as u32;
+    let bits = match exp {
+        0 if mant == 0 => sign,
+        0 => {
+            // Subnormal values are multiples of 2^-24
+            let x = mant as f32 / 16777216.0;
+            return if sign != 0 { -x } else { x };
+        }
+        31 => sign | 0x7f80_0000 | (mant << 13),
+        _ => sign | ((exp + 112) << 23) | (mant << 13),
+    };
+    f32::from_bits(bits)
+}
+
+/// Convert an `f32` value to the bit representation of the nearest `f16` value
+pub fn f32_to_f16(x: f32) -> u16 {
+    let bits = x.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mant = bits & 0x7f_ffff;
+    if exp == 255 {
+        return sign | 0x7c00 | if mant != 0 { 0x200 } else { 0 };
+    }
+
+    let e = exp - 127 + 15;
+    if e >= 31 {
+        return sign | 0x7c00;
+    }
+
+    // Round to nearest, ties to even
+    if e <= 0 {
+        if e < -10 {
+            return sign;
+        }
+        let m = mant | 0x80_0000;
+        let shift = (14 - e) as u32;
+        let rounded = (m + (1 << (shift - 1)) - 1 + ((m >> shift) & 1)) >> shift;
+        return sign | rounded as u16;
+    }
+    let rounded = mant + 0xfff + ((mant >> 13) & 1);
+    let out = ((e as u32) << 10) + (rounded >> 13);
+    sign | out.min(0x7c00) as u16
+}
+
+fn float_text(text: String, nan: bool, inf: bool, negative: bool, ty: &str) -> String {
+    if nan {
+        format!("{ty}.nan")
+    } else if inf && negative {
+        format!("-{ty}.inf")
+    } else if inf {
+        format!("{ty}.inf")
+    } else {
+        format!("{text}{ty}")
+    }
+}
+
+impl Values {
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        match self {
+            Values::I8(v) => v.len(),
+            Values::I16(v) => v.len(),
+            Values::I32(v) => v.len(),
+            Values::I64(v) => v.len(),
+            Values::U8(v) => v.len(),
+            Values::U16(v) | Values::F16(v) => v.len(),
+            Values::U32(v) => v.len(),
+            Values::U64(v) => v.len(),
+            Values::F32(v) => v.len(),
+            Values::F64(v) => v.len(),
+            Values::Bool(v) => v.len(),
+        }
+    }
+
+    /// Returns true when there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Element type
+    pub fn elemtype(&self) -> ElemType {
+        match self {
+            Values::I8(_) => ElemType::I8,
+            Values::I16(_) => ElemType::I16,
+            Values::I32(_) => ElemType::I32,
+            Values::I64(_) => ElemType::I64,
+            Values::U8(_) => ElemType::U8,
+            Values::U16(_) => ElemType::U16,
+            Values::U32(_) => ElemType::U32,
+            Values::U64(_) => ElemType::U64,
+            Values::F16(_) => ElemType::F16,
+            Values::F32(_) => ElemType::F32,
+            Values::F64(_) => ElemType::F64,
+            Values::Bool(_) => ElemType::Bool,
+        }
+    }
+
+    fn write_le(&self, out: &mut Vec<u8>) {
+        match self {
+            Values::I8(v) => extend_le(out, v, i8::to_le_bytes),
+            Values::I16(v) => extend_le(out, v, i16::to_le_bytes),
+            Values::I32(v) => extend_le(out, v, i32::to_le_bytes),
+            Values::I64(v) => extend_le(out, v, i64::to_le_bytes),
+            Values::U8(v) => out.extend_from_slice(v),
+            Values::U16(v) | Values::F16(v) => extend_le(out, v, u16::to_le_bytes),
+            Values::U32(v) => extend_le(out, v, u32::to_le_bytes),
+            Values::U64(v) => extend_le(out, v, u64::to_le_bytes),
+            Values::F32(v) => extend_le(out, v, f32::to_le_bytes),
+            Values::F64(v) => extend_le(out, v, f64::to_le_bytes),
+            Values::Bool(v) => out.extend(v.iter().map(|x| *x as u8)),
+        }
+    }
+
+    fn read_le(elemtype: ElemType, bytes: &[u8]) -> Values {
+        match elemtype {
+            ElemType::I8 => Values::I8(decode_le(bytes, i8::from_le_bytes)),
+            ElemType::I16 => Values::I16(decode_le(bytes, i16::from_le_bytes)),
+            ElemType::I32 => Values::I32(decode_le(bytes, i32::from_le_bytes)),
+            ElemType::I64 => Values::I64(decode_le(bytes, i64::from_le_bytes)),
+            ElemType::U8 => Values::U8(bytes.to_vec()),
+            ElemType::U16 => Values::U16(decode_le(bytes, u16::from_le_bytes)),
+            ElemType::U32 => Values::U32(decode_le(bytes, u32::from_le_bytes)),
+            ElemType::U64 => Values::U64(decode_le(bytes, u64::from_le_bytes)),
+            ElemType::F16 => Values::F16(decode_le(bytes, u16::from_le_bytes)),
+            ElemType::F32 => Values::F32(decode_le(bytes, f32::from_le_bytes)),
+            ElemType::F64 => Values::F64(decode_le(bytes, f64::from_le_bytes)),
+            ElemType::Bool => Values::Bool(bytes.iter().map(|b| *b != 0).collect()),
+        }
+    }
+
+    /// Format each element using the text format
+    fn to_text(&self) -> Vec<String> {
+        fn ints<T: std::fmt::Display>(v: &[T], ty: &str) -> Vec<String> {
+            v.iter().map(|x| format!("{x}{ty}")).collect()
+        }
+
+        match self {
+            Values::I8(v) => ints(v, "i8"),
+            Values::I16(v) => ints(v, "i16"),
+            Values::I32(v) => ints(v, "i32"),
+            Values::I64(v) => ints(v, "i64"),
+            Values::U8(v) => ints(v, "u8"),
+            Values::U16(v) => ints(v, "u16"),
+            Values::U32(v) => ints(v, "u32"),
+            Values::U64(v) => ints(v, "u64"),
+            Values::F16(v) => v
+                .iter()
+                .map(|x| {
+                    let x = f16_to_f32(*x);
+                    float_text(
+                        format!("{x:?}"),
+                        x.is_nan(),
+                        x.is_infinite(),
+                        x < 0.0,
+                        "f16",
+                    )
+                })
+                .collect(),
+            Values::F32(v) => v
+                .iter()
+                .map(|x| {
+                    float_text(
+                        format!("{x:?}"),
+                        x.is_nan(),
+                        x.is_infinite(),
+                        *x < 0.0,
+                        "f32",
+                    )
+                })
+                .collect(),
+            Values::F64(v) => v
+                .iter()
+                .map(|x| {
+                    float_text(
+                        format!("{x:?}"),
+                        x.is_nan(),
+                        x.is_infinite(),
+                        *x < 0.0,
+                        "f64",
+                    )
+                })
+                .collect(),
+            Values::Bool(v) => v.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+}
+
+impl Value {
+    /// Create a new value, `shape` is empty for scalars
+    pub fn new(shape: Vec<i64>, values: impl Into<Values>) -> Result<Value, Error> {
+        let values = values.into();
+        if elements(&shape) != Some(values.len()) {
+            return Err(data_error(format!(
+                "shape {shape:?} doesn't match the number of elements: {}",
+                values.len()
+            )));
+        }
+        if shape.len() > u8::MAX as usize {
+            return Err(data_error("rank is too large"));
+        }
+        Ok(Value { shape, values })
+    }
+
+    /// Array dimensions, empty for scalars
+    pub fn shape(&self) -> &[i64] {
+        &self.shape
+    }
+
+    /// Number of dimensions
+    pub fn rank(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Element type
+    pub fn elemtype(&self) -> ElemType {
+        self.values.elemtype()
+    }
+
+    /// Get the elements
+    pub fn values(&self) -> &Values {
+        &self.values
+    }
+
+    /// Convert the value into its elements
+    pub fn into_values(self) -> Values {
+        self.values
+    }
+
+    /// Encode the value using the binary format
+    pub fn to_binary(&self) -> Vec<u8> {
+        let elemtype = self.elemtype();
+        let mut out =
+            Vec::with_capacity(7 + self.shape.len() * 8 + self.values.len() * elemtype.size());
+        out.extend_from_slice(&[b'b', 2, self.shape.len() as u8]);
+        out.extend_from_slice(elemtype.binary_name().as_bytes());
+        extend_le(&mut out, &self.shape, i64::to_le_bytes);
+        self.values.write_le(&mut out);
+        out
+    }
+}
+
+fn write_nested(
+    f: &mut std::fmt::Formatter<'_>,
+    shape: &[i64],
+    elems: &[String],
+) -> std::fmt::Result {
+    let (_, inner) = match shape.split_first() {
+        Some(x) => x,
+        None => return f.write_str(&elems[0]),
+    };
+    let chunk = inner.iter().product::<i64>() as usize;
+    f.write_str("[")?;
+    for (i, part) in elems.chunks(chunk).enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write_nested(f, inner, part)?;
+    }
+    f.write_str("]")
+}
+
+/// Formats the value using the text format
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.values.is_empty() {
+            f.write_str("empty(")?;
+            for d in &self.shape {
+                write!(f, "[{d}]")?;
+            }
+            return write!(f, "{})", self.elemtype().to_str());
+        }
+        write_nested(f, &self.shape, &self.values.to_text())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Int,
+    Float,
+    Bool,
+    Nan,
+    Inf,
+}
+
+/// Scalar literal in the text format
+struct Literal {
+    text: String,
+    kind: Kind,
+    elemtype: Option<ElemType>,
+}
+
+enum Tree {
+    Scalar(Literal),
+    Array(Vec<Tree>),
+    Empty(Vec<i64>, ElemType),
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> Error {
+        data_error(format!("{msg} at byte {}", self.pos))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.input[self.pos..]
+    }
+
+    /// Skip whitespace and `--` comments
+    fn skip_space(&mut self) {
+        loop {
+            while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+                self.pos += 1;
+            }
+            if !self.rest().starts_with(b"--") {
+                break;
+            }
+            while self.peek().is_some_and(|c| c != b'\n') {
+                self.pos += 1;
+            }
+        }
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        self.skip_space();
+        if self.rest().starts_with(s.as_bytes()) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), Error> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{s}`")))
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&f) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.pos]).unwrap()
+    }
+
+    fn binary(&mut self) -> Result<Value, Error> {
+        let header = self.rest();
+        if header.len() < 7 || header[1] != 2 {
+            return Err(self.error("invalid binary value header"));
+        }
+        let rank = header[2] as usize;
+        let elemtype = std::str::from_utf8(&header[3..7])
+            .ok()
+            .and_then(|name| ElemType::from_name(name.trim_start()))
+            .ok_or_else(|| self.error("invalid binary value type"))?;
+        if header.len() < 7 + rank * 8 {
+            return Err(self.error("truncated binary value shape"));
+        }
+        let shape = decode_le(&header[7..7 + rank * 8], i64::from_le_bytes);
+        if shape.iter().any(|d| *d < 0) {
+            return Err(self.error("negative dimension in binary value"));
+        }
+        let start = 7 + rank * 8;
+        let end = elements(&shape)
+            .and_then(|n| n.checked_mul(elemtype.size()))
+            .and_then(|n| n.checked_add(start))
+            .ok_or_else(|| self.error("binary value is too large"))?;
+        if header.len() < end {
+            return Err(self.error("truncated binary value"));
+        }
+        let values = Values::read_le(elemtype, &header[start..end]);
+        self.pos += end;
+        Ok(Value { shape, values })
+    }
+
+    fn tree(&mut self) -> Result<Tree, Error> {
+        if self.eat("[") {
+            let mut elems = vec![self.tree()?];
+            while self.eat(",") {
+                elems.push(self.tree()?);
+            }
+            self.expect("]")?;
+            return Ok(Tree::Array(elems));
+        }
+
+        if self.eat("empty") {
+            self.expect("(")?;
+            let mut shape = Vec::new();
+            while self.eat("[") {
+                self.skip_space();
+                let dim = self.take_while(|c| c.is_ascii_digit());
+                shape.push(dim.parse().map_err(|_| self.error("invalid dimension"))?);
+                self.expect("]")?;
+            }
+            self.skip_space();
+            let name = self.take_while(|c| c.is_ascii_alphanumeric());
+            let elemtype = ElemType::from_name(name).ok_or_else(|| self.error("invalid type"))?;
+            self.expect(")")?;
+            return Ok(Tree::Empty(shape, elemtype));
+        }
+
+        for b in ["true", "false"] {
+            if self.eat(b) {
+                return Ok(Tree::Scalar(Literal {
+                    text: b.to_string(),
+                    kind: Kind::Bool,
+                    elemtype: Some(ElemType::Bool),
+                }));
+            }
+        }
+
+        self.skip_space();
+        let negative = self.eat("-");
+        self.skip_space();
+
+        // `f32.nan`, `f64.inf`, ...
+        if self.peek() == Some(b'f') {
+            let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == b'.');
+            let (ty, value) = name
+                .split_once('.')
+                .ok_or_else(|| self.error("invalid value"))?;
+            let elemtype = ElemType::from_name(ty)
+                .filter(|t| matches!(t, ElemType::F16 | ElemType::F32 | ElemType::F64))
+                .ok_or_else(|| self.error("invalid float type"))?;
+            let kind = match value {
+                "nan" => Kind::Nan,
+                "inf" => Kind::Inf,
+                _ => return Err(self.error("invalid float constant")),
+            };
+            return Ok(Tree::Scalar(Literal {
+                text: if negative { "-" } else { "" }.to_string(),
+                kind,
+                elemtype: Some(elemtype),
+            }));
+        }
+
+        let mut text = if negative {
+            "-".to_string()
+        } else {
+            String::new()
+        };
+        let mut kind = Kind::Int;
+        if self.rest().starts_with(b"0x") || self.rest().starts_with(b"0X") {
+            self.pos += 2;
+            text.push_str("0x");
+            text.push_str(self.take_while(|c| c.is_ascii_hexdigit() || c == b'_'));
+        } else {
+            text.push_str(self.take_while(|c| c.is_ascii_digit() || c == b'_'));
+            if self.peek() == Some(b'.') {
+                self.pos += 1;
+                kind = Kind::Float;
+                text.push('.');
+                text.push_str(self.take_while(|c| c.is_ascii_digit() || c == b'_'));
+            }
+            if matches!(self.peek(), Some(b'e') | Some(b'E')) {
+                self.pos += 1;
+                kind = Kind::Float;
+                text.push('e');
+                if let Some(sign @ (b'+' | b'-')) = self.peek() {
+                    self.pos += 1;
+                    text.push(sign as char);
+                }
+                text.push_str(self.take_while(|c| c.is_ascii_digit()));
+            }
+        }
+        let text = text.replace('_', "");
+        if !text.bytes().any(|c| c.is_ascii_digit()) {
+            return Err(self.error("expected a value"));
+        }
+
+        let elemtype = if matches!(self.peek(), Some(b'i' | b'u' | b'f')) {
+            let name = self.take_while(|c| c.is_ascii_alphanumeric());
+            match ElemType::from_name(name) {
+                Some(t) if t != ElemType::Bool => Some(t),
+                _ => return Err(self.error("invalid type suffix")),
+            }
+        } else {
+            None
+        };
+        Ok(Tree::Scalar(Literal {
+            text,
+            kind,
+            elemtype,
+        }))
+    }
+
+    fn text(&mut self) -> Result<Value, Error> {
+        let tree = self.tree()?;
+        let shape = tree_shape(&tree).ok_or_else(|| self.error("irregular array"))?;
+        let mut literals = Vec::new();
+        let mut elemtype = None;
+        collect(&tree, &mut literals, &mut elemtype).map_err(|msg| self.error(&msg))?;
+        let elemtype = elemtype.unwrap_or_else(|| {
+            if literals.iter().any(|l| l.kind == Kind::Float) {
+                ElemType::F64
+            } else {
+                ElemType::I32
+            }
+        });
+        let values = convert(&literals, elemtype).map_err(|msg| self.error(&msg))?;
+        if elements(&shape) != Some(values.len()) {
+            return Err(self.error("shape doesn't match the number of elements"));
+        }
+        Ok(Value { shape, values })
+    }
+}
+
+fn tree_shape(tree: &Tree) -> Option<Vec<i64>> {
+    match tree {
+        Tree::Scalar(_) => Some(Vec::new()),
+        Tree::Empty(shape, _) => Some(shape.clone()),
+        Tree::Array(elems) => {
+            let inner = tree_shape(&elems[0])?;
+            for elem in &elems[1..] {
+                if tree_shape(elem)? != inner {
+                    return None;
+                }
+            }
+            let mut shape = vec![elems.len() as i64];
+            shape.extend(inner);
+            Some(shape)
+        }
+    }
+}
+
+fn collect<'a>(
+    tree: &'a Tree,
+    literals: &mut Vec<&'a Literal>,
+    elemtype: &mut Option<ElemType>,
+) -> Result<(), String> {
+    let mut set_type = |t: ElemType| match elemtype {
+        Some(e) if *e != t => Err(format!(
+            "mixed element types: {} and {}",
+            e.to_str(),
+            t.to_str()
+        )),
+        _ => {
+            *elemtype = Some(t);
+            Ok(())
+        }
+    };
+    match tree {
+        Tree::Scalar(l) => {
+            if let Some(t) = l.elemtype {
+                set_type(t)?;
+            }
+            literals.push(l);
+        }
+        Tree::Empty(_, t) => set_type(*t)?,
+        Tree::Array(elems) => {
+            for elem in elems {
+                collect(elem, literals, elemtype)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_int<T: TryFrom<i128>>(l: &Literal) -> Result<T, String> {
+    let (negative, digits) = match l.text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, l.text.as_str()),
+    };
+    let n = match digits.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16),
+        None => digits.parse::<i128>(),
+    }
+    .map_err(|_| format!("invalid integer: {}", l.text))?;
+    let n = if negative { -n } else { n };
+    T::try_from(n).map_err(|_| format!("integer out of range: {}", l.text))
+}
+
+fn parse_float(l: &Literal) -> Result<f64, String> {
+    let negative = l.text.starts_with('-');
+    match l.kind {
+        Kind::Nan => Ok(f64::NAN),
+        Kind::Inf if negative => Ok(f64::NEG_INFINITY),
+        Kind::Inf => Ok(f64::INFINITY),
+        Kind::Int if l.text.contains("0x") => parse_int::<i64>(l).map(|n| n as f64),
+        Kind::Int | Kind::Float => l
+            .text
+            .parse()
+            .map_err(|_| format!("invalid float: {}", l.text)),
+        Kind::Bool => Err(format!("expected a number: {}", l.text)),
+    }
+}
+
+fn convert(literals: &[&Literal], elemtype: ElemType) -> Result<Values, String> {
+    fn ints<T: TryFrom<i128>>(literals: &[&Literal]) -> Result<Vec<T>, String> {
+        literals
+            .iter()
+            .map(|l| match l.kind {
+                Kind::Int => parse_int(l),
+                _ => Err(format!("expected an integer: {}", l.text)),
+            })
+            .collect()
+    }
+
+    fn floats(literals: &[&Literal]) -> Result<Vec<f64>, String> {
+        literals.iter().map(|l| parse_float(l)).collect()
+    }
+
+    Ok(match elemtype {
+        ElemType::I8 => Values::I8(ints(literals)?),
+        ElemType::I16 => Values::I16(ints(literals)?),
+        ElemType::I32 => Values::I32(ints(literals)?),
+        ElemType::I64 => Values::I64(ints(literals)?),
+        ElemType::U8 => Values::U8(ints(literals)?),
+        ElemType::U16 => Values::U16(ints(literals)?),
+        ElemType::U32 => Values::U32(ints(literals)?),
+        ElemType::U64 => Values::U64(ints(literals)?),
+        ElemType::F16 => Values::F16(
+            floats(literals)?
+                .into_iter()
+                .map(|x| f32_to_f16(x as f32))
+                .collect(),
+        ),
+        ElemType::F32 => Values::F32(
+            literals
+                .iter()
+                .map(|l| match l.kind {
+                    // Parse directly as `f32` to avoid rounding twice
+                    Kind::Float => l
+                        .text
+                        .parse()
+                        .map_err(|_| format!("invalid float: {}", l.text)),
+                    _ => parse_float(l).map(|x| x as f32),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        ElemType::F64 => Values::F64(floats(literals)?),
+        ElemType::Bool => Values::Bool(
+            literals
+                .iter()
+                .map(|l| match l.kind {
+                    Kind::Bool => Ok(l.text == "true"),
+                    _ => Err(format!("expected a boolean: {}", l.text)),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+    })
+}
+
+/// Parse all values in `input`, values can be in either the binary or text format
+pub fn parse(input: &[u8]) -> Result<Vec<Value>, Error> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut values = Vec::new();
+    loop {
+        parser.skip_space();
+        match parser.peek() {
+            None => break,
+            Some(b'b') => values.push(parser.binary()?),
+            Some(_) => values.push(parser.text()?),
+        }
+    }
+    Ok(values)
+}
+
+/// Read all values from `reader`, values can be in either the binary or text format
+pub fn read(mut reader: impl std::io::Read) -> Result<Vec<Value>, Error> {
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    parse(&input)
+}
+
+/// Read all values from a file, for example the `.in` and `.out` files used by `futhark test`
+pub fn read_file(path: impl AsRef<std::path::Path>) -> Result<Vec<Value>, Error> {
+    parse(&std::fs::read(path)?)
+}
+
+/// Write values using the binary format
+pub fn write_binary(mut writer: impl std::io::Write, values: &[Value]) -> Result<(), Error> {
+    for value in values {
+        writer.write_all(&value.to_binary())?;
+    }
+    Ok(())
+}
+
+/// Write values using the text format, one value per line
+pub fn write_text(mut writer: impl std::io::Write, values: &[Value]) -> Result<(), Error> {
+    for value in values {
+        writeln!(writer, "{value}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary_header(rank: u8, ty: &str, shape: &[i64]) -> Vec<u8> {
+        let mut out = vec![b'b', 2, rank];
+        out.extend_from_slice(ty.as_bytes());
+        extend_le(&mut out, shape, i64::to_le_bytes);
+        out
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let input = "[[1i32, 2i32], [3i32, 4i32]]\n-- comment\n2.5f64\ntrue\nempty([0][3]u8)\n";
+        let values = parse(input.as_bytes()).unwrap();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[0].shape(), &[2, 2]);
+        assert_eq!(values[0].values(), &Values::I32(vec![1, 2, 3, 4]));
+        assert_eq!(values[1].values(), &Values::F64(vec![2.5]));
+        assert_eq!(values[2].values(), &Values::Bool(vec![true]));
+        assert_eq!(values[3].shape(), &[0, 3]);
+        assert_eq!(values[3].elemtype(), ElemType::U8);
+
+        let mut text = Vec::new();
+        write_text(&mut text, &values).unwrap();
+        assert_eq!(
+            String::from_utf8(text.clone()).unwrap(),
+            "[[1i32, 2i32], [3i32, 4i32]]\n2.5f64\ntrue\nempty([0][3]u8)\n"
+        );
+        assert_eq!(parse(&text).unwrap(), values);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let values = vec![
+            Value::new(vec![2, 3], vec![1i64, -2, 3, -4, 5, -6]).unwrap(),
+            Value::new(vec![], vec![1.5f32]).unwrap(),
+            Value::new(vec![2], Values::F16(vec![0x3c00, 0xc000])).unwrap(),
+            Value::new(vec![0], Vec::<u16>::new()).unwrap(),
+        ];
+        let mut binary = Vec::new();
+        write_binary(&mut binary, &values).unwrap();
+        assert_eq!(&binary[..7], b"b\x02\x02 i64");
+        assert_eq!(parse(&binary).unwrap(), values);
+    }
+
+    #[test]
+    fn invalid_shape() {
+        assert!(Value::new(vec![3], vec![1i32, 2]).is_err());
+        assert!(Value::new(vec![-1], Vec::<i32>::new()).is_err());
+        assert!(Value::new(vec![i64::MAX, 2], Vec::<i32>::new()).is_err());
+        assert!(parse(b"empty([3]i32)").is_err());
+        assert!(parse(b"[[1, 2], [3]]").is_err());
+    }
+
+    #[test]
+    fn truncated_input() {
+        let binary = Value::new(vec![4], vec![1i32, 2, 3, 4])
+            .unwrap()
+            .to_binary();
+        for len in 1..binary.len() {
+            assert!(matches!(parse(&binary[..len]), Err(Error::Data(_))));
+        }
+        assert!(matches!(parse(b"[1, 2"), Err(Error::Data(_))));
+    }
+
+    #[test]
+    fn binary_size_overflow() {
+        let huge = binary_header(1, " i32", &[i64::MAX]);
+        assert!(matches!(parse(&huge), Err(Error::Data(_))));
+
+        let huge = binary_header(2, " i64", &[i64::MAX, i64::MAX]);
+        assert!(matches!(parse(&huge), Err(Error::Data(_))));
+
+        let negative = binary_header(1, "  u8", &[-1]);
+        assert!(matches!(parse(&negative), Err(Error::Data(_))));
+    }
+
+    #[test]
+    fn f16_conversions() {
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16(70000.0), 0x7c00);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xfc00);
+        assert_eq!(f32_to_f16(1e-7), 0x0002);
+        assert_eq!(f32_to_f16(1e-9), 0);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0x7bff), 65504.0);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x8000), -0.0);
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+
+        for bits in (0..0x7c00).chain(0x8000..0xfc00) {
+            assert_eq!(f32_to_f16(f16_to_f32(bits)), bits);
+        }
+    }
+}
@@ -9,6 +9,27 @@ pub enum Error {
 
     /// std::io::Error
     Io(std::io::Error),
+
+    /// External generator failed
+    Generator(String),
+
+    /// Unable to find the SDK required by a backend
+    SdkNotFound(String),
+
+    /// `ispc` failed, contains its output
+    Ispc(String),
+
+    /// The version of Futhark is too old or doesn't provide a required feature
+    UnsupportedVersion(String),
+
+    /// `futhark pkg sync` failed, contains its output
+    PackageSync(String),
+
+    /// Invalid value in the Futhark data format
+    Data(String),
+
+    /// The manifest refers to a type that it doesn't define
+    UnknownType(String),
 }
 
 impl From<serde_json::Error> for Error {
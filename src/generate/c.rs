@@ -0,0 +1,265 @@
+use crate::*;
+use std::io::Write;
+
+/// C codegen, generates a header with convenience wrappers around the header generated by
+/// Futhark. Functions are prefixed using the name of the output file
+#[derive(Default)]
+pub struct C {
+    prefix: String,
+}
+
+const C_TYPE_MAP: &[(&str, &str)] = &[
+    ("i8", "int8_t"),
+    ("i16", "int16_t"),
+    ("i32", "int32_t"),
+    ("i64", "int64_t"),
+    ("u8", "uint8_t"),
+    ("u16", "uint16_t"),
+    ("u32", "uint32_t"),
+    ("u64", "uint64_t"),
+    ("f16", "uint16_t"),
+    ("f32", "float"),
+    ("f64", "double"),
+    ("bool", "bool"),
+];
+
+enum CType {
+    /// Futhark struct name and free function
+    Pointer(String, String),
+
+    /// C type
+    Scalar(&'static str),
+}
+
+fn scalar_type(t: &str) -> &'static str {
+    match C_TYPE_MAP.iter().find(|(name, _)| *name == t) {
+        Some((_, c)) => c,
+        None => panic!("Unsupported type: {t}"),
+    }
+}
+
+impl C {
+    fn get_type(pkg: &Package, t: &str) -> CType {
+        match pkg.types().resolved(t) {
+            codegen::Type::Scalar(_) => CType::Scalar(scalar_type(t)),
+            ty => CType::Pointer(
+                ty.c_name().unwrap().to_string(),
+                ty.free_fn().unwrap().to_string(),
+            ),
+        }
+    }
+
+    /// Replace the `futhark` prefix of a Futhark function name
+    fn function_name(&self, f: &str) -> String {
+        format!("{}{}", self.prefix, f.strip_prefix("futhark").unwrap_or(f))
+    }
+}
+
+impl Generate for C {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile]
+    }
+
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "// Generated by futhark-bindgen\n")?;
+
+        self.prefix = config
+            .output_path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let guard = format!("{}_H", self.prefix.to_ascii_uppercase());
+
+        let (backend_options, backend_config) = match pkg.manifest.backend {
+            Backend::Multicore => (
+                "\n  int num_threads;",
+                "\n  futhark_context_config_set_num_threads(c->config, options->num_threads);",
+            ),
+            Backend::CUDA | Backend::OpenCL => (
+                "\n  // Futhark keeps a pointer to the device name, it must outlive the context\n  const char *device;",
+                "\n  if (options->device != NULL) {\n    futhark_context_config_set_device(c->config, options->device);\n  }",
+            ),
+            _ => ("", ""),
+        };
+
+        write!(
+            config.output_file,
+            include_str!("templates/c/context.h"),
+            guard = guard,
+            prefix = self.prefix,
+            header = pkg.h_file.file_name().unwrap().to_string_lossy(),
+            backend_options = backend_options,
+            backend_config = backend_config,
+        )?;
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let array = pkg.types().resolved(name);
+        let new_dim_params: Vec<_> = (0..a.rank).map(|i| format!("int64_t dim{i}")).collect();
+        let dim_args: Vec<_> = (0..a.rank).map(|i| format!("dim{i}")).collect();
+        let futhark_type = array.c_name().unwrap();
+
+        write!(
+            config.output_file,
+            include_str!("templates/c/array.h"),
+            prefix = self.prefix,
+            suffix = futhark_type.strip_prefix("futhark_").unwrap(),
+            rank = a.rank,
+            elemtype = a.elemtype.to_str(),
+            c_elemtype = scalar_type(a.elemtype.to_str()),
+            new_dim_params = new_dim_params.join(", "),
+            dim_args = dim_args.join(", "),
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let opaque = pkg.types().resolved(name);
+        let futhark_type = opaque.c_name().unwrap();
+        let suffix = futhark_type.strip_prefix("futhark_").unwrap();
+
+        write!(
+            config.output_file,
+            include_str!("templates/c/opaque.h"),
+            prefix = self.prefix,
+            suffix = suffix,
+            name = name,
+        )?;
+
+        let record = match &ty.record {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        let mut new_params = Vec::new();
+        let mut new_args = Vec::new();
+
+        for field in &record.fields {
+            let param = format!("field{}", field.name);
+            let (out_param, free_out) = match Self::get_type(pkg, &field.r#type) {
+                CType::Pointer(s, free) => {
+                    new_params.push(format!("const struct {s} *{param}"));
+                    (
+                        format!("struct {s} **out"),
+                        format!("    {free}(c->ctx, *out);\n    *out = NULL;\n"),
+                    )
+                }
+                CType::Scalar(t) => {
+                    new_params.push(format!("{t} {param}"));
+                    (format!("{t} *out"), String::new())
+                }
+            };
+            new_args.push(param);
+
+            write!(
+                config.output_file,
+                include_str!("templates/c/record_project.h"),
+                prefix = self.prefix,
+                suffix = suffix,
+                name = name,
+                field = field.name,
+                project_name = self.function_name(&field.project),
+                project_fn = field.project,
+                out_param = out_param,
+                free_out = free_out,
+            )?;
+        }
+
+        write!(
+            config.output_file,
+            include_str!("templates/c/record.h"),
+            prefix = self.prefix,
+            suffix = suffix,
+            name = name,
+            new_name = self.function_name(&record.new),
+            new_fn = record.new,
+            new_params = new_params.join(", "),
+            new_args = new_args.join(", "),
+        )?;
+
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut params = String::new();
+        let mut call_args = String::new();
+        let mut clear_outputs = String::new();
+        let mut free_outputs = String::new();
+
+        for (i, out) in entry.outputs.iter().enumerate() {
+            let name = format!("out{i}");
+            call_args += &format!(", {name}");
+            match Self::get_type(pkg, &out.r#type) {
+                CType::Pointer(s, free) => {
+                    params += &format!("struct {s} **{name}, ");
+                    clear_outputs += &format!("    *{name} = NULL;\n");
+                    free_outputs += &format!("    {free}(c->ctx, *{name});\n    *{name} = NULL;\n");
+                }
+                CType::Scalar(t) => {
+                    params += &format!("{t} *{name}, ");
+                }
+            }
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let name = format!("input{i}");
+            call_args += &format!(", {name}");
+            match Self::get_type(pkg, &input.r#type) {
+                CType::Pointer(s, _) => {
+                    params += &format!("const struct {s} *{name}, ");
+                }
+                CType::Scalar(t) => {
+                    params += &format!("{t} {name}, ");
+                }
+            }
+        }
+
+        // Only free the outputs on failure when there is something to free
+        if !free_outputs.is_empty() {
+            free_outputs = format!("  if (rc != 0) {{\n{free_outputs}  }}\n");
+        }
+
+        write!(
+            config.output_file,
+            include_str!("templates/c/entry.h"),
+            prefix = self.prefix,
+            name = name,
+            entry_name = self.function_name(&entry.cfun),
+            entry_fn = entry.cfun,
+            params = params,
+            call_args = call_args,
+            clear_outputs = clear_outputs,
+            free_outputs = free_outputs,
+        )?;
+
+        Ok(())
+    }
+
+    fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
+        let mut out = std::fs::OpenOptions::new().append(true).open(path)?;
+        writeln!(out, "\n#ifdef __cplusplus\n}}\n#endif\n\n#endif")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+use crate::codegen::first_uppercase;
+use crate::*;
+use std::io::Write;
+
+/// C# codegen, generates P/Invoke bindings for a shared library named `lib<output name>`
+#[derive(Default)]
+pub struct CSharp;
+
+/// Futhark type, C# type and the type used in `DllImport` declarations
+///
+/// `f16` values are represented by their bit pattern
+const CSHARP_TYPE_MAP: &[(&str, &str, &str)] = &[
+    ("i8", "sbyte", "sbyte"),
+    ("i16", "short", "short"),
+    ("i32", "int", "int"),
+    ("i64", "long", "long"),
+    ("u8", "byte", "byte"),
+    ("u16", "ushort", "ushort"),
+    ("u32", "uint", "uint"),
+    ("u64", "ulong", "ulong"),
+    ("f16", "ushort", "ushort"),
+    ("f32", "float", "float"),
+    ("f64", "double", "double"),
+    ("bool", "bool", "byte"),
+];
+
+enum CSharpType {
+    Array(String),
+    Opaque(String),
+
+    /// C# type and native type
+    Scalar(&'static str, &'static str),
+}
+
+fn scalar_type(t: &str) -> (&'static str, &'static str) {
+    match CSHARP_TYPE_MAP.iter().find(|(name, _, _)| *name == t) {
+        Some((_, cs, native)) => (cs, native),
+        None => panic!("Unsupported type: {t}"),
+    }
+}
+
+/// Convert a C# scalar value into the value passed to Futhark
+fn to_native(cs: &str, expr: &str) -> String {
+    if cs == "bool" {
+        format!("({expr} ? (byte)1 : (byte)0)")
+    } else {
+        expr.to_string()
+    }
+}
+
+/// Convert a scalar value returned by Futhark into a C# value
+fn from_native(cs: &str, expr: &str) -> String {
+    if cs == "bool" {
+        format!("{expr} != 0")
+    } else {
+        expr.to_string()
+    }
+}
+
+/// Convert `snake_case` names into `PascalCase` names
+fn pascal_case(s: &str) -> String {
+    s.split('_').map(first_uppercase).collect()
+}
+
+impl CSharp {
+    fn get_type(pkg: &Package, t: &str) -> CSharpType {
+        match pkg.types().resolved(t) {
+            codegen::Type::Scalar(_) => {
+                let (cs, native) = scalar_type(t);
+                CSharpType::Scalar(cs, native)
+            }
+            codegen::Type::Array(a) => CSharpType::Array(a.type_name()),
+            ty => CSharpType::Opaque(ty.type_name().unwrap()),
+        }
+    }
+}
+
+impl Generate for CSharp {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "// Generated by futhark-bindgen\n")?;
+
+        let library_name: String = config
+            .output_path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let namespace = pascal_case(&library_name);
+
+        let (backend_options, backend_imports, backend_config) = match pkg.manifest.backend {
+            Backend::Multicore => (
+                "\n\n    public int NumThreads { get; set; }",
+                "\n    [DllImport(Lib)]\n    internal static extern void futhark_context_config_set_num_threads(IntPtr cfg, int n);\n",
+                "\n        Native.futhark_context_config_set_num_threads(config, options.NumThreads);",
+            ),
+            Backend::CUDA | Backend::OpenCL => (
+                "\n\n    /// <summary>Name of the device to use</summary>\n    public string? Device { get; set; }",
+                "\n    [DllImport(Lib)]\n    internal static extern void futhark_context_config_set_device(IntPtr cfg, IntPtr device);\n",
+                "\n        if (options.Device != null)\n        {\n            device = Marshal.StringToCoTaskMemUTF8(options.Device);\n            Native.futhark_context_config_set_device(config, device);\n        }",
+            ),
+            _ => ("", "", ""),
+        };
+
+        write!(
+            config.output_file,
+            include_str!("templates/csharp/context.cs"),
+            namespace = namespace,
+            library_name = library_name,
+            backend_options = backend_options,
+            backend_imports = backend_imports,
+            backend_config = backend_config,
+        )?;
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let array = pkg.types().resolved(name);
+        let elemtype = a.elemtype.to_str();
+        let (cs_elemtype, native_elemtype) = scalar_type(elemtype);
+        let (native_data, native_output) = if cs_elemtype == native_elemtype {
+            ("data".to_string(), "output".to_string())
+        } else {
+            (
+                format!("MemoryMarshal.Cast<{cs_elemtype}, {native_elemtype}>(data)"),
+                format!("MemoryMarshal.Cast<{cs_elemtype}, {native_elemtype}>(output)"),
+            )
+        };
+
+        let new_dim_params: Vec<_> = (0..a.rank).map(|i| format!("long dim{i}")).collect();
+        let dim_args: Vec<_> = (0..a.rank).map(|i| format!("dim{i}")).collect();
+
+        write!(
+            config.output_file,
+            include_str!("templates/csharp/array.cs"),
+            cs_type = array.type_name().unwrap(),
+            rank = a.rank,
+            elemtype = elemtype,
+            cs_elemtype = cs_elemtype,
+            native_elemtype = native_elemtype,
+            native_data = native_data,
+            native_output = native_output,
+            new_fn = a.ops.new,
+            free_fn = a.ops.free,
+            values_fn = a.ops.values,
+            shape_fn = a.ops.shape,
+            new_dim_params = new_dim_params.join(", "),
+            dim_args = dim_args.join(", "),
+            dim_product = dim_args.join(" * "),
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let opaque = pkg.types().resolved(name);
+        let cs_type = opaque.type_name().unwrap();
+
+        write!(
+            config.output_file,
+            include_str!("templates/csharp/opaque.cs"),
+            cs_type = cs_type,
+            name = name,
+            free_fn = ty.ops.free,
+        )?;
+
+        let record = match &ty.record {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        let mut new_params = Vec::new();
+        let mut new_args = Vec::new();
+        let mut new_call_args = Vec::new();
+        let mut new_extern_params = Vec::new();
+        let mut project_imports = String::new();
+        let mut project_functions = String::new();
+
+        for field in &record.fields {
+            let param = format!("field{}", field.name);
+            let (cs_field_type, out_type, output) = match Self::get_type(pkg, &field.r#type) {
+                CSharpType::Array(t) | CSharpType::Opaque(t) => {
+                    new_params.push(format!("{t} {param}"));
+                    new_call_args.push(param.clone());
+                    new_extern_params.push(format!("{t} {param}"));
+                    (t.clone(), "IntPtr", format!("new {t}(Context, output)"))
+                }
+                CSharpType::Scalar(cs, native) => {
+                    new_params.push(format!("{cs} {param}"));
+                    new_call_args.push(to_native(cs, &param));
+                    new_extern_params.push(format!("{native} {param}"));
+                    (cs.to_string(), native, from_native(cs, "output"))
+                }
+            };
+            new_args.push(param);
+
+            project_imports += &format!(
+                "\n    [DllImport(Lib)]\n    internal static extern int {}(Context ctx, out {out_type} output, {cs_type} obj);\n",
+                field.project
+            );
+
+            project_functions += &format!(
+                include_str!("templates/csharp/record_project.cs"),
+                name = field.name,
+                field_name = pascal_case(&field.name),
+                cs_field_type = cs_field_type,
+                out_type = out_type,
+                project_fn = field.project,
+                output = output,
+            );
+        }
+
+        write!(
+            config.output_file,
+            include_str!("templates/csharp/record.cs"),
+            cs_type = cs_type,
+            new_fn = record.new,
+            new_params = new_params.join(", "),
+            new_args = new_args.join(", "),
+            new_call_args = new_call_args.join(", "),
+            new_extern_params = new_extern_params.join(", "),
+            project_imports = project_imports,
+            project_functions = project_functions,
+        )?;
+
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut extern_params = vec!["Context ctx".to_string()];
+        let mut entry_params = Vec::new();
+        let mut call_args = vec!["this".to_string()];
+        let mut return_type = Vec::new();
+        let mut entry_return = Vec::new();
+
+        for (i, out) in entry.outputs.iter().enumerate() {
+            let name = format!("out{i}");
+            match Self::get_type(pkg, &out.r#type) {
+                CSharpType::Array(t) | CSharpType::Opaque(t) => {
+                    extern_params.push(format!("out IntPtr {name}"));
+                    call_args.push(format!("out IntPtr {name}"));
+                    entry_return.push(format!("new {t}(this, {name})"));
+                    return_type.push(t);
+                }
+                CSharpType::Scalar(cs, native) => {
+                    extern_params.push(format!("out {native} {name}"));
+                    call_args.push(format!("out {native} {name}"));
+                    entry_return.push(from_native(cs, &name));
+                    return_type.push(cs.to_string());
+                }
+            }
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let name = format!("input{i}");
+            match Self::get_type(pkg, &input.r#type) {
+                CSharpType::Array(t) | CSharpType::Opaque(t) => {
+                    extern_params.push(format!("{t} {name}"));
+                    entry_params.push(format!("{t} {name}"));
+                    call_args.push(name);
+                }
+                CSharpType::Scalar(cs, native) => {
+                    extern_params.push(format!("{native} {name}"));
+                    entry_params.push(format!("{cs} {name}"));
+                    call_args.push(to_native(cs, &name));
+                }
+            }
+        }
+
+        let (return_type, entry_return) = match entry.outputs.len() {
+            0 => ("void".to_string(), "AutoSyncContext();".to_string()),
+            1 => (
+                return_type.join(", "),
+                format!(
+                    "AutoSyncContext();\n        return {};",
+                    entry_return.join(", ")
+                ),
+            ),
+            _ => (
+                format!("({})", return_type.join(", ")),
+                format!(
+                    "AutoSyncContext();\n        return ({});",
+                    entry_return.join(", ")
+                ),
+            ),
+        };
+
+        write!(
+            config.output_file,
+            include_str!("templates/csharp/entry.cs"),
+            entry_fn = entry.cfun,
+            name = name,
+            entry_name = pascal_case(name),
+            extern_params = extern_params.join(", "),
+            entry_params = entry_params.join(", "),
+            return_type = return_type,
+            call_args = call_args.join(", "),
+            entry_return = entry_return,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use crate::*;
+use std::io::Write;
+
+/// External codegen, runs a `futhark-bindgen-<name>` executable from `$PATH`
+///
+/// The executable receives a JSON object on stdin containing the `package` (the parsed
+/// `manifest` and the `c_file`, `h_file` and `src` paths), the `output` path and the codegen
+/// options. It should print a JSON object to stdout containing a `files` array, each file has
+/// a `path`, relative to the directory of the output file, and its `contents`. Failures can be
+/// reported by exiting with a non-zero status or by setting the `error` field.
+pub struct External {
+    name: String,
+}
+
+#[derive(serde::Serialize)]
+struct Request<'a> {
+    package: &'a Package,
+    output: &'a std::path::Path,
+    async_entry_points: bool,
+    pending_results: bool,
+    server_client: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct Response {
+    #[serde(default)]
+    files: Vec<OutputFile>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct OutputFile {
+    path: std::path::PathBuf,
+    contents: String,
+}
+
+impl External {
+    /// Create a new external generator, `name` is the language name used to find the
+    /// `futhark-bindgen-<name>` executable
+    pub fn new(name: impl Into<String>) -> External {
+        External { name: name.into() }
+    }
+
+    /// Name of the executable
+    pub fn executable(&self) -> String {
+        format!("futhark-bindgen-{}", self.name)
+    }
+}
+
+impl Generate for External {
+    fn generate(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        let exe = self.executable();
+        let request = serde_json::to_vec(&Request {
+            package: pkg,
+            output: &config.output_path,
+            async_entry_points: config.async_entry_points,
+            pending_results: config.pending_results,
+            server_client: config.server_client,
+        })?;
+
+        let mut child = std::process::Command::new(&exe)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::Generator(format!("unable to run {exe}: {e}")))?;
+
+        // The request is written from another thread so a generator that writes its output
+        // before reading all of its input can't fill the pipe and deadlock, dropping stdin
+        // closes the pipe so the generator sees the end of the input
+        let mut stdin = child.stdin.take().unwrap();
+        let writer = std::thread::spawn(move || stdin.write_all(&request));
+        let output = child.wait_with_output()?;
+        let written = writer.join().expect("Unable to write generator input");
+        if !output.status.success() {
+            let mut msg = format!("{exe} failed: {}", output.status);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.trim().is_empty() {
+                msg += &format!("\n{}", stderr.trim_end());
+            }
+            return Err(Error::Generator(msg));
+        }
+        written?;
+
+        let response: Response = serde_json::from_slice(&output.stdout)?;
+        if let Some(error) = response.error {
+            return Err(Error::Generator(format!("{exe}: {error}")));
+        }
+
+        let dir = config
+            .output_path
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."));
+        for file in response.files {
+            if file.path.is_absolute()
+                || file
+                    .path
+                    .components()
+                    .any(|c| c == std::path::Component::ParentDir)
+            {
+                return Err(Error::Generator(format!(
+                    "{exe}: invalid output path: {}",
+                    file.path.display()
+                )));
+            }
+            let path = dir.join(&file.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, file.contents)?;
+        }
+        Ok(())
+    }
+
+    fn bindings(&mut self, _pkg: &Package, _config: &mut Config) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        _pkg: &Package,
+        _config: &mut Config,
+        _name: &str,
+        _ty: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        _pkg: &Package,
+        _config: &mut Config,
+        _name: &str,
+        _ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        _pkg: &Package,
+        _config: &mut Config,
+        _name: &str,
+        _entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
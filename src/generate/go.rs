@@ -0,0 +1,305 @@
+use crate::codegen::first_uppercase;
+use crate::*;
+use std::io::Write;
+
+/// Go codegen, generates a cgo package that compiles the Futhark C file in the same directory
+#[derive(Default)]
+pub struct Go;
+
+const GO_TYPE_MAP: &[(&str, (&str, &str))] = &[
+    ("i8", ("int8", "C.int8_t")),
+    ("i16", ("int16", "C.int16_t")),
+    ("i32", ("int32", "C.int32_t")),
+    ("i64", ("int64", "C.int64_t")),
+    ("u8", ("uint8", "C.uint8_t")),
+    ("u16", ("uint16", "C.uint16_t")),
+    ("u32", ("uint32", "C.uint32_t")),
+    ("u64", ("uint64", "C.uint64_t")),
+    // Go has no half type, `f16` values are passed as their bit representation
+    ("f16", ("uint16", "C.uint16_t")),
+    ("f32", ("float32", "C.float")),
+    ("f64", ("float64", "C.double")),
+    ("bool", ("bool", "C.bool")),
+];
+
+enum GoType {
+    /// Go type and the name of the underlying Futhark struct
+    Array(String, String),
+
+    /// Go type and the name of the underlying Futhark struct
+    Opaque(String, String),
+
+    /// Go type and cgo type
+    Scalar(&'static str, &'static str),
+}
+
+fn scalar_type(t: &str) -> GoType {
+    match GO_TYPE_MAP.iter().find(|(name, _)| *name == t) {
+        Some((_, (go, c))) => GoType::Scalar(go, c),
+        None => panic!("Unsupported type: {t}"),
+    }
+}
+
+/// Convert `snake_case` names into exported `CamelCase` names
+fn exported_name(s: &str) -> String {
+    s.split('_').map(first_uppercase).collect()
+}
+
+impl Go {
+    fn get_type(pkg: &Package, t: &str) -> GoType {
+        match pkg.types().resolved(t) {
+            codegen::Type::Scalar(_) => scalar_type(t),
+            codegen::Type::Array(a) => GoType::Array(a.type_name(), a.c_name),
+            ty => GoType::Opaque(ty.type_name().unwrap(), ty.c_name().unwrap().to_string()),
+        }
+    }
+}
+
+impl Generate for Go {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "// Generated by futhark-bindgen\n")?;
+
+        let package_name: String = config
+            .output_path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        let mut ldflags: Vec<_> = pkg
+            .manifest
+            .backend
+            .required_c_libs()
+            .iter()
+            .map(|x| format!("-l{x}"))
+            .collect();
+        if !ldflags.iter().any(|x| x == "-lm") {
+            ldflags.push("-lm".to_string());
+        }
+
+        let (backend_options, backend_config) = match pkg.manifest.backend {
+            Backend::Multicore => (
+                "\tNumThreads int",
+                "\tC.futhark_context_config_set_num_threads(config, C.int(opts.NumThreads))",
+            ),
+            Backend::CUDA | Backend::OpenCL => (
+                "\tDevice    string",
+                "\tif opts.Device != \"\" {\n\t\tdevice = C.CString(opts.Device)\n\t\tC.futhark_context_config_set_device(config, device)\n\t}",
+            ),
+            _ => ("", ""),
+        };
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/go/context.go"),
+            package_name = package_name,
+            ldflags = ldflags.join(" "),
+            header = pkg.h_file.file_name().unwrap().to_string_lossy(),
+            backend_options = backend_options,
+            backend_config = backend_config,
+        )?;
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let array = pkg.types().resolved(name);
+        let elemtype = a.elemtype.to_str();
+        let (go_elemtype, c_elemtype) = match scalar_type(elemtype) {
+            GoType::Scalar(go, c) => (go, c),
+            _ => unreachable!(),
+        };
+        let dim_args: Vec<_> = (0..a.rank)
+            .map(|i| format!("C.int64_t(dims[{i}])"))
+            .collect();
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/go/array.go"),
+            go_type = array.type_name().unwrap(),
+            futhark_type = array.c_name().unwrap(),
+            rank = a.rank,
+            elemtype = elemtype,
+            go_elemtype = go_elemtype,
+            c_elemtype = c_elemtype,
+            new_fn = a.ops.new,
+            free_fn = a.ops.free,
+            values_fn = a.ops.values,
+            shape_fn = a.ops.shape,
+            dim_args = dim_args.join(", "),
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let opaque = pkg.types().resolved(name);
+        let go_type = opaque.type_name().unwrap();
+        let futhark_type = opaque.c_name().unwrap();
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/go/opaque.go"),
+            go_type = go_type,
+            name = name,
+            futhark_type = futhark_type,
+            free_fn = ty.ops.free,
+        )?;
+
+        let record = match &ty.record {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        let mut new_params = Vec::new();
+        let mut new_call_args = Vec::new();
+        let mut keep_alive = Vec::new();
+        for field in &record.fields {
+            let param = format!("field{}", field.name);
+            let (go_field_type, c_field_type, zero, output) =
+                match Self::get_type(pkg, &field.r#type) {
+                    GoType::Array(t, s) | GoType::Opaque(t, s) => {
+                        new_call_args.push(format!("{param}.ptr"));
+                        keep_alive.push(format!("defer runtime.KeepAlive({param})"));
+                        new_params.push(format!("{param} *{t}"));
+                        (
+                            format!("*{t}"),
+                            format!("*C.struct_{s}"),
+                            "nil".to_string(),
+                            format!("new{t}FromPtr(t.ctx, out)"),
+                        )
+                    }
+                    GoType::Scalar(t, c) => {
+                        new_call_args.push(format!("{c}({param})"));
+                        new_params.push(format!("{param} {t}"));
+                        (
+                            t.to_string(),
+                            c.to_string(),
+                            format!("{t}(0)").replace("bool(0)", "false"),
+                            format!("{t}(out)"),
+                        )
+                    }
+                };
+
+            writeln!(
+                config.output_file,
+                include_str!("templates/go/record_project.go"),
+                go_type = go_type,
+                name = field.name,
+                field_name = exported_name(&field.name),
+                go_field_type = go_field_type,
+                c_field_type = c_field_type,
+                project_fn = field.project,
+                zero = zero,
+                output = output,
+            )?;
+        }
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/go/record.go"),
+            go_type = go_type,
+            futhark_type = futhark_type,
+            new_fn = record.new,
+            free_fn = ty.ops.free,
+            new_params = new_params.join(", "),
+            new_call_args = new_call_args.join(", "),
+            keep_alive = keep_alive.join("\n\t"),
+        )?;
+
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut entry_params = Vec::new();
+        let mut return_types = Vec::new();
+        let mut out_decl = Vec::new();
+        let mut call_args = Vec::new();
+        let mut zero_values = Vec::new();
+        let mut entry_return = Vec::new();
+        let mut keep_alive = Vec::new();
+
+        for (i, out) in entry.outputs.iter().enumerate() {
+            let name = format!("out{i}");
+            call_args.push(format!("&{name}"));
+            match Self::get_type(pkg, &out.r#type) {
+                GoType::Array(t, s) | GoType::Opaque(t, s) => {
+                    out_decl.push(format!("var {name} *C.struct_{s}"));
+                    return_types.push(format!("*{t}"));
+                    zero_values.push("nil".to_string());
+                    entry_return.push(format!("new{t}FromPtr(c, {name})"));
+                }
+                GoType::Scalar(t, c) => {
+                    out_decl.push(format!("var {name} {c}"));
+                    return_types.push(t.to_string());
+                    zero_values.push(format!("{t}(0)").replace("bool(0)", "false"));
+                    entry_return.push(format!("{t}({name})"));
+                }
+            }
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let name = format!("input{i}");
+            match Self::get_type(pkg, &input.r#type) {
+                GoType::Array(t, _) | GoType::Opaque(t, _) => {
+                    entry_params.push(format!("{name} *{t}"));
+                    call_args.push(format!("{name}.ptr"));
+                    keep_alive.push(format!("defer runtime.KeepAlive({name})"));
+                }
+                GoType::Scalar(t, c) => {
+                    entry_params.push(format!("{name} {t}"));
+                    call_args.push(format!("{c}({name})"));
+                }
+            }
+        }
+
+        return_types.push("error".to_string());
+        zero_values.push("c.error(rc)".to_string());
+        entry_return.push("nil".to_string());
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/go/entry.go"),
+            entry_name = name,
+            entry_go_name = exported_name(name),
+            entry_fn = entry.cfun,
+            entry_params = entry_params.join(", "),
+            entry_return_type = return_types.join(", "),
+            out_decl = out_decl.join("\n\t"),
+            call_args = call_args.join(", "),
+            error_return = zero_values.join(", "),
+            keep_alive = keep_alive.join("\n\t"),
+            entry_return = entry_return.join(", "),
+        )?;
+
+        Ok(())
+    }
+
+    fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
+        let _ = std::process::Command::new("gofmt").arg("-w").arg(path).status();
+        Ok(())
+    }
+}
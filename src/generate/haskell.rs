@@ -0,0 +1,368 @@
+use crate::codegen::first_uppercase;
+use crate::*;
+use std::io::Write;
+
+/// Haskell codegen, generates a module using the FFI, arrays are converted to and from
+/// `Data.Vector.Storable` vectors
+#[derive(Default)]
+pub struct Haskell;
+
+const HASKELL_TYPE_MAP: &[(&str, (&str, &str))] = &[
+    ("i8", ("Int8", "Int8")),
+    ("i16", ("Int16", "Int16")),
+    ("i32", ("Int32", "Int32")),
+    ("i64", ("Int64", "Int64")),
+    ("u8", ("Word8", "Word8")),
+    ("u16", ("Word16", "Word16")),
+    ("u32", ("Word32", "Word32")),
+    ("u64", ("Word64", "Word64")),
+    // Haskell has no half type, `f16` values are passed as their bit representation
+    ("f16", ("Word16", "Word16")),
+    ("f32", ("Float", "Float")),
+    ("f64", ("Double", "Double")),
+    // `Bool` is not the same size as a C bool
+    ("bool", ("Bool", "CBool")),
+];
+
+// Reserved words that can't be used as function names
+const HASKELL_KEYWORDS: &[&str] = &[
+    "case", "class", "data", "default", "deriving", "do", "else", "foreign", "if", "import", "in",
+    "infix", "infixl", "infixr", "instance", "let", "module", "newtype", "of", "then", "type",
+    "where",
+];
+
+enum HaskellType {
+    /// Haskell type name
+    Array(String),
+
+    /// Haskell type name
+    Opaque(String),
+
+    /// Haskell type and FFI type
+    Scalar(&'static str, &'static str),
+}
+
+fn scalar_type(t: &str) -> HaskellType {
+    match HASKELL_TYPE_MAP.iter().find(|(name, _)| *name == t) {
+        Some((_, (hs, c))) => HaskellType::Scalar(hs, c),
+        None => panic!("Unsupported type: {t}"),
+    }
+}
+
+/// Prefix used for functions associated with a type, `ArrayF32D1` becomes `arrayF32D1`
+fn function_prefix(s: &str) -> String {
+    let mut s = s.to_string();
+    if let Some(r) = s.get_mut(0..1) {
+        r.make_ascii_lowercase();
+    }
+    s
+}
+
+/// Convert `snake_case` names into `camelCase` function names
+fn function_name(s: &str) -> String {
+    let mut parts = s.split('_');
+    let mut name = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        name += &first_uppercase(part);
+    }
+    if HASKELL_KEYWORDS.contains(&name.as_str()) {
+        name.push('\'');
+    }
+    name
+}
+
+/// Convert a scalar value to the type used by the FFI
+fn scalar_input(c: &str, name: &str) -> String {
+    if c == "CBool" {
+        format!("(fromBool {name})")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Read a scalar value from a pointer returned by the FFI
+fn scalar_output(c: &str, ptr: &str) -> String {
+    if c == "CBool" {
+        format!("toBool <$> peek {ptr}")
+    } else {
+        format!("peek {ptr}")
+    }
+}
+
+impl Haskell {
+    fn get_type(pkg: &Package, t: &str) -> HaskellType {
+        match pkg.types().resolved(t) {
+            codegen::Type::Scalar(_) => scalar_type(t),
+            codegen::Type::Array(a) => HaskellType::Array(a.type_name()),
+            ty => HaskellType::Opaque(ty.type_name().unwrap()),
+        }
+    }
+}
+
+impl Generate for Haskell {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "-- Generated by futhark-bindgen\n")?;
+
+        let module_name: String = config
+            .output_path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .map(first_uppercase)
+            .collect();
+
+        let (backend_imports, backend_options, backend_defaults, backend_config, backend_free) =
+            match pkg.manifest.backend {
+                Backend::Multicore => (
+                    "\nforeign import ccall unsafe \"futhark_context_config_set_num_threads\"\n  futhark_context_config_set_num_threads :: Ptr FutharkContextConfig -> CInt -> IO ()\n",
+                    ",\n    optNumThreads :: Int",
+                    ",\n      optNumThreads = 0",
+                    "  futhark_context_config_set_num_threads config (fromIntegral (optNumThreads opts))",
+                    "",
+                ),
+                Backend::CUDA | Backend::OpenCL => (
+                    "\nforeign import ccall unsafe \"futhark_context_config_set_device\"\n  futhark_context_config_set_device :: Ptr FutharkContextConfig -> CString -> IO ()\n",
+                    ",\n    optDevice :: Maybe String",
+                    ",\n      optDevice = Nothing",
+                    "  device <- maybe (return nullPtr) newCString (optDevice opts)\n  when (device /= nullPtr) $ futhark_context_config_set_device config device",
+                    "\n        free device",
+                ),
+                _ => ("", "", "", "", ""),
+            };
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/haskell/context.hs"),
+            module_name = module_name,
+            backend_imports = backend_imports,
+            backend_options = backend_options,
+            backend_defaults = backend_defaults,
+            backend_config = backend_config,
+            backend_free = backend_free,
+        )?;
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let array = pkg.types().resolved(name);
+        let elemtype = a.elemtype.to_str();
+        let c_elemtype = match scalar_type(elemtype) {
+            HaskellType::Scalar(_, c) => c,
+            _ => unreachable!(),
+        };
+        let hs_type = array.type_name().unwrap();
+        let dim_types: Vec<_> = (0..a.rank).map(|_| "Int64").collect();
+        let dim_names: Vec<_> = (0..a.rank).map(|i| format!("d{i}")).collect();
+        let dim_args: Vec<_> = dim_names
+            .iter()
+            .map(|d| format!("(fromIntegral {d})"))
+            .collect();
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/haskell/array.hs"),
+            hs_type = hs_type,
+            prefix = function_prefix(&hs_type),
+            rank = a.rank,
+            elemtype = elemtype,
+            c_elemtype = c_elemtype,
+            new_fn = a.ops.new,
+            free_fn = a.ops.free,
+            values_fn = a.ops.values,
+            shape_fn = a.ops.shape,
+            dim_types = dim_types.join(" -> "),
+            dim_names = dim_names.join(", "),
+            dim_args = dim_args.join(" "),
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let opaque = pkg.types().resolved(name);
+        let hs_type = opaque.type_name().unwrap();
+        let prefix = function_prefix(&hs_type);
+
+        let mut record_imports = String::new();
+        let mut record_functions = String::new();
+
+        if let Some(record) = &ty.record {
+            let mut new_param_types = Vec::new();
+            let mut new_params = Vec::new();
+            let mut new_foreign_types = Vec::new();
+            let mut new_call_args = Vec::new();
+            let mut wrappers = vec!["withContext ctx $ \\c ->".to_string()];
+
+            for field in &record.fields {
+                let param = format!("field{}", field.name);
+                new_params.push(param.clone());
+                let (hs_field_type, out_type, output) = match Self::get_type(pkg, &field.r#type) {
+                    HaskellType::Array(t) | HaskellType::Opaque(t) => {
+                        new_param_types.push(t.clone());
+                        new_foreign_types.push(format!("Ptr Raw{t}"));
+                        wrappers.push(format!("with{t} {param} $ \\{param}Ptr ->"));
+                        new_call_args.push(format!("{param}Ptr"));
+                        let output = format!("peek out >>= {}FromPtr ctx", function_prefix(&t));
+                        (t.clone(), format!("(Ptr Raw{t})"), output)
+                    }
+                    HaskellType::Scalar(t, c) => {
+                        new_param_types.push(t.to_string());
+                        new_foreign_types.push(c.to_string());
+                        new_call_args.push(scalar_input(c, &param));
+                        (t.to_string(), c.to_string(), scalar_output(c, "out"))
+                    }
+                };
+
+                record_imports += &format!(
+                    "\nforeign import ccall unsafe \"{0}\"\n  {0} :: Ptr FutharkContext -> Ptr {out_type} -> Ptr Raw{hs_type} -> IO CInt\n",
+                    field.project
+                );
+
+                record_functions += &format!(
+                    include_str!("templates/haskell/record_project.hs"),
+                    hs_type = hs_type,
+                    prefix = prefix,
+                    name = field.name,
+                    getter = format!("{prefix}{}", first_uppercase(&field.name)),
+                    hs_field_type = hs_field_type,
+                    project_fn = field.project,
+                    output = output,
+                );
+            }
+
+            wrappers.push("alloca $ \\out ->".to_string());
+
+            record_imports += &format!(
+                "\nforeign import ccall unsafe \"{0}\"\n  {0} :: Ptr FutharkContext -> Ptr (Ptr Raw{hs_type}) -> {1} -> IO CInt\n",
+                record.new,
+                new_foreign_types.join(" -> "),
+            );
+
+            record_functions += &format!(
+                include_str!("templates/haskell/record.hs"),
+                hs_type = hs_type,
+                prefix = prefix,
+                new_fn = record.new,
+                new_param_types = new_param_types.join(" -> "),
+                new_params = new_params.join(" "),
+                new_call_args = new_call_args.join(" "),
+                wrappers = wrappers.join("\n  "),
+            );
+        }
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/haskell/opaque.hs"),
+            hs_type = hs_type,
+            prefix = prefix,
+            name = name,
+            free_fn = ty.ops.free,
+            record_imports = record_imports,
+        )?;
+        write!(config.output_file, "{record_functions}")?;
+
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut foreign_types = vec!["Ptr FutharkContext".to_string()];
+        let mut entry_types = vec!["Context".to_string()];
+        let mut entry_params = vec!["ctx".to_string()];
+        let mut wrappers = vec!["withContext ctx $ \\c ->".to_string()];
+        let mut call_args = Vec::new();
+        let mut read_outputs = Vec::new();
+        let mut return_types = Vec::new();
+        let mut results = Vec::new();
+
+        for (i, out) in entry.outputs.iter().enumerate() {
+            let name = format!("out{i}");
+            let result = format!("r{i}");
+            match Self::get_type(pkg, &out.r#type) {
+                HaskellType::Array(t) | HaskellType::Opaque(t) => {
+                    foreign_types.push(format!("Ptr (Ptr Raw{t})"));
+                    read_outputs.push(format!(
+                        "{result} <- peek {name} >>= {}FromPtr ctx",
+                        function_prefix(&t)
+                    ));
+                    return_types.push(t);
+                }
+                HaskellType::Scalar(t, c) => {
+                    foreign_types.push(format!("Ptr {c}"));
+                    read_outputs.push(format!("{result} <- {}", scalar_output(c, &name)));
+                    return_types.push(t.to_string());
+                }
+            }
+            wrappers.push(format!("alloca $ \\{name} ->"));
+            call_args.push(name);
+            results.push(result);
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let name = format!("input{i}");
+            match Self::get_type(pkg, &input.r#type) {
+                HaskellType::Array(t) | HaskellType::Opaque(t) => {
+                    foreign_types.push(format!("Ptr Raw{t}"));
+                    wrappers.push(format!("with{t} {name} $ \\{name}Ptr ->"));
+                    call_args.push(format!("{name}Ptr"));
+                    entry_types.push(t);
+                }
+                HaskellType::Scalar(t, c) => {
+                    foreign_types.push(c.to_string());
+                    call_args.push(scalar_input(c, &name));
+                    entry_types.push(t.to_string());
+                }
+            }
+            entry_params.push(name);
+        }
+
+        let (return_type, result) = match results.len() {
+            0 => ("()".to_string(), "()".to_string()),
+            1 => (return_types.remove(0), results.remove(0)),
+            _ => (
+                format!("({})", return_types.join(", ")),
+                format!("({})", results.join(", ")),
+            ),
+        };
+        read_outputs.push(format!("return {result}"));
+        foreign_types.push("IO CInt".to_string());
+        entry_types.push(format!("IO {return_type}"));
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/haskell/entry.hs"),
+            name = name,
+            entry_name = function_name(name),
+            entry_fn = entry.cfun,
+            foreign_type = foreign_types.join(" -> "),
+            entry_type = entry_types.join(" -> "),
+            entry_params = entry_params.join(" "),
+            wrappers = wrappers.join("\n  "),
+            call_args = call_args.join(" "),
+            read_outputs = read_outputs.join("\n    "),
+        )?;
+
+        Ok(())
+    }
+}
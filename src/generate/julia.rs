@@ -0,0 +1,312 @@
+use crate::codegen::first_uppercase;
+use crate::*;
+use std::io::Write;
+
+/// Julia codegen, generates a module that loads the Futhark program from a shared library
+/// named `lib<output name>` in the same directory
+#[derive(Default)]
+pub struct Julia;
+
+const JULIA_TYPE_MAP: &[(&str, &str)] = &[
+    ("i8", "Int8"),
+    ("i16", "Int16"),
+    ("i32", "Int32"),
+    ("i64", "Int64"),
+    ("u8", "UInt8"),
+    ("u16", "UInt16"),
+    ("u32", "UInt32"),
+    ("u64", "UInt64"),
+    ("f16", "Float16"),
+    ("f32", "Float32"),
+    ("f64", "Float64"),
+    ("bool", "Bool"),
+];
+
+// Reserved words that need to be escaped using `var"name"` syntax
+const JULIA_KEYWORDS: &[&str] = &[
+    "baremodule",
+    "begin",
+    "break",
+    "catch",
+    "const",
+    "continue",
+    "do",
+    "else",
+    "elseif",
+    "end",
+    "export",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "global",
+    "if",
+    "import",
+    "let",
+    "local",
+    "macro",
+    "module",
+    "quote",
+    "return",
+    "struct",
+    "true",
+    "try",
+    "using",
+    "while",
+];
+
+enum JuliaType {
+    Array(String),
+    Opaque(String),
+    Scalar(&'static str),
+}
+
+fn scalar_type(t: &str) -> &'static str {
+    match JULIA_TYPE_MAP.iter().find(|(name, _)| *name == t) {
+        Some((_, julia)) => julia,
+        None => panic!("Unsupported type: {t}"),
+    }
+}
+
+fn identifier(s: &str) -> String {
+    if JULIA_KEYWORDS.contains(&s) {
+        format!("var\"{s}\"")
+    } else {
+        s.to_string()
+    }
+}
+
+impl Julia {
+    fn get_type(pkg: &Package, t: &str) -> JuliaType {
+        match pkg.types().resolved(t) {
+            codegen::Type::Scalar(_) => JuliaType::Scalar(scalar_type(t)),
+            codegen::Type::Array(a) => JuliaType::Array(a.type_name()),
+            ty => JuliaType::Opaque(ty.type_name().unwrap()),
+        }
+    }
+}
+
+impl Generate for Julia {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "# Generated by futhark-bindgen\n")?;
+
+        let library_name: String = config
+            .output_path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let module_name: String = library_name.split('_').map(first_uppercase).collect();
+
+        let (backend_options, backend_config) = match pkg.manifest.backend {
+            Backend::Multicore => (
+                ", num_threads::Integer=0",
+                "        ccall((:futhark_context_config_set_num_threads, libfuthark), Cvoid, (Ptr{Cvoid}, Cint), config, num_threads)",
+            ),
+            Backend::CUDA | Backend::OpenCL => (
+                ", device::Union{String,Nothing}=nothing",
+                "        if device !== nothing\n            push!(strings, device)\n            ccall((:futhark_context_config_set_device, libfuthark), Cvoid, (Ptr{Cvoid}, Cstring), config, device)\n        end",
+            ),
+            _ => ("", ""),
+        };
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/julia/context.jl"),
+            module_name = module_name,
+            library_name = library_name,
+            backend_options = backend_options,
+            backend_config = backend_config,
+        )?;
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let array = pkg.types().resolved(name);
+        let elemtype = a.elemtype.to_str();
+        let dim_types: Vec<_> = (0..a.rank).map(|_| "Int64").collect();
+        let dim_args: Vec<_> = (1..=a.rank).map(|i| format!("dims[{i}]")).collect();
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/julia/array.jl"),
+            julia_type = array.type_name().unwrap(),
+            rank = a.rank,
+            elemtype = elemtype,
+            julia_elemtype = scalar_type(elemtype),
+            new_fn = a.ops.new,
+            free_fn = a.ops.free,
+            values_fn = a.ops.values,
+            shape_fn = a.ops.shape,
+            dim_types = dim_types.join(", "),
+            dim_args = dim_args.join(", "),
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let opaque = pkg.types().resolved(name);
+        let julia_type = opaque.type_name().unwrap();
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/julia/opaque.jl"),
+            julia_type = julia_type,
+            name = name,
+            free_fn = ty.ops.free,
+        )?;
+
+        let record = match &ty.record {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        let mut new_params = Vec::new();
+        let mut new_arg_types = Vec::new();
+        let mut new_call_args = Vec::new();
+        let mut preserve = vec!["ctx".to_string()];
+        for field in &record.fields {
+            let param = format!("field{}", field.name);
+            let (out_type, out_init, output) = match Self::get_type(pkg, &field.r#type) {
+                JuliaType::Array(t) | JuliaType::Opaque(t) => {
+                    new_params.push(format!("{param}::{t}"));
+                    new_arg_types.push("Ptr{Cvoid}".to_string());
+                    new_call_args.push(format!("{param}.ptr"));
+                    preserve.push(param);
+                    (
+                        "Ptr{Cvoid}".to_string(),
+                        "C_NULL",
+                        format!("{t}(t.ctx, out[])"),
+                    )
+                }
+                JuliaType::Scalar(t) => {
+                    new_params.push(format!("{param}::{t}"));
+                    new_arg_types.push(t.to_string());
+                    new_call_args.push(param);
+                    (t.to_string(), "", "out[]".to_string())
+                }
+            };
+
+            writeln!(
+                config.output_file,
+                include_str!("templates/julia/record_project.jl"),
+                julia_type = julia_type,
+                name = field.name,
+                field_name = field.name,
+                out_type = out_type,
+                out_init = out_init,
+                project_fn = field.project,
+                output = output,
+            )?;
+        }
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/julia/record.jl"),
+            julia_type = julia_type,
+            new_fn = record.new,
+            new_params = new_params.join(", "),
+            new_arg_types = new_arg_types.join(", "),
+            new_call_args = new_call_args.join(", "),
+            preserve = preserve.join(" "),
+        )?;
+
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut entry_params = vec!["ctx::Context".to_string()];
+        let mut arg_types = vec!["Ptr{Cvoid}".to_string()];
+        let mut call_args = vec!["handle(ctx)".to_string()];
+        let mut preserve = vec!["ctx".to_string()];
+        let mut out_decl = Vec::new();
+        let mut entry_return = Vec::new();
+
+        for (i, out) in entry.outputs.iter().enumerate() {
+            let name = format!("out{i}");
+            match Self::get_type(pkg, &out.r#type) {
+                JuliaType::Array(t) | JuliaType::Opaque(t) => {
+                    out_decl.push(format!("{name} = Ref{{Ptr{{Cvoid}}}}(C_NULL)"));
+                    arg_types.push("Ref{Ptr{Cvoid}}".to_string());
+                    entry_return.push(format!("{t}(ctx, {name}[])"));
+                }
+                JuliaType::Scalar(t) => {
+                    out_decl.push(format!("{name} = Ref{{{t}}}()"));
+                    arg_types.push(format!("Ref{{{t}}}"));
+                    entry_return.push(format!("{name}[]"));
+                }
+            }
+            call_args.push(name);
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let name = format!("input{i}");
+            match Self::get_type(pkg, &input.r#type) {
+                JuliaType::Array(t) | JuliaType::Opaque(t) => {
+                    entry_params.push(format!("{name}::{t}"));
+                    arg_types.push("Ptr{Cvoid}".to_string());
+                    call_args.push(format!("{name}.ptr"));
+                    preserve.push(name);
+                }
+                JuliaType::Scalar(t) => {
+                    entry_params.push(format!("{name}::{t}"));
+                    arg_types.push(t.to_string());
+                    call_args.push(name);
+                }
+            }
+        }
+
+        let entry_return = match entry_return.len() {
+            0 => "nothing".to_string(),
+            1 => entry_return.remove(0),
+            _ => format!("({})", entry_return.join(", ")),
+        };
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/julia/entry.jl"),
+            name = name,
+            entry_name = identifier(name),
+            entry_fn = entry.cfun,
+            entry_params = entry_params.join(", "),
+            out_decl = out_decl.join("\n    "),
+            preserve = preserve.join(" "),
+            arg_types = arg_types.join(", "),
+            call_args = call_args.join(", "),
+            entry_return = entry_return,
+        )?;
+
+        Ok(())
+    }
+
+    fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
+        // Close the module opened in `bindings`
+        let mut file = std::fs::OpenOptions::new().append(true).open(path)?;
+        writeln!(file, "end # module")?;
+        Ok(())
+    }
+}
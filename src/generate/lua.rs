@@ -0,0 +1,294 @@
+use crate::*;
+use std::io::Write;
+
+/// LuaJIT codegen, generates a module that loads the Futhark program from a shared library
+/// named `lib<output name>` in the same directory
+#[derive(Default)]
+pub struct Lua;
+
+/// Futhark type and the C type used in `ffi.cdef` declarations
+///
+/// `f16` values are represented by their bit pattern
+const LUA_TYPE_MAP: &[(&str, &str)] = &[
+    ("i8", "int8_t"),
+    ("i16", "int16_t"),
+    ("i32", "int32_t"),
+    ("i64", "int64_t"),
+    ("u8", "uint8_t"),
+    ("u16", "uint16_t"),
+    ("u32", "uint32_t"),
+    ("u64", "uint64_t"),
+    ("f16", "uint16_t"),
+    ("f32", "float"),
+    ("f64", "double"),
+    ("bool", "bool"),
+];
+
+// Reserved words that can't be used as method names
+const LUA_KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+enum LuaType {
+    /// Lua wrapper type and the name of the underlying Futhark struct
+    Array(String, String),
+
+    /// Lua wrapper type and the name of the underlying Futhark struct
+    Opaque(String, String),
+
+    /// C type
+    Scalar(&'static str),
+}
+
+fn scalar_type(t: &str) -> &'static str {
+    match LUA_TYPE_MAP.iter().find(|(name, _)| *name == t) {
+        Some((_, c)) => c,
+        None => panic!("Unsupported type: {t}"),
+    }
+}
+
+impl Lua {
+    fn get_type(pkg: &Package, t: &str) -> LuaType {
+        match pkg.types().resolved(t) {
+            codegen::Type::Scalar(_) => LuaType::Scalar(scalar_type(t)),
+            codegen::Type::Array(a) => LuaType::Array(a.type_name(), a.c_name),
+            ty => LuaType::Opaque(ty.type_name().unwrap(), ty.c_name().unwrap().to_string()),
+        }
+    }
+}
+
+impl Generate for Lua {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "-- Generated by futhark-bindgen\n")?;
+
+        let library_name: String = config
+            .output_path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+
+        let (backend_cdef, backend_option_names, backend_config) = match pkg.manifest.backend {
+            Backend::Multicore => (
+                "\nvoid futhark_context_config_set_num_threads(struct futhark_context_config *cfg, int n);",
+                " and `num_threads`",
+                "\n  if options.num_threads ~= nil then\n    lib.futhark_context_config_set_num_threads(config, options.num_threads)\n  end",
+            ),
+            Backend::CUDA | Backend::OpenCL => (
+                "\nvoid futhark_context_config_set_device(struct futhark_context_config *cfg, const char *s);",
+                " and `device`",
+                "\n  if options.device ~= nil then\n    table.insert(strings, options.device)\n    lib.futhark_context_config_set_device(config, options.device)\n  end",
+            ),
+            _ => ("", "", ""),
+        };
+
+        write!(
+            config.output_file,
+            include_str!("templates/lua/context.lua"),
+            library_name = library_name,
+            backend_cdef = backend_cdef,
+            backend_option_names = backend_option_names,
+            backend_config = backend_config,
+        )?;
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let array = pkg.types().resolved(name);
+        let new_dim_params: Vec<_> = (0..a.rank).map(|i| format!("int64_t dim{i}")).collect();
+        let dim_args: Vec<_> = (1..=a.rank).map(|i| format!("dims[{i}]")).collect();
+
+        write!(
+            config.output_file,
+            include_str!("templates/lua/array.lua"),
+            lua_type = array.type_name().unwrap(),
+            futhark_type = array.c_name().unwrap(),
+            rank = a.rank,
+            elemtype = a.elemtype.to_str(),
+            c_elemtype = scalar_type(a.elemtype.to_str()),
+            new_fn = a.ops.new,
+            free_fn = a.ops.free,
+            values_fn = a.ops.values,
+            shape_fn = a.ops.shape,
+            new_dim_params = new_dim_params.join(", "),
+            dim_args = dim_args.join(", "),
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let opaque = pkg.types().resolved(name);
+        let lua_type = opaque.type_name().unwrap();
+        let futhark_type = opaque.c_name().unwrap();
+
+        write!(
+            config.output_file,
+            include_str!("templates/lua/opaque.lua"),
+            lua_type = lua_type,
+            name = name,
+            futhark_type = futhark_type,
+            free_fn = ty.ops.free,
+        )?;
+
+        let record = match &ty.record {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        let mut new_params = Vec::new();
+        let mut new_call_args = Vec::new();
+        let mut new_cdef_params = Vec::new();
+        let mut project_cdef = String::new();
+        let mut project_functions = String::new();
+
+        for field in &record.fields {
+            let param = format!("field{}", field.name);
+            let (out_type, out_param, output) = match Self::get_type(pkg, &field.r#type) {
+                LuaType::Array(t, s) | LuaType::Opaque(t, s) => {
+                    new_call_args.push(format!("{param}:get_ptr()"));
+                    new_cdef_params.push(format!("struct {s} *{param}"));
+                    (
+                        format!("struct {s} *"),
+                        format!("struct {s} **out"),
+                        format!("M.{t}.from_ptr(self.ctx, out[0])"),
+                    )
+                }
+                LuaType::Scalar(t) => {
+                    new_call_args.push(param.clone());
+                    new_cdef_params.push(format!("{t} {param}"));
+                    (t.to_string(), format!("{t} *out"), "out[0]".to_string())
+                }
+            };
+            new_params.push(param);
+
+            project_cdef += &format!(
+                "int {}(struct futhark_context *ctx, {out_param}, struct {futhark_type} *obj);\n",
+                field.project,
+            );
+
+            project_functions += &format!(
+                include_str!("templates/lua/record_project.lua"),
+                lua_type = lua_type,
+                name = field.name,
+                field_name = field.name,
+                out_type = out_type,
+                project_fn = field.project,
+                output = output,
+            );
+        }
+
+        write!(
+            config.output_file,
+            include_str!("templates/lua/record.lua"),
+            lua_type = lua_type,
+            futhark_type = futhark_type,
+            new_fn = record.new,
+            new_params = new_params.join(", "),
+            new_call_args = new_call_args.join(", "),
+            new_cdef_params = new_cdef_params.join(", "),
+            project_cdef = project_cdef,
+        )?;
+        write!(config.output_file, "{project_functions}")?;
+
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut cdef_params = vec!["struct futhark_context *ctx".to_string()];
+        let mut entry_params = Vec::new();
+        let mut out_decl = String::new();
+        let mut call_args = vec!["self:get_handle()".to_string()];
+        let mut entry_return = Vec::new();
+
+        for (i, out) in entry.outputs.iter().enumerate() {
+            let name = format!("out{i}");
+            call_args.push(name.clone());
+            match Self::get_type(pkg, &out.r#type) {
+                LuaType::Array(t, s) | LuaType::Opaque(t, s) => {
+                    cdef_params.push(format!("struct {s} **{name}"));
+                    out_decl += &format!("  local {name} = ffi.new(\"struct {s} *[1]\")\n");
+                    entry_return.push(format!("M.{t}.from_ptr(self, {name}[0])"));
+                }
+                LuaType::Scalar(t) => {
+                    cdef_params.push(format!("{t} *{name}"));
+                    out_decl += &format!("  local {name} = ffi.new(\"{t}[1]\")\n");
+                    entry_return.push(format!("{name}[0]"));
+                }
+            }
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let name = format!("input{i}");
+            match Self::get_type(pkg, &input.r#type) {
+                LuaType::Array(_, s) | LuaType::Opaque(_, s) => {
+                    cdef_params.push(format!("struct {s} *{name}"));
+                    call_args.push(format!("{name}:get_ptr()"));
+                }
+                LuaType::Scalar(t) => {
+                    cdef_params.push(format!("{t} {name}"));
+                    call_args.push(name.clone());
+                }
+            }
+            entry_params.push(name);
+        }
+
+        let entry_header = if LUA_KEYWORDS.contains(&name) {
+            let mut params = vec!["self".to_string()];
+            params.extend(entry_params);
+            format!("Context[\"{name}\"] = function({})", params.join(", "))
+        } else {
+            format!("function Context:{name}({})", entry_params.join(", "))
+        };
+
+        let entry_return = if entry_return.is_empty() {
+            String::new()
+        } else {
+            format!("\n  return {}", entry_return.join(", "))
+        };
+
+        write!(
+            config.output_file,
+            include_str!("templates/lua/entry.lua"),
+            entry_fn = entry.cfun,
+            name = name,
+            entry_header = entry_header,
+            cdef_params = cdef_params.join(", "),
+            out_decl = out_decl,
+            call_args = call_args.join(", "),
+            entry_return = entry_return,
+        )?;
+
+        Ok(())
+    }
+
+    fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
+        let mut out = std::fs::OpenOptions::new().append(true).open(path)?;
+        writeln!(out, "\nreturn M")?;
+        Ok(())
+    }
+}
@@ -1,29 +1,30 @@
 use crate::*;
 
+mod c;
+mod csharp;
+mod external;
+mod go;
+mod haskell;
+mod julia;
+mod lua;
+mod node;
 mod ocaml;
 mod rust;
-
+mod rust_server;
+mod zig;
+
+pub use c::C;
+pub use csharp::CSharp;
+pub use external::External;
+pub use go::Go;
+pub use haskell::Haskell;
+pub use julia::Julia;
+pub use lua::Lua;
+pub use node::Node;
 pub use ocaml::OCaml;
 pub use rust::Rust;
-
-pub(crate) fn first_uppercase(s: &str) -> String {
-    let mut s = s.to_string();
-    if let Some(r) = s.get_mut(0..1) {
-        r.make_ascii_uppercase();
-    }
-    s
-}
-
-pub(crate) fn convert_struct_name(s: &str) -> &str {
-    s.strip_prefix("struct")
-        .unwrap()
-        .strip_suffix('*')
-        .unwrap()
-        .strip_prefix(|x: char| x.is_ascii_whitespace())
-        .unwrap()
-        .strip_suffix(|x: char| x.is_ascii_whitespace())
-        .unwrap()
-}
+pub use rust_server::RustServer;
+pub use zig::Zig;
 
 /// Code generation config
 pub struct Config {
@@ -32,6 +33,15 @@ pub struct Config {
 
     /// Path to output file
     pub output_file: std::fs::File,
+
+    /// Generate `async` variants of entry points and array readback functions (Rust only)
+    pub async_entry_points: bool,
+
+    /// Return `Pending` values from entry points instead of the results directly (Rust only)
+    pub pending_results: bool,
+
+    /// Generate a client for a Futhark server executable instead of FFI bindings (Rust only)
+    pub server_client: bool,
 }
 
 impl Config {
@@ -40,13 +50,40 @@ impl Config {
         Ok(Config {
             output_path: output.as_ref().to_path_buf(),
             output_file: std::fs::File::create(output)?,
+            async_entry_points: false,
+            pending_results: false,
+            server_client: false,
         })
     }
+
+    /// Enable or disable generation of `async` entry points
+    pub fn with_async_entry_points(mut self, enable: bool) -> Self {
+        self.async_entry_points = enable;
+        self
+    }
+
+    /// Enable or disable `Pending` entry point results, these must be synced using
+    /// `Pending::wait` before they can be read on the host
+    pub fn with_pending_results(mut self, enable: bool) -> Self {
+        self.pending_results = enable;
+        self
+    }
+
+    /// Generate a client for an executable built using `futhark <backend> --server` instead of
+    /// FFI bindings, see [`RustServer`]
+    pub fn with_server_client(mut self, enable: bool) -> Self {
+        self.server_client = enable;
+        self
+    }
 }
 
 pub trait Generate {
     /// Iterates through the manifest and generates code
     fn generate(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        if let Some(version) = pkg.version() {
+            version.check(self.required_features())?;
+        }
+
         self.bindings(pkg, config)?;
         for (name, ty) in &pkg.manifest.types {
             match ty {
@@ -66,6 +103,12 @@ pub trait Generate {
         Ok(())
     }
 
+    /// Features of the Futhark C API used by the generated code, `generate` returns an error if
+    /// the package was compiled with a version of Futhark that doesn't provide them
+    fn required_features(&self) -> &'static [Feature] {
+        &[]
+    }
+
     /// Step 1: generate any setup code or low-level bindings
     fn bindings(&mut self, _pkg: &Package, _config: &mut Config) -> Result<(), Error>;
 
@@ -102,14 +145,50 @@ pub trait Generate {
     }
 }
 
-fn rust() -> Box<impl Generate> {
-    Box::<Rust>::default()
+fn rust(config: &Config) -> Box<dyn Generate> {
+    if config.server_client {
+        Box::<RustServer>::default()
+    } else {
+        Box::<Rust>::default()
+    }
 }
 
 fn ocaml(config: &Config) -> Box<impl Generate> {
     Box::new(OCaml::new(config).unwrap())
 }
 
+fn go() -> Box<impl Generate> {
+    Box::<Go>::default()
+}
+
+fn zig() -> Box<impl Generate> {
+    Box::<Zig>::default()
+}
+
+fn julia() -> Box<impl Generate> {
+    Box::<Julia>::default()
+}
+
+fn haskell() -> Box<impl Generate> {
+    Box::<Haskell>::default()
+}
+
+fn node(config: &Config) -> Box<impl Generate> {
+    Box::new(Node::new(config).unwrap())
+}
+
+fn csharp() -> Box<impl Generate> {
+    Box::<CSharp>::default()
+}
+
+fn lua() -> Box<impl Generate> {
+    Box::<Lua>::default()
+}
+
+fn c() -> Box<impl Generate> {
+    Box::<C>::default()
+}
+
 impl Config {
     /// Automatically detect output language
     pub fn detect(&self) -> Option<Box<dyn Generate>> {
@@ -118,9 +197,36 @@ impl Config {
             .extension()
             .map(|x| x.to_str().expect("Invalid extension"))
         {
-            Some("rs") => Some(rust()),
+            Some("rs") => Some(rust(self)),
             Some("ml") => Some(ocaml(self)),
+            Some("go") => Some(go()),
+            Some("zig") => Some(zig()),
+            Some("jl") => Some(julia()),
+            Some("hs") => Some(haskell()),
+            // The TypeScript output only contains declarations
+            Some("ts") if self.output_path.to_string_lossy().ends_with(".d.ts") => Some(node(self)),
+            Some("cs") => Some(csharp()),
+            Some("lua") => Some(lua()),
+            Some("h") => Some(c()),
             _ => None,
         }
     }
+
+    /// Select the output language by name, names that don't match a built-in generator are
+    /// handled by an external `futhark-bindgen-<name>` executable
+    pub fn lang(&self, name: &str) -> Box<dyn Generate> {
+        match name {
+            "rust" => rust(self),
+            "ocaml" => ocaml(self),
+            "go" => go(),
+            "zig" => zig(),
+            "julia" => julia(),
+            "haskell" => haskell(),
+            "node" => node(self),
+            "csharp" => csharp(),
+            "lua" => lua(),
+            "c" => c(),
+            _ => Box::new(External::new(name)),
+        }
+    }
 }
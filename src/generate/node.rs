@@ -0,0 +1,552 @@
+use crate::codegen::first_uppercase;
+use crate::*;
+use std::io::Write;
+
+/// Node.js codegen, the output file contains TypeScript declarations and the N-API addon
+/// source is written to `<name>_napi.c` in the same directory
+pub struct Node {
+    c_file: std::fs::File,
+    define_classes: Vec<String>,
+    entry_properties: Vec<String>,
+    entry_methods: Vec<String>,
+    backend_options: &'static str,
+}
+
+struct ScalarType {
+    /// C type
+    c: &'static str,
+
+    /// N-API typed array type
+    napi_array: &'static str,
+
+    /// TypeScript type accepted as an input
+    ts_input: &'static str,
+
+    /// TypeScript type returned as an output
+    ts_output: &'static str,
+
+    /// TypeScript typed array type
+    ts_array: &'static str,
+}
+
+const NODE_TYPE_MAP: &[(&str, ScalarType)] = &[
+    (
+        "i8",
+        ScalarType {
+            c: "int8_t",
+            napi_array: "napi_int8_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Int8Array",
+        },
+    ),
+    (
+        "i16",
+        ScalarType {
+            c: "int16_t",
+            napi_array: "napi_int16_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Int16Array",
+        },
+    ),
+    (
+        "i32",
+        ScalarType {
+            c: "int32_t",
+            napi_array: "napi_int32_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Int32Array",
+        },
+    ),
+    (
+        "i64",
+        ScalarType {
+            c: "int64_t",
+            napi_array: "napi_bigint64_array",
+            ts_input: "bigint | number",
+            ts_output: "bigint",
+            ts_array: "BigInt64Array",
+        },
+    ),
+    (
+        "u8",
+        ScalarType {
+            c: "uint8_t",
+            napi_array: "napi_uint8_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Uint8Array",
+        },
+    ),
+    (
+        "u16",
+        ScalarType {
+            c: "uint16_t",
+            napi_array: "napi_uint16_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Uint16Array",
+        },
+    ),
+    (
+        "u32",
+        ScalarType {
+            c: "uint32_t",
+            napi_array: "napi_uint32_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Uint32Array",
+        },
+    ),
+    (
+        "u64",
+        ScalarType {
+            c: "uint64_t",
+            napi_array: "napi_biguint64_array",
+            ts_input: "bigint | number",
+            ts_output: "bigint",
+            ts_array: "BigUint64Array",
+        },
+    ),
+    // `f16` values are passed as their bit representation
+    (
+        "f16",
+        ScalarType {
+            c: "uint16_t",
+            napi_array: "napi_uint16_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Uint16Array",
+        },
+    ),
+    (
+        "f32",
+        ScalarType {
+            c: "float",
+            napi_array: "napi_float32_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Float32Array",
+        },
+    ),
+    (
+        "f64",
+        ScalarType {
+            c: "double",
+            napi_array: "napi_float64_array",
+            ts_input: "number",
+            ts_output: "number",
+            ts_array: "Float64Array",
+        },
+    ),
+    (
+        "bool",
+        ScalarType {
+            c: "bool",
+            napi_array: "napi_uint8_array",
+            ts_input: "boolean",
+            ts_output: "boolean",
+            ts_array: "Uint8Array",
+        },
+    ),
+];
+
+enum NodeType {
+    /// Class name and Futhark struct name
+    Array(String, String),
+
+    /// Class name and Futhark struct name
+    Opaque(String, String),
+
+    /// Futhark scalar type
+    Scalar(String),
+}
+
+fn scalar_type(t: &str) -> &'static ScalarType {
+    match NODE_TYPE_MAP.iter().find(|(name, _)| *name == t) {
+        Some((_, ty)) => ty,
+        None => panic!("Unsupported type: {t}"),
+    }
+}
+
+/// Convert `snake_case` names into `camelCase` method names
+fn method_name(s: &str) -> String {
+    let mut parts = s.split('_');
+    let mut name = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        name += &first_uppercase(part);
+    }
+    name
+}
+
+/// Declaration and conversion of a JavaScript value into a C value
+fn input_decl(ty: &NodeType, name: &str, arg: &str) -> String {
+    match ty {
+        NodeType::Array(t, s) | NodeType::Opaque(t, s) => format!(
+            "struct {s} *{name} =\n      value_ptr(env, {arg}, &{t}_tag, \"Expected {t}\", ctx);\n  if ({name} == NULL)\n    return NULL;"
+        ),
+        NodeType::Scalar(t) => format!(
+            "{} {name};\n  if (!get_{t}(env, {arg}, &{name}))\n    return NULL;",
+            scalar_type(t).c
+        ),
+    }
+}
+
+/// Declaration of an output value
+fn output_decl(ty: &NodeType, name: &str) -> String {
+    match ty {
+        NodeType::Array(_, s) | NodeType::Opaque(_, s) => format!("struct {s} *{name} = NULL;"),
+        NodeType::Scalar(t) => format!("{} {name};", scalar_type(t).c),
+    }
+}
+
+/// Conversion of an output value into a JavaScript value
+fn output_value(ty: &NodeType, name: &str, ctx: &str) -> String {
+    match ty {
+        NodeType::Array(t, _) | NodeType::Opaque(t, _) => {
+            format!("wrap_value(env, {t}_tag.upper, {ctx}, {name}, {t}_free_fn)")
+        }
+        NodeType::Scalar(t) => format!("new_{t}(env, {name})"),
+    }
+}
+
+fn ts_type(ty: &NodeType, input: bool) -> String {
+    match ty {
+        NodeType::Array(t, _) | NodeType::Opaque(t, _) => t.clone(),
+        NodeType::Scalar(t) if input => scalar_type(t).ts_input.to_string(),
+        NodeType::Scalar(t) => scalar_type(t).ts_output.to_string(),
+    }
+}
+
+impl Node {
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        let name = config
+            .output_path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let name = name
+            .strip_suffix(".d.ts")
+            .or_else(|| name.strip_suffix(".ts"))
+            .unwrap_or(&name);
+        let c_path = config.output_path.with_file_name(format!("{name}_napi.c"));
+        let c_file = std::fs::File::create(c_path)?;
+        Ok(Node {
+            c_file,
+            define_classes: Vec::new(),
+            entry_properties: Vec::new(),
+            entry_methods: Vec::new(),
+            backend_options: "",
+        })
+    }
+
+    fn get_type(pkg: &Package, t: &str) -> NodeType {
+        match pkg.types().resolved(t) {
+            codegen::Type::Scalar(_) => NodeType::Scalar(t.to_string()),
+            codegen::Type::Array(a) => NodeType::Array(a.type_name(), a.c_name),
+            ty => NodeType::Opaque(ty.type_name().unwrap(), ty.c_name().unwrap().to_string()),
+        }
+    }
+}
+
+impl Generate for Node {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "// Generated by futhark-bindgen\n")?;
+        writeln!(self.c_file, "// Generated by futhark-bindgen\n")?;
+
+        let (backend_decls, backend_options, backend_config, ts_options) =
+            match pkg.manifest.backend {
+                Backend::Multicore => (
+                    "  int32_t num_threads = 0;\n",
+                    " &&\n          get_int_option(env, argv[0], \"numThreads\", &num_threads)",
+                    "  futhark_context_config_set_num_threads(c->cfg, num_threads);",
+                    "\n  numThreads?: number;",
+                ),
+                Backend::CUDA | Backend::OpenCL => (
+                    "",
+                    " &&\n          get_string_option(env, argv[0], \"device\", &c->device)",
+                    "  if (c->device != NULL)\n    futhark_context_config_set_device(c->cfg, c->device);",
+                    "\n  device?: string;",
+                ),
+                _ => ("", "", "", ""),
+            };
+        self.backend_options = ts_options;
+
+        writeln!(
+            self.c_file,
+            include_str!("templates/node/context.c"),
+            header = pkg.h_file.file_name().unwrap().to_string_lossy(),
+            backend_decls = backend_decls,
+            backend_options = backend_options,
+            backend_config = backend_config,
+        )?;
+
+        // Type tags are declared up front since records and entry points may refer to types
+        // that are generated later, the second half of each tag is used to find the class
+        // constructor
+        for (index, ty) in pkg.types().iter().enumerate() {
+            let class_name = ty.type_name().unwrap();
+            let free_fn = ty.free_fn().unwrap();
+            let index = index + 1;
+            writeln!(
+                self.c_file,
+                include_str!("templates/node/value.c"),
+                class_name = class_name,
+                index = index,
+                free_fn = free_fn,
+            )?;
+            self.define_classes.push(format!(
+                "define_class(env, exports, \"{class_name}\", {class_name}_new, PROPERTIES({class_name}_properties), &classes[{index}])"
+            ));
+        }
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let array = pkg.types().resolved(name);
+        let class_name = array.type_name().unwrap();
+        let elemtype = a.elemtype.to_str();
+        let scalar = scalar_type(elemtype);
+        let dim_args: Vec<_> = (0..a.rank).map(|i| format!("dims[{i}]")).collect();
+
+        writeln!(
+            self.c_file,
+            include_str!("templates/node/array.c"),
+            class_name = class_name,
+            futhark_type = array.c_name().unwrap(),
+            rank = a.rank,
+            c_elemtype = scalar.c,
+            napi_array_type = scalar.napi_array,
+            new_fn = a.ops.new,
+            values_fn = a.ops.values,
+            shape_fn = a.ops.shape,
+            dim_args = dim_args.join(", "),
+        )?;
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/node/array.d.ts"),
+            class_name = class_name,
+            rank = a.rank,
+            elemtype = elemtype,
+            ts_array_type = scalar.ts_array,
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let opaque = pkg.types().resolved(name);
+        let class_name = opaque.type_name().unwrap();
+        let futhark_type = opaque.c_name().unwrap();
+
+        let mut properties = Vec::new();
+        let mut ts_fields = Vec::new();
+
+        let ts_constructor = match &ty.record {
+            Some(record) => {
+                let mut field_names = Vec::new();
+                let mut field_decls = Vec::new();
+                let mut new_call_args = Vec::new();
+                let mut ts_params = vec!["ctx: Context".to_string()];
+
+                for (i, field) in record.fields.iter().enumerate() {
+                    let field_type = Self::get_type(pkg, &field.r#type);
+                    let param = format!("field{}", field.name);
+                    field_names.push(field.name.clone());
+                    field_decls.push(input_decl(&field_type, &param, &format!("argv[{}]", i + 1)));
+                    new_call_args.push(param);
+                    ts_params.push(format!(
+                        "field{}: {}",
+                        field.name,
+                        ts_type(&field_type, true)
+                    ));
+                    ts_fields.push(format!(
+                        "  readonly \"{}\": {};",
+                        field.name,
+                        ts_type(&field_type, false)
+                    ));
+
+                    writeln!(
+                        self.c_file,
+                        include_str!("templates/node/record_project.c"),
+                        class_name = class_name,
+                        name = field.name,
+                        field_name = field.name,
+                        out_decl = output_decl(&field_type, "out"),
+                        project_fn = field.project,
+                        output = output_value(&field_type, "out", "v->ctx"),
+                    )?;
+
+                    properties.push(format!(
+                        "    {{\"{0}\", NULL, NULL, {class_name}_get_{0}, NULL, NULL, napi_enumerable, NULL}},",
+                        field.name
+                    ));
+                }
+
+                writeln!(
+                    self.c_file,
+                    include_str!("templates/node/record.c"),
+                    class_name = class_name,
+                    futhark_type = futhark_type,
+                    new_fn = record.new,
+                    argc = record.fields.len() + 1,
+                    field_names = field_names.join(", "),
+                    field_decls = field_decls.join("\n  "),
+                    new_call_args = new_call_args.join(", "),
+                )?;
+
+                format!("constructor({});", ts_params.join(", "))
+            }
+            None => {
+                writeln!(
+                    self.c_file,
+                    include_str!("templates/node/opaque_new.c"),
+                    class_name = class_name,
+                )?;
+                "private constructor();".to_string()
+            }
+        };
+
+        properties.push(format!(
+            "    {{\"free\", NULL, value_free, NULL, NULL, NULL, napi_default, (void *)&{class_name}_tag}},"
+        ));
+        writeln!(
+            self.c_file,
+            "\nstatic const napi_property_descriptor {class_name}_properties[] = {{\n{}\n}};",
+            properties.join("\n")
+        )?;
+
+        ts_fields.insert(0, format!("  {ts_constructor}"));
+        writeln!(
+            config.output_file,
+            include_str!("templates/node/opaque.d.ts"),
+            class_name = class_name,
+            name = name,
+            constructor = ts_fields.join("\n").trim_start(),
+        )?;
+
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        _config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut decls = Vec::new();
+        let mut call_args = vec!["ctx->ctx".to_string()];
+        let mut outputs = Vec::new();
+        let mut ts_params = Vec::new();
+
+        for (i, out) in entry.outputs.iter().enumerate() {
+            let name = format!("out{i}");
+            let ty = Self::get_type(pkg, &out.r#type);
+            decls.push(output_decl(&ty, &name));
+            call_args.push(format!("&{name}"));
+            outputs.push((name, ty));
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let name = format!("input{i}");
+            let ty = Self::get_type(pkg, &input.r#type);
+            decls.push(input_decl(&ty, &name, &format!("argv[{i}]")));
+            ts_params.push(format!("{name}: {}", ts_type(&ty, true)));
+            call_args.push(name);
+        }
+
+        let (entry_return, ts_return) = match outputs.len() {
+            0 => ("return NULL;".to_string(), "void".to_string()),
+            1 => (
+                format!(
+                    "return {};",
+                    output_value(&outputs[0].1, &outputs[0].0, "ctx")
+                ),
+                ts_type(&outputs[0].1, false),
+            ),
+            n => {
+                let mut s = format!(
+                    "napi_value result, item;\n  NAPI_CALL(env, napi_create_array_with_length(env, {n}, &result));"
+                );
+                for (i, (name, ty)) in outputs.iter().enumerate() {
+                    s += &format!(
+                        "\n  if ((item = {}) == NULL)\n    return NULL;\n  NAPI_CALL(env, napi_set_element(env, result, {i}, item));",
+                        output_value(ty, name, "ctx")
+                    );
+                }
+                s += "\n  return result;";
+                let ts: Vec<_> = outputs.iter().map(|(_, ty)| ts_type(ty, false)).collect();
+                (s, format!("[{}]", ts.join(", ")))
+            }
+        };
+
+        writeln!(
+            self.c_file,
+            include_str!("templates/node/entry.c"),
+            name = name,
+            entry_fn = entry.cfun,
+            argc = entry.inputs.len(),
+            argv_len = entry.inputs.len().max(1),
+            decls = decls.join("\n  "),
+            call_args = call_args.join(", "),
+            entry_return = entry_return,
+        )?;
+
+        let method = method_name(name);
+        self.entry_properties.push(format!(
+            "    {{\"{method}\", NULL, entry_{name}, NULL, NULL, NULL, napi_default, NULL}},"
+        ));
+        self.entry_methods.push(format!(
+            "  /** Entry point: {name} */\n  {method}({}): {ts_return};",
+            ts_params.join(", ")
+        ));
+
+        Ok(())
+    }
+
+    fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
+        // The `Context` class and module initialization are written last since they
+        // reference the entry points and every other class
+        let define_classes: String = self
+            .define_classes
+            .iter()
+            .map(|x| format!(" &&\n      {x}"))
+            .collect();
+        writeln!(
+            self.c_file,
+            include_str!("templates/node/init.c"),
+            entry_properties = self.entry_properties.join("\n"),
+            class_count = self.define_classes.len() + 1,
+            define_classes = define_classes,
+        )?;
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(path)?;
+        writeln!(
+            file,
+            include_str!("templates/node/context.d.ts"),
+            backend_options = self.backend_options,
+            entry_methods = self.entry_methods.join("\n"),
+        )?;
+        Ok(())
+    }
+}
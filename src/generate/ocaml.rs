@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use crate::generate::{convert_struct_name, first_uppercase};
+use crate::codegen::{self, first_uppercase};
 use crate::*;
 
 /// OCaml codegen
@@ -56,12 +56,23 @@ const OCAML_BA_TYPE_MAP: &[(&str, (&str, &str))] = &[
     ("bool", ("int", "Bigarray.int8_unsigned_elt")),
 ];
 
-fn type_is_array(t: &str) -> bool {
-    t.contains("array_f") || t.contains("array_i") || t.contains("array_u") || t.contains("array_b")
+// Ctypes and OCaml types used to represent device memory in `futhark_new_raw_*` and `futhark_values_raw_*`
+fn raw_pointer_type(backend: Backend) -> Option<(&'static str, &'static str)> {
+    match backend {
+        Backend::CUDA => Some(("uint64_t", "UInt64.t")),
+        Backend::OpenCL | Backend::HIP => Some(("ptr void", "unit Ctypes.ptr")),
+        _ => None,
+    }
 }
 
-fn type_is_opaque(t: &str) -> bool {
-    t.contains(".t")
+/// OCaml name of an opaque type, names that aren't valid identifiers are prefixed with `type_`
+fn opaque_name(ty: &codegen::OpaqueType) -> String {
+    let name = ty.base_name();
+    if name.chars().next().unwrap().is_numeric() || ty.name.contains(' ') {
+        format!("type_{name}")
+    } else {
+        name.to_string()
+    }
 }
 
 fn ba_kind(t: &str) -> String {
@@ -143,6 +154,10 @@ impl OCaml {
 }
 
 impl Generate for OCaml {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
     fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
         writeln!(self.mli_file, "(* Generated by futhark-bindgen *)\n")?;
         writeln!(config.output_file, "(* Generated by futhark-bindgen *)\n")?;
@@ -172,6 +187,19 @@ impl Generate for OCaml {
             _ => (),
         }
 
+        // `?log_callback` is only available when the runtime has `futhark_context_set_logging_file`
+        let logging_file = pkg.supports(Feature::LoggingFile);
+        if logging_file {
+            generated_foreign_functions.push(format!(
+                "  {}",
+                self.foreign_function(
+                    "futhark_context_set_logging_file",
+                    "void",
+                    vec!["context", "ptr void"]
+                )
+            ));
+        }
+
         for (name, ty) in &pkg.manifest.types {
             match ty {
                 manifest::Type::Array(a) => {
@@ -211,16 +239,42 @@ impl Generate for OCaml {
                             vec!["context", &ocaml_name]
                         )
                     ));
+                    let index = a.ops.index.as_ref();
+                    if let Some(index) = index.filter(|_| pkg.supports(Feature::ArrayIndex)) {
+                        let mut index_args = vec!["context", &elem_ptr, &ocaml_name];
+                        index_args.resize(rank as usize + 3, "int64_t");
+                        generated_foreign_functions.push(format!(
+                            "  {}",
+                            self.foreign_function(index, "int", index_args)
+                        ));
+                    }
+                    if let (Some((raw_ctype, _)), Some(new_raw), Some(values_raw)) = (
+                        raw_pointer_type(pkg.manifest.backend)
+                            .filter(|_| pkg.supports(Feature::RawArrays)),
+                        &a.ops.new_raw,
+                        &a.ops.values_raw,
+                    ) {
+                        let mut new_args = vec!["context", raw_ctype];
+                        new_args.resize(rank as usize + 2, "int64_t");
+                        generated_foreign_functions.push(format!(
+                            "  {}",
+                            self.foreign_function(new_raw, &ocaml_name, new_args)
+                        ));
+                        generated_foreign_functions.push(format!(
+                            "  {}",
+                            self.foreign_function(
+                                values_raw,
+                                raw_ctype,
+                                vec!["context", &ocaml_name]
+                            )
+                        ));
+                    }
                 }
                 manifest::Type::Opaque(ty) => {
-                    let futhark_name = convert_struct_name(&ty.ctype);
-                    let mut ocaml_name = futhark_name
-                        .strip_prefix("futhark_opaque_")
-                        .unwrap()
-                        .to_string();
-                    if ocaml_name.chars().next().unwrap().is_numeric() || name.contains(' ') {
-                        ocaml_name = format!("type_{ocaml_name}");
-                    }
+                    let opaque = pkg.types().resolved(name);
+                    let opaque = opaque.opaque().unwrap();
+                    let futhark_name = &opaque.c_name;
+                    let ocaml_name = opaque_name(opaque);
 
                     self.typemap
                         .insert(name.clone(), format!("{}.t", first_uppercase(&ocaml_name)));
@@ -314,16 +368,25 @@ impl Generate for OCaml {
             _ => ("", "", ""),
         };
 
+        let log = |s: &'static str| if logging_file { s } else { "" };
         writeln!(
             config.output_file,
             include_str!("templates/ocaml/context.ml"),
             extra_param = extra_param,
-            extra_line = extra_line
+            extra_line = extra_line,
+            log_callback_param = log("?log_callback "),
+            enable_log = log("let log = log || Option.is_some log_callback in"),
+            open_log = if logging_file {
+                include_str!("templates/ocaml/context_log.ml").trim()
+            } else {
+                "let log = None in"
+            },
         )?;
         writeln!(
             self.mli_file,
             include_str!("templates/ocaml/context.mli"),
-            extra_mli = extra_mli
+            extra_mli = extra_mli,
+            log_callback_mli = log("?log_callback:(string -> unit) -> "),
         )?;
 
         Ok(())
@@ -331,7 +394,7 @@ impl Generate for OCaml {
 
     fn array_type(
         &mut self,
-        _pkg: &Package,
+        pkg: &Package,
         config: &mut Config,
         name: &str,
         ty: &manifest::ArrayType,
@@ -348,6 +411,43 @@ impl Generate for OCaml {
         let (ocaml_elemtype, ba_elemtype) = self.get_ba_type(&elemtype);
         let ocaml_ctype = self.get_ctype(&elemtype);
 
+        let mut extra_fns = String::new();
+        let mut extra_mli = String::new();
+        let index_fn = ty.ops.index.as_ref();
+        if let Some(index_fn) = index_fn.filter(|_| pkg.supports(Feature::ArrayIndex)) {
+            let index_args: Vec<_> = (0..rank)
+                .map(|i| format!("(Int64.of_int idx.({i}))"))
+                .collect();
+            extra_fns += &format!(
+                include_str!("templates/ocaml/array_index.ml"),
+                rank = rank,
+                index_fn = index_fn,
+                index_args = index_args.join(" "),
+                ocaml_ctype = ocaml_ctype,
+            );
+            extra_mli += &format!(
+                include_str!("templates/ocaml/array_index.mli"),
+                ocaml_type = self.get_type(&elemtype),
+            );
+        }
+
+        if let (Some((_, raw_type)), Some(new_raw_fn), Some(values_raw_fn)) = (
+            raw_pointer_type(pkg.manifest.backend).filter(|_| pkg.supports(Feature::RawArrays)),
+            &ty.ops.new_raw,
+            &ty.ops.values_raw,
+        ) {
+            extra_fns += &format!(
+                include_str!("templates/ocaml/array_raw.ml"),
+                new_raw_fn = new_raw_fn,
+                values_raw_fn = values_raw_fn,
+                dim_args = dim_args.join(" "),
+            );
+            extra_mli += &format!(
+                include_str!("templates/ocaml/array_raw.mli"),
+                raw_type = raw_type
+            );
+        }
+
         writeln!(
             config.output_file,
             include_str!("templates/ocaml/array.ml"),
@@ -359,6 +459,7 @@ impl Generate for OCaml {
             ba_elemtype = ba_elemtype,
             ba_kind = ba_kind(&ba_elemtype),
             ocaml_ctype = ocaml_ctype,
+            extra_fns = extra_fns,
         )?;
 
         writeln!(
@@ -367,6 +468,7 @@ impl Generate for OCaml {
             module_name = module_name,
             ocaml_elemtype = ocaml_elemtype,
             ba_elemtype = ba_elemtype,
+            extra_mli = extra_mli,
         )?;
 
         Ok(())
@@ -374,19 +476,12 @@ impl Generate for OCaml {
 
     fn opaque_type(
         &mut self,
-        _pkg: &Package,
+        pkg: &Package,
         config: &mut Config,
         name: &str,
         ty: &manifest::OpaqueType,
     ) -> Result<(), Error> {
-        let futhark_name = convert_struct_name(&ty.ctype);
-        let mut ocaml_name = futhark_name
-            .strip_prefix("futhark_opaque_")
-            .unwrap()
-            .to_string();
-        if ocaml_name.chars().next().unwrap().is_numeric() || name.contains(' ') {
-            ocaml_name = format!("type_{ocaml_name}");
-        }
+        let ocaml_name = opaque_name(pkg.types().resolved(name).opaque().unwrap());
         let module_name = first_uppercase(&ocaml_name);
         self.typemap
             .insert(ocaml_name.clone(), format!("{module_name}.t"));
@@ -417,14 +512,15 @@ impl Generate for OCaml {
         let mut new_call_args = Vec::new();
         let mut new_arg_types = Vec::new();
         for f in record.fields.iter() {
+            let field_type = pkg.types().resolved(&f.r#type);
             let t = self.get_type(&f.r#type);
 
             new_params.push(format!("field{}", f.name));
 
-            if type_is_array(&t) {
+            if field_type.is_array() {
                 new_call_args.push(format!("(get_ptr field{})", f.name));
                 new_arg_types.push(format!("{}.t", first_uppercase(&t)));
-            } else if type_is_opaque(&t) {
+            } else if field_type.is_opaque() {
                 new_call_args.push(format!("(get_opaque_ptr field{})", f.name));
                 new_arg_types.push(t.to_string());
             } else {
@@ -448,14 +544,15 @@ impl Generate for OCaml {
         )?;
 
         for f in record.fields.iter() {
+            let field_type = pkg.types().resolved(&f.r#type);
             let t = self.get_type(&f.r#type);
             let name = &f.name;
             let project = &f.project;
 
-            let (out, out_type) = if type_is_opaque(&t) {
+            let (out, out_type) = if field_type.is_opaque() {
                 let call = t.replace(".t", ".of_ptr");
                 (format!("{call} t.opaque_ctx !@out"), t.to_string())
-            } else if type_is_array(&t) {
+            } else if field_type.is_array() {
                 let array = first_uppercase(&t);
                 (
                     format!("{array}.of_ptr t.opaque_ctx !@out"),
@@ -465,9 +562,9 @@ impl Generate for OCaml {
                 ("!@out".to_string(), t.to_string())
             };
 
-            let alloc_type = if type_is_array(&t) {
+            let alloc_type = if field_type.is_array() {
                 format!("Bindings.{t}")
-            } else if type_is_opaque(&t) {
+            } else if field_type.is_opaque() {
                 t
             } else {
                 self.get_ctype(&f.r#type)
@@ -497,7 +594,7 @@ impl Generate for OCaml {
 
     fn entry(
         &mut self,
-        _pkg: &Package,
+        pkg: &Package,
         config: &mut Config,
         name: &str,
         entry: &manifest::Entry,
@@ -510,13 +607,14 @@ impl Generate for OCaml {
         let mut out_decl = Vec::new();
 
         for (i, out) in entry.outputs.iter().enumerate() {
+            let ty = pkg.types().resolved(&out.r#type);
             let t = self.get_type(&out.r#type);
             let ct = self.get_ctype(&out.r#type);
 
             let mut ocaml_elemtype = t.clone();
 
             // Transform into `Module.t`
-            if ocaml_elemtype.contains("array_") {
+            if ty.is_array() {
                 ocaml_elemtype = first_uppercase(&ocaml_elemtype) + ".t"
             }
 
@@ -528,7 +626,7 @@ impl Generate for OCaml {
                 i.to_string()
             };
 
-            if type_is_array(&t) || type_is_opaque(&t) {
+            if !ty.is_scalar() {
                 out_decl.push(format!("  let out{i}_ptr = allocate (ptr void) null in"));
             } else {
                 out_decl.push(format!("  let out{i}_ptr = allocate_n {ct} ~count:1 in"));
@@ -536,10 +634,10 @@ impl Generate for OCaml {
 
             call_args.push(format!("out{i}_ptr"));
 
-            if type_is_array(&t) {
+            if ty.is_array() {
                 let m = first_uppercase(&t);
                 out_return.push(format!("({m}.of_ptr ctx !@out{i}_ptr)"));
-            } else if type_is_opaque(&t) {
+            } else if ty.is_opaque() {
                 let m = first_uppercase(&t);
                 let m = m.strip_suffix(".t").unwrap_or(&m);
                 out_return.push(format!("({m}.of_ptr ctx !@out{i}_ptr)"));
@@ -551,19 +649,19 @@ impl Generate for OCaml {
         for (i, input) in entry.inputs.iter().enumerate() {
             entry_params.push(format!("input{i}"));
 
+            let ty = pkg.types().resolved(&input.r#type);
             let mut ocaml_elemtype = self.get_type(&input.r#type);
 
             // Transform into `Module.t`
-            if type_is_array(&ocaml_elemtype) {
+            if ty.is_array() {
                 ocaml_elemtype = first_uppercase(&ocaml_elemtype) + ".t"
             }
 
             arg_types.push(ocaml_elemtype);
 
-            let t = self.get_type(&input.r#type);
-            if type_is_array(&t) {
+            if ty.is_array() {
                 call_args.push(format!("(get_ptr input{i})"));
-            } else if type_is_opaque(&t) {
+            } else if ty.is_opaque() {
                 call_args.push(format!("(get_opaque_ptr input{i})"));
             } else {
                 call_args.push(format!("input{i}"));
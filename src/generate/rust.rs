@@ -1,30 +1,20 @@
-use crate::generate::{convert_struct_name, first_uppercase};
+use crate::codegen;
 use crate::*;
 use std::io::Write;
 
 /// Rust codegen
-pub struct Rust {
-    typemap: BTreeMap<String, String>,
-}
-
-fn type_is_array(t: &str) -> bool {
-    t.contains("ArrayF") || t.contains("ArrayI") || t.contains("ArrayU") || t.contains("ArrayB")
-}
-
-fn type_is_opaque(a: &str) -> bool {
-    a.contains("futhark_opaque_")
-}
+#[derive(Default)]
+pub struct Rust;
 
 // Rust `f16` codgen requires the `half` crate
 const RUST_TYPE_MAP: &[(&str, &str)] = &[("f16", "half::f16")];
 
-impl Default for Rust {
-    fn default() -> Self {
-        let typemap = RUST_TYPE_MAP
-            .iter()
-            .map(|(a, b)| (a.to_string(), b.to_string()))
-            .collect();
-        Rust { typemap }
+// Type used by `futhark_new_raw_*` and `futhark_values_raw_*` to represent device memory
+fn raw_pointer_type(backend: Backend) -> Option<&'static str> {
+    match backend {
+        Backend::CUDA => Some("u64"),
+        Backend::OpenCL | Backend::HIP => Some("*mut std::ffi::c_void"),
+        _ => None,
     }
 }
 
@@ -36,32 +26,38 @@ struct ArrayInfo {
 }
 
 impl Rust {
-    fn get_type(typemap: &BTreeMap<String, String>, t: &str) -> String {
-        let a = typemap.get(t);
-        let x = match a {
-            Some(t) => t.clone(),
-            None => t.to_string(),
-        };
-        if x.is_empty() {
-            panic!("Unsupported type: {t}");
+    /// Returns the type used in the `extern` declarations and the type used in the generated API
+    pub(super) fn get_type(ty: &codegen::Type) -> (String, String) {
+        match ty {
+            codegen::Type::Scalar(t) => {
+                let t = match RUST_TYPE_MAP.iter().find(|(name, _)| *name == t.to_str()) {
+                    Some((_, rust)) => rust.to_string(),
+                    None => t.to_str().to_string(),
+                };
+                (t.clone(), t)
+            }
+            _ => (ty.c_name().unwrap().to_string(), ty.type_name().unwrap()),
         }
-        x
     }
 }
 
 impl Generate for Rust {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
     fn array_type(
         &mut self,
-        _pkg: &Package,
+        pkg: &Package,
         config: &mut Config,
         name: &str,
         a: &manifest::ArrayType,
     ) -> Result<(), Error> {
         let elemtype = a.elemtype.to_str();
-        let rank = a.rank;
 
-        let futhark_type = convert_struct_name(&a.ctype).to_string();
-        let rust_type = format!("Array{}D{rank}", first_uppercase(elemtype));
+        let ty = pkg.types().resolved(name);
+        let futhark_type = ty.c_name().unwrap().to_string();
+        let rust_type = ty.type_name().unwrap();
         let info = ArrayInfo {
             futhark_type,
             rust_type,
@@ -92,24 +88,82 @@ impl Generate for Rust {
             new_dim_args = new_dim_args.join(", ")
         )?;
 
-        self.typemap
-            .insert(name.to_string(), info.futhark_type.clone());
-        self.typemap.insert(info.futhark_type, info.rust_type);
+        let (write_elem, read_elem) = match a.elemtype {
+            manifest::ElemType::Bool => ("out.push(x as u8)".to_string(), "b[0] != 0".to_string()),
+            _ => (
+                "out.extend_from_slice(&x.to_le_bytes())".to_string(),
+                format!("{}::from_le_bytes(b.try_into().unwrap())", info.elem),
+            ),
+        };
+        writeln!(
+            config.output_file,
+            include_str!("templates/rust/array_data.rs"),
+            rust_type = info.rust_type,
+            rank = a.rank,
+            elemtype = info.elem,
+            elem_size = a.elemtype.size(),
+            binary_name = a.elemtype.binary_name(),
+            write_elem = write_elem,
+            read_elem = read_elem,
+        )?;
+
+        if let (Some(_), Some(new_raw_fn), Some(values_raw_fn)) = (
+            raw_pointer_type(pkg.manifest.backend).filter(|_| pkg.supports(Feature::RawArrays)),
+            &a.ops.new_raw,
+            &a.ops.values_raw,
+        ) {
+            writeln!(
+                config.output_file,
+                include_str!("templates/rust/array_raw.rs"),
+                futhark_type = info.futhark_type,
+                rust_type = info.rust_type,
+                rank = a.rank,
+                new_raw_fn = new_raw_fn,
+                values_raw_fn = values_raw_fn,
+                dim_params = dim_params.join(", "),
+                new_dim_args = new_dim_args.join(", ")
+            )?;
+        }
+
+        let index_fn = a.ops.index.as_ref();
+        if let Some(index_fn) = index_fn.filter(|_| pkg.supports(Feature::ArrayIndex)) {
+            let index_args: Vec<_> = (0..a.rank).map(|i| format!("index[{i}]")).collect();
+            let index_params: Vec<_> = (0..a.rank).map(|i| format!("i{i}: i64")).collect();
+            writeln!(
+                config.output_file,
+                include_str!("templates/rust/array_index.rs"),
+                futhark_type = info.futhark_type,
+                rust_type = info.rust_type,
+                rank = a.rank,
+                elemtype = info.elem,
+                index_fn = index_fn,
+                index_args = index_args.join(", "),
+                index_params = index_params.join(", "),
+            )?;
+        }
+
+        if config.async_entry_points {
+            writeln!(
+                config.output_file,
+                include_str!("templates/rust/array_async.rs"),
+                rust_type = info.rust_type,
+                elemtype = info.elem,
+                values_fn = a.ops.values,
+            )?;
+        }
         Ok(())
     }
 
     fn opaque_type(
         &mut self,
-        _pkg: &Package,
+        pkg: &Package,
         config: &mut Config,
         name: &str,
         ty: &manifest::OpaqueType,
     ) -> Result<(), Error> {
-        let futhark_type = convert_struct_name(&ty.ctype).to_string();
-        let mut rust_type = first_uppercase(futhark_type.strip_prefix("futhark_opaque_").unwrap());
-        if rust_type.chars().next().unwrap().is_numeric() || name.contains(' ') {
-            rust_type = format!("Type{}", rust_type);
-        }
+        let t = pkg.types().resolved(name);
+        let futhark_type = t.c_name().unwrap().to_string();
+        let rust_type = t.type_name().unwrap();
 
         writeln!(
             config.output_file,
@@ -121,11 +175,7 @@ impl Generate for Rust {
 
         let record = match &ty.record {
             Some(r) => r,
-            None => {
-                self.typemap.insert(name.to_string(), futhark_type.clone());
-                self.typemap.insert(futhark_type, rust_type);
-                return Ok(());
-            }
+            None => return Ok(()),
         };
 
         let mut new_call_args = vec![];
@@ -133,8 +183,8 @@ impl Generate for Rust {
         let mut new_extern_params = vec![];
         for field in record.fields.iter() {
             // Build new function
-            let a = Self::get_type(&self.typemap, &field.r#type);
-            let t = Self::get_type(&self.typemap, &a);
+            let field_type = pkg.types().resolved(&field.r#type);
+            let (a, t) = Self::get_type(&field_type);
 
             let u = if t == field.r#type {
                 t.to_string()
@@ -142,10 +192,10 @@ impl Generate for Rust {
                 format!("&{t}")
             };
 
-            if type_is_opaque(&a) {
+            if field_type.is_opaque() {
                 new_call_args.push(format!("field{}.data", field.name));
                 new_extern_params.push(format!("field{}: *const {a}", field.name));
-            } else if type_is_array(&t) {
+            } else if field_type.is_array() {
                 new_call_args.push(format!("field{}.ptr", field.name));
                 new_extern_params.push(format!("field{}: *const {a}", field.name));
             } else {
@@ -158,9 +208,9 @@ impl Generate for Rust {
             // Implement get function
 
             // If the output type is an array or opaque type then we need to wrap the return value
-            let (output, futhark_field_type) = if type_is_opaque(&a) || type_is_array(&t) {
+            let (output, futhark_field_type) = if !field_type.is_scalar() {
                 (
-                    format!("Ok({t}::from_ptr(self.ctx, out))"),
+                    format!("Ok({t}::from_ptr(self.ctx.clone(), out))"),
                     format!("*mut {a}"),
                 )
             } else {
@@ -191,15 +241,12 @@ impl Generate for Rust {
             new_extern_params = new_extern_params.join(", "),
         )?;
 
-        self.typemap.insert(name.to_string(), futhark_type.clone());
-        self.typemap.insert(futhark_type, rust_type);
-
         Ok(())
     }
 
     fn entry(
         &mut self,
-        _pkg: &Package,
+        pkg: &Package,
         config: &mut Config,
         name: &str,
         entry: &manifest::Entry,
@@ -210,56 +257,92 @@ impl Generate for Rust {
         let mut out_decl = Vec::new();
         let mut futhark_entry_params = Vec::new();
         let mut entry_return = Vec::new();
+        let mut entry_lifetimes = Vec::new();
+
+        // The `async` variants move their inputs into the worker and return `'static` outputs
+        let mut async_params = Vec::new();
+        let mut async_inputs = Vec::new();
+        let mut async_call_args = Vec::new();
+        let mut async_return_type = Vec::new();
+        let mut async_return = Vec::new();
 
         // Output arguments
         for (i, arg) in entry.outputs.iter().enumerate() {
-            let a = Self::get_type(&self.typemap, &arg.r#type);
+            let ty = pkg.types().resolved(&arg.r#type);
+            let (a, t) = Self::get_type(&ty);
 
             let name = format!("out{i}");
 
-            let t = Self::get_type(&self.typemap, &a);
-
-            if type_is_array(&t) || type_is_opaque(&a) {
+            if !ty.is_scalar() {
                 futhark_entry_params.push(format!("{name}: *mut *mut {a}"));
             } else {
                 futhark_entry_params.push(format!("{name}: *mut {a}"));
             }
 
-            if type_is_array(&t) || type_is_opaque(&a) {
+            if !ty.is_scalar() {
                 entry_return.push(format!("{t}::from_ptr(self, {name}.assume_init())",));
+                async_return.push(format!("{t}::from_ptr(ctx.clone(), {name}.assume_init())"));
+                async_return_type.push(format!("{t}<'static>"));
             } else {
                 entry_return.push(format!("{name}.assume_init()"));
+                async_return.push(format!("{name}.assume_init()"));
+                async_return_type.push(t.clone());
             }
 
             out_decl.push(format!("let mut {name} = std::mem::MaybeUninit::zeroed();"));
             call_args.push(format!("{name}.as_mut_ptr()"));
+            async_call_args.push(format!("{name}.as_mut_ptr()"));
             return_type.push(t);
         }
 
         // Input arguments
         for (i, arg) in entry.inputs.iter().enumerate() {
-            let a = Self::get_type(&self.typemap, &arg.r#type);
+            let ty = pkg.types().resolved(&arg.r#type);
+            let (a, t) = Self::get_type(&ty);
             let name = format!("input{i}");
 
-            let t = Self::get_type(&self.typemap, &a);
+            // Pending results can be passed directly to another entry point
+            let (param, input) = if config.pending_results {
+                if !ty.is_scalar() {
+                    entry_lifetimes.push(format!("'{name}"));
+                }
+                (
+                    format!("&impl Input<{t}<'{name}>>"),
+                    format!("{name}.input(sealed::Private)"),
+                )
+            } else {
+                (format!("&{t}<'_>"), name.clone())
+            };
+
+            if !ty.is_scalar() {
+                let shared = format!("std::sync::Arc<{t}<'static>>");
+                async_params.push(format!("{name}: impl Into<{shared}>"));
+                async_inputs.push(format!("let {name}: {shared} = {name}.into();"));
+            } else {
+                async_params.push(format!("{name}: {t}"));
+            }
 
-            if type_is_array(&t) {
+            if ty.is_array() {
                 futhark_entry_params.push(format!("{name}: *const {a}"));
 
-                entry_params.push(format!("{name}: &{t}"));
-                call_args.push(format!("{name}.ptr as *mut _"));
-            } else if type_is_opaque(&a) {
+                entry_params.push(format!("{name}: {param}"));
+                call_args.push(format!("{input}.ptr as *mut _"));
+                async_call_args.push(format!("{name}.ptr as *mut _"));
+            } else if ty.is_opaque() {
                 futhark_entry_params.push(format!("{name}: *const {a}"));
 
-                entry_params.push(format!("{name}: &{t}"));
-                call_args.push(format!("{name}.data as *mut _"));
+                entry_params.push(format!("{name}: {param}"));
+                call_args.push(format!("{input}.data as *mut _"));
+                async_call_args.push(format!("{name}.data as *mut _"));
             } else {
                 futhark_entry_params.push(format!("{name}: {a}"));
                 entry_params.push(format!("{name}: {t}"));
-                call_args.push(name);
+                call_args.push(name.clone());
+                async_call_args.push(name);
             }
         }
 
+        let output_values = entry_return.clone();
         let (entry_return_type, entry_return) = match entry.outputs.len() {
             0 => ("()".to_string(), "()".to_string()),
             1 => (return_type.join(", "), entry_return.join(", ")),
@@ -269,19 +352,79 @@ impl Generate for Rust {
             ),
         };
 
+        let entry_lifetimes = if entry_lifetimes.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", entry_lifetimes.join(", "))
+        };
+
+        let (pending_return_type, pending_return) =
+            if config.pending_results && entry.outputs.len() > 1 {
+                // Each output gets its own `Pending` so they can be passed on separately
+                (
+                    format!(
+                        "({})",
+                        return_type
+                            .iter()
+                            .map(|t| format!("Pending<'_, {t}>"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    format!(
+                        "{{ let synced = std::sync::Arc::default(); ({}) }}",
+                        output_values
+                            .iter()
+                            .map(|x| format!("Pending::new({x}, self, &synced)"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                )
+            } else if config.pending_results {
+                (
+                    format!("Pending<'_, {entry_return_type}>"),
+                    format!("Pending::new({entry_return}, self, &std::sync::Arc::default())"),
+                )
+            } else {
+                (entry_return_type.clone(), entry_return.clone())
+            };
+
         writeln!(
             config.output_file,
             include_str!("templates/rust/entry.rs"),
             entry_fn = entry.cfun,
             entry_name = name,
+            entry_lifetimes = entry_lifetimes,
             entry_params = entry_params.join(", "),
-            entry_return_type = entry_return_type,
+            entry_return_type = pending_return_type,
             out_decl = out_decl.join(";\n"),
             call_args = call_args.join(", "),
-            entry_return = entry_return,
+            entry_return = pending_return,
             futhark_entry_params = futhark_entry_params.join(", "),
         )?;
 
+        if config.async_entry_points {
+            let (async_return_type, async_return) = match entry.outputs.len() {
+                0 => ("()".to_string(), "()".to_string()),
+                1 => (async_return_type.join(", "), async_return.join(", ")),
+                _ => (
+                    format!("({})", async_return_type.join(", ")),
+                    format!("({})", async_return.join(", ")),
+                ),
+            };
+            writeln!(
+                config.output_file,
+                include_str!("templates/rust/entry_async.rs"),
+                entry_fn = entry.cfun,
+                entry_name = name,
+                entry_params = async_params.join(", "),
+                shared_inputs = async_inputs.join("\n"),
+                entry_return_type = async_return_type,
+                out_decl = out_decl.join(";\n"),
+                call_args = async_call_args.join(", "),
+                entry_return = async_return,
+            )?;
+        }
+
         Ok(())
     }
 
@@ -322,6 +465,21 @@ impl Generate for Rust {
             "let _ = &options.device;"
         };
 
+        // Log callbacks need `futhark_context_set_logging_file`
+        let logging_file = pkg.supports(Feature::LoggingFile);
+        let log = |s: &'static str| if logging_file { s } else { "" };
+        let configure_log = log("let log = match options.log_callback {
+                Some(callback) => match Log::new(context, callback) {
+                    Some(log) => Some(log),
+                    None => {
+                        futhark_context_free(context);
+                        futhark_context_config_free(config);
+                        return Err(Error::NullPtr);
+                    }
+                },
+                None => None,
+            };");
+
         writeln!(
             config.output_file,
             include_str!("templates/rust/context.rs"),
@@ -329,13 +487,42 @@ impl Generate for Rust {
             configure_num_threads = configure_num_threads,
             configure_set_device = configure_set_device,
             backend_extern_functions = backend_extern_functions,
+            log_options_field = log("log_callback: std::option::Option<LogCallback>,"),
+            log_options_init = log("log_callback: None,"),
+            log_context_field = log("log: std::option::Option<Log>,"),
+            log_context_init = log("log: None"),
+            log_context_value = log("log"),
+            configure_log = configure_log,
+            flush_log = log("if let Some(log) = &self.log { log.flush(); }"),
         )?;
 
+        if logging_file {
+            writeln!(config.output_file, include_str!("templates/rust/log.rs"))?;
+        }
+
+        if let Some(t) = raw_pointer_type(pkg.manifest.backend) {
+            writeln!(
+                config.output_file,
+                "/// Device memory handle used by `from_raw` and `as_raw`\npub type RawPtr = {t};"
+            )?;
+        }
+
+        if config.async_entry_points {
+            writeln!(config.output_file, include_str!("templates/rust/async.rs"))?;
+        }
+
+        if config.pending_results {
+            writeln!(config.output_file, include_str!("templates/rust/pending.rs"))?;
+        }
+
         Ok(())
     }
 
     fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
-        let _ = std::process::Command::new("rustfmt").arg(path).status();
+        let _ = std::process::Command::new("rustfmt")
+            .arg("--edition=2021")
+            .arg(path)
+            .status();
         Ok(())
     }
 }
@@ -0,0 +1,217 @@
+use crate::codegen;
+use crate::manifest::ElemType;
+use crate::*;
+use std::io::Write;
+
+/// Rust codegen for server mode, generates a client for an executable built using
+/// `futhark <backend> --server` instead of FFI bindings
+///
+/// Entry points run in a separate process and values are transferred using the Futhark binary
+/// data format, so a crash in the Futhark program doesn't take down the host process
+#[derive(Default)]
+pub struct RustServer;
+
+/// Returns true when any array, record field or entry point uses `f16`
+fn uses_f16(pkg: &Package) -> bool {
+    let is_f16 = |t: &codegen::Type| matches!(t, codegen::Type::Scalar(ElemType::F16));
+    let types = pkg.types().iter().any(|t| match t {
+        codegen::Type::Array(a) => a.elemtype == ElemType::F16,
+        codegen::Type::Record(r) => r.fields.iter().any(|f| is_f16(&f.r#type)),
+        _ => false,
+    });
+    let entries = pkg.types().entries().any(|e| {
+        e.inputs
+            .iter()
+            .chain(e.outputs.iter())
+            .any(|p| is_f16(&p.r#type))
+    });
+    types || entries
+}
+
+/// Rust type used in the generated API
+fn rust_type(ty: &codegen::Type) -> String {
+    Rust::get_type(ty).1
+}
+
+/// Expression converting the output variable `var` into a value of type `ty`
+fn output(ty: &codegen::Type, var: &str) -> String {
+    match ty {
+        codegen::Type::Scalar(_) => format!("{var}.get_scalar::<{}>()?", rust_type(ty)),
+        _ => format!("{}::from_var({var})", rust_type(ty)),
+    }
+}
+
+impl Generate for RustServer {
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "// Generated by futhark-bindgen\n")?;
+        let f16_elem = if uses_f16(pkg) {
+            "elem!(half::f16, b\" f16\");\n"
+        } else {
+            ""
+        };
+        writeln!(
+            config.output_file,
+            include_str!("templates/rust_server/server.rs"),
+            f16_elem = f16_elem,
+        )?;
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let ty = pkg.types().resolved(name);
+        writeln!(
+            config.output_file,
+            include_str!("templates/rust_server/array.rs"),
+            rust_type = rust_type(&ty),
+            type_name = format!("{name:?}"),
+            rank = a.rank,
+            elemtype = rust_type(&codegen::Type::Scalar(a.elemtype)),
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let t = pkg.types().resolved(name);
+        let opaque_type = rust_type(&t);
+        writeln!(
+            config.output_file,
+            include_str!("templates/rust_server/opaque.rs"),
+            rust_type = opaque_type,
+            type_name = format!("{name:?}"),
+        )?;
+
+        let record = match &ty.record {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        let mut new_params = Vec::new();
+        let mut field_vars = Vec::new();
+        let mut new_args = Vec::new();
+        for field in &record.fields {
+            let field_type = pkg.types().resolved(&field.r#type);
+            let t = rust_type(&field_type);
+            let param = format!("field{}", field.name);
+            let rust_field_type = if field_type.is_scalar() {
+                t.clone()
+            } else {
+                format!("{t}<'a>")
+            };
+            if field_type.is_scalar() {
+                new_params.push(format!("{param}: {t}"));
+                field_vars.push(format!(
+                    "let {param} = server.scalar({:?}, {param})?;",
+                    field.r#type
+                ));
+                new_args.push(format!("&{param}.name"));
+            } else {
+                new_params.push(format!("{param}: &{t}"));
+                new_args.push(format!("&{param}.var.name"));
+            }
+
+            writeln!(
+                config.output_file,
+                include_str!("templates/rust_server/record_project.rs"),
+                rust_type = opaque_type,
+                field_name = field.name,
+                rust_field_type = rust_field_type,
+                output = format!("Ok({})", output(&field_type, "out")),
+            )?;
+        }
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/rust_server/record.rs"),
+            rust_type = opaque_type,
+            type_name = format!("{name:?}"),
+            new_params = new_params.join(", "),
+            field_vars = field_vars.join("\n"),
+            new_args = new_args.join(", "),
+        )?;
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut entry_params = Vec::new();
+        let mut input_vars = Vec::new();
+        let mut inputs = Vec::new();
+        for (i, arg) in entry.inputs.iter().enumerate() {
+            let ty = pkg.types().resolved(&arg.r#type);
+            let t = rust_type(&ty);
+            let name = format!("input{i}");
+            if ty.is_scalar() {
+                entry_params.push(format!("{name}: {t}"));
+                input_vars.push(format!(
+                    "let {name} = self.scalar({:?}, {name})?;",
+                    arg.r#type
+                ));
+                inputs.push(format!("&{name}"));
+            } else {
+                entry_params.push(format!("{name}: &{t}<'_>"));
+                inputs.push(format!("&{name}.var"));
+            }
+        }
+
+        let mut out_decl = Vec::new();
+        let mut outputs = Vec::new();
+        let mut return_type = Vec::new();
+        let mut entry_return = Vec::new();
+        for (i, arg) in entry.outputs.iter().enumerate() {
+            let ty = pkg.types().resolved(&arg.r#type);
+            let name = format!("out{i}");
+            out_decl.push(format!("let {name} = Var::output(self);"));
+            outputs.push(format!("&{name}"));
+            if ty.is_scalar() {
+                return_type.push(rust_type(&ty));
+            } else {
+                return_type.push(format!("{}<'_>", rust_type(&ty)));
+            }
+            entry_return.push(output(&ty, &name));
+        }
+
+        let (entry_return_type, entry_return) = match entry.outputs.len() {
+            0 => ("()".to_string(), "()".to_string()),
+            1 => (return_type.join(", "), entry_return.join(", ")),
+            _ => (
+                format!("({})", return_type.join(", ")),
+                format!("({})", entry_return.join(", ")),
+            ),
+        };
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/rust_server/entry.rs"),
+            entry_name = name,
+            entry_params = entry_params.join(", "),
+            entry_return_type = entry_return_type,
+            input_vars = input_vars.join("\n"),
+            out_decl = out_decl.join("\n"),
+            outputs = outputs.join(", "),
+            inputs = inputs.join(", "),
+            entry_return = entry_return,
+        )?;
+        Ok(())
+    }
+
+    fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
+        Rust.format(path)
+    }
+}
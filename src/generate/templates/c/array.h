@@ -0,0 +1,55 @@
+
+// Number of elements in an array with {rank} dimensions and {elemtype} elements
+static inline int64_t {prefix}_len_{suffix}(struct {prefix}_context *c, struct futhark_{suffix} *arr) {{
+  const int64_t *shape = futhark_shape_{suffix}(c->ctx, arr);
+  int64_t len = 1;
+  for (int i = 0; i < {rank}; i++) {{
+    len *= shape[i];
+  }}
+  return len;
+}}
+
+// Create a new array from row-major `data`, returns `NULL` on failure
+static inline struct futhark_{suffix} *{prefix}_new_{suffix}(struct {prefix}_context *c, const {c_elemtype} *data,
+    {new_dim_params}, char *err, size_t err_len) {{
+  struct futhark_{suffix} *arr = futhark_new_{suffix}(c->ctx, data, {dim_args});
+  if (arr == NULL) {{
+    {prefix}_fetch_error(c, err, err_len);
+    return NULL;
+  }}
+  if ({prefix}_auto_sync(c, err, err_len) != 0) {{
+    futhark_free_{suffix}(c->ctx, arr);
+    return NULL;
+  }}
+  return arr;
+}}
+
+// Copy the array values into a new buffer in row-major order, the buffer must be released
+// using `free`. The number of elements is written to `len` when it isn't `NULL`. Returns
+// `NULL` on failure
+static inline {c_elemtype} *{prefix}_values_{suffix}(struct {prefix}_context *c, struct futhark_{suffix} *arr,
+    int64_t *len, char *err, size_t err_len) {{
+  int64_t n = {prefix}_len_{suffix}(c, arr);
+  // Allocate at least one element so an empty array isn't mistaken for an error
+  {c_elemtype} *data = ({c_elemtype} *)malloc((n > 0 ? (size_t)n : 1) * sizeof({c_elemtype}));
+  if (data == NULL) {{
+    {prefix}_set_error(err, err_len, "out of memory");
+    return NULL;
+  }}
+  if ({prefix}_check(c, futhark_values_{suffix}(c->ctx, arr, data), err, err_len) != 0 ||
+      {prefix}_sync(c, err, err_len) != 0) {{
+    free(data);
+    return NULL;
+  }}
+  if (len != NULL) {{
+    *len = n;
+  }}
+  return data;
+}}
+
+// Free the array, `NULL` is ignored
+static inline void {prefix}_free_{suffix}(struct {prefix}_context *c, struct futhark_{suffix} *arr) {{
+  if (arr != NULL) {{
+    futhark_free_{suffix}(c->ctx, arr);
+  }}
+}}
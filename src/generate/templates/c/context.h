@@ -0,0 +1,117 @@
+#ifndef {guard}
+#define {guard}
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include "{header}"
+
+#ifdef __cplusplus
+extern "C" {{
+#endif
+
+// Functions that can fail return the Futhark error code, or `-1` if the error was raised by
+// these helpers. When `err` is not `NULL` the error message is written to `err`, truncated
+// to `err_len` bytes
+
+// Options used to create a new context
+struct {prefix}_options {{
+  bool debug;
+  bool profile;
+  bool log;
+  // Futhark keeps a pointer to the cache file, it must outlive the context
+  const char *cache_file;
+  // Sync the context after each call that creates values
+  bool auto_sync;{backend_options}
+}};
+
+// Context and config wrapper
+struct {prefix}_context {{
+  struct futhark_context_config *config;
+  struct futhark_context *ctx;
+  bool auto_sync;
+}};
+
+// Get the default options
+static inline struct {prefix}_options {prefix}_default_options(void) {{
+  struct {prefix}_options options = {{0}};
+  options.auto_sync = true;
+  return options;
+}}
+
+// Write a message into the caller-provided error buffer
+static inline void {prefix}_set_error(char *err, size_t err_len, const char *msg) {{
+  if (err != NULL && err_len > 0) {{
+    snprintf(err, err_len, "%s", msg != NULL ? msg : "unknown error");
+  }}
+}}
+
+// Fetch the last Futhark error message into the caller-provided error buffer
+static inline void {prefix}_fetch_error(struct {prefix}_context *c, char *err, size_t err_len) {{
+  char *msg = futhark_context_get_error(c->ctx);
+  {prefix}_set_error(err, err_len, msg);
+  free(msg);
+}}
+
+// Check the result of a Futhark function, fetching the error message if `rc` is non-zero
+static inline int {prefix}_check(struct {prefix}_context *c, int rc, char *err, size_t err_len) {{
+  if (rc != 0) {{
+    {prefix}_fetch_error(c, err, err_len);
+  }}
+  return rc;
+}}
+
+// Create a new context, `options` may be `NULL` to use the default options
+static inline int {prefix}_context_init(struct {prefix}_context *c, const struct {prefix}_options *options,
+    char *err, size_t err_len) {{
+  struct {prefix}_options defaults = {prefix}_default_options();
+  if (options == NULL) {{
+    options = &defaults;
+  }}
+  c->ctx = NULL;
+  c->config = futhark_context_config_new();
+  if (c->config == NULL) {{
+    {prefix}_set_error(err, err_len, "unable to create context config");
+    return -1;
+  }}
+  futhark_context_config_set_debugging(c->config, options->debug);
+  futhark_context_config_set_profiling(c->config, options->profile);
+  futhark_context_config_set_logging(c->config, options->log);
+  if (options->cache_file != NULL) {{
+    futhark_context_config_set_cache_file(c->config, options->cache_file);
+  }}{backend_config}
+  c->ctx = futhark_context_new(c->config);
+  if (c->ctx == NULL) {{
+    futhark_context_config_free(c->config);
+    c->config = NULL;
+    {prefix}_set_error(err, err_len, "unable to create context");
+    return -1;
+  }}
+  c->auto_sync = options->auto_sync;
+  return 0;
+}}
+
+// Free the context, all values created using the context must be freed first
+static inline void {prefix}_context_free(struct {prefix}_context *c) {{
+  if (c->ctx != NULL) {{
+    futhark_context_sync(c->ctx);
+    futhark_context_free(c->ctx);
+    c->ctx = NULL;
+  }}
+  if (c->config != NULL) {{
+    futhark_context_config_free(c->config);
+    c->config = NULL;
+  }}
+}}
+
+// Wait for all pending operations to finish
+static inline int {prefix}_sync(struct {prefix}_context *c, char *err, size_t err_len) {{
+  return {prefix}_check(c, futhark_context_sync(c->ctx), err, err_len);
+}}
+
+// Sync the context if `auto_sync` is enabled
+static inline int {prefix}_auto_sync(struct {prefix}_context *c, char *err, size_t err_len) {{
+  return c->auto_sync ? {prefix}_sync(c, err, err_len) : 0;
+}}
@@ -0,0 +1,10 @@
+
+// Entry point: {name}
+static inline int {entry_name}(struct {prefix}_context *c, {params}char *err, size_t err_len) {{
+  int rc = {prefix}_check(c, {entry_fn}(c->ctx{call_args}), err, err_len);
+  if (rc != 0) {{
+{clear_outputs}    return rc;
+  }}
+  rc = {prefix}_auto_sync(c, err, err_len);
+{free_outputs}  return rc;
+}}
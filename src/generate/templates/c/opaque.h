@@ -0,0 +1,7 @@
+
+// Free a value of type {name}, `NULL` is ignored
+static inline void {prefix}_free_{suffix}(struct {prefix}_context *c, struct futhark_{suffix} *obj) {{
+  if (obj != NULL) {{
+    futhark_free_{suffix}(c->ctx, obj);
+  }}
+}}
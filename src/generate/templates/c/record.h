@@ -0,0 +1,15 @@
+
+// Create a new value of type {name} from its fields
+static inline int {new_name}(struct {prefix}_context *c, struct futhark_{suffix} **out, {new_params},
+    char *err, size_t err_len) {{
+  int rc = {prefix}_check(c, {new_fn}(c->ctx, out, {new_args}), err, err_len);
+  if (rc != 0) {{
+    *out = NULL;
+    return rc;
+  }}
+  if ((rc = {prefix}_auto_sync(c, err, err_len)) != 0) {{
+    futhark_free_{suffix}(c->ctx, *out);
+    *out = NULL;
+  }}
+  return rc;
+}}
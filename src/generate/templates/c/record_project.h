@@ -0,0 +1,12 @@
+
+// Get the {field} field of a value of type {name}
+static inline int {project_name}(struct {prefix}_context *c, {out_param},
+    const struct futhark_{suffix} *obj, char *err, size_t err_len) {{
+  int rc = {prefix}_check(c, {project_fn}(c->ctx, out, obj), err, err_len);
+  if (rc != 0) {{
+    return rc;
+  }}
+  if ((rc = {prefix}_auto_sync(c, err, err_len)) != 0) {{
+{free_out}  }}
+  return rc;
+}}
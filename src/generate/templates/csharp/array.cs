@@ -0,0 +1,83 @@
+
+internal static partial class Native
+{{
+    [DllImport(Lib)]
+    internal static extern IntPtr {new_fn}(Context ctx, ref {native_elemtype} data, {new_dim_params});
+
+    [DllImport(Lib)]
+    internal static extern int {free_fn}(IntPtr ctx, IntPtr arr);
+
+    [DllImport(Lib)]
+    internal static extern int {values_fn}(Context ctx, {cs_type} arr, ref {native_elemtype} data);
+
+    [DllImport(Lib)]
+    internal static extern IntPtr {shape_fn}(Context ctx, IntPtr arr);
+}}
+
+/// <summary>Array with {rank} dimensions and {elemtype} elements</summary>
+public sealed class {cs_type} : FutharkValue
+{{
+    private readonly long[] shape;
+
+    /// <summary>Array dimensions</summary>
+    public ReadOnlySpan<long> Shape => shape;
+
+    internal {cs_type}(Context ctx, IntPtr ptr) : base(ctx, ptr)
+    {{
+        shape = new long[{rank}];
+        Marshal.Copy(Native.{shape_fn}(ctx, ptr), shape, 0, {rank});
+    }}
+
+    /// <summary>Create a new array from row-major <paramref name="data"/></summary>
+    public {cs_type}(Context ctx, ReadOnlySpan<{cs_elemtype}> data, {new_dim_params})
+        : this(ctx, New(ctx, data, {dim_args}))
+    {{
+        ctx.AutoSyncContext();
+    }}
+
+    private static IntPtr New(Context ctx, ReadOnlySpan<{cs_elemtype}> data, {new_dim_params})
+    {{
+        if (data.Length != {dim_product})
+        {{
+            throw new ArgumentException("Data does not match the array shape", nameof(data));
+        }}
+        IntPtr ptr = Native.{new_fn}(ctx, ref MemoryMarshal.GetReference({native_data}), {dim_args});
+        if (ptr == IntPtr.Zero)
+        {{
+            throw new FutharkException(0, ctx.GetError() ?? "Unable to create array");
+        }}
+        return ptr;
+    }}
+
+    internal override int Free(IntPtr ctx, IntPtr ptr) => Native.{free_fn}(ctx, ptr);
+
+    /// <summary>Copy the array values into <paramref name="output"/> in row-major order</summary>
+    public void Values(Span<{cs_elemtype}> output)
+    {{
+        long len = 1;
+        foreach (long n in shape)
+        {{
+            len *= n;
+        }}
+        if (output.Length != len)
+        {{
+            throw new ArgumentException("Output does not match the array shape", nameof(output));
+        }}
+        Context.Check(Native.{values_fn}(Context, this, ref MemoryMarshal.GetReference({native_output})));
+        // Futhark may write to the output asynchronously
+        Context.Sync();
+    }}
+
+    /// <summary>Copy the array values into a new array in row-major order</summary>
+    public {cs_elemtype}[] Get()
+    {{
+        long len = 1;
+        foreach (long n in shape)
+        {{
+            len *= n;
+        }}
+        var output = new {cs_elemtype}[len];
+        Values(output);
+        return output;
+    }}
+}}
@@ -0,0 +1,218 @@
+#nullable enable
+
+using System;
+using System.Runtime.InteropServices;
+
+namespace {namespace};
+
+/// <summary>Exception thrown when a Futhark function fails</summary>
+public class FutharkException : Exception
+{{
+    /// <summary>Futhark error code, or 0 if the error was raised by the bindings</summary>
+    public int Code {{ get; }}
+
+    public FutharkException(int code, string message) : base(message)
+    {{
+        Code = code;
+    }}
+}}
+
+/// <summary>Options used to configure a new <see cref="Context"/></summary>
+public sealed class Options
+{{
+    public bool Debug {{ get; set; }}
+    public bool Profile {{ get; set; }}
+    public bool Log {{ get; set; }}
+    public string? CacheFile {{ get; set; }}
+
+    /// <summary>Sync the context after copying data to or from the device</summary>
+    public bool AutoSync {{ get; set; }} = true;{backend_options}
+}}
+
+internal static partial class Native
+{{
+    internal const string Lib = "{library_name}";
+
+    [DllImport(Lib)]
+    internal static extern IntPtr futhark_context_config_new();
+
+    [DllImport(Lib)]
+    internal static extern void futhark_context_config_free(IntPtr cfg);
+
+    [DllImport(Lib)]
+    internal static extern void futhark_context_config_set_debugging(IntPtr cfg, int flag);
+
+    [DllImport(Lib)]
+    internal static extern void futhark_context_config_set_profiling(IntPtr cfg, int flag);
+
+    [DllImport(Lib)]
+    internal static extern void futhark_context_config_set_logging(IntPtr cfg, int flag);
+
+    [DllImport(Lib)]
+    internal static extern void futhark_context_config_set_cache_file(IntPtr cfg, IntPtr path);
+{backend_imports}
+    [DllImport(Lib)]
+    internal static extern IntPtr futhark_context_new(IntPtr cfg);
+
+    [DllImport(Lib)]
+    internal static extern void futhark_context_free(IntPtr ctx);
+
+    [DllImport(Lib)]
+    internal static extern int futhark_context_sync(Context ctx);
+
+    [DllImport(Lib)]
+    internal static extern int futhark_context_sync(IntPtr ctx);
+
+    [DllImport(Lib)]
+    internal static extern int futhark_context_clear_caches(Context ctx);
+
+    [DllImport(Lib)]
+    internal static extern void futhark_context_pause_profiling(Context ctx);
+
+    [DllImport(Lib)]
+    internal static extern void futhark_context_unpause_profiling(Context ctx);
+
+    [DllImport(Lib)]
+    internal static extern IntPtr futhark_context_get_error(Context ctx);
+
+    [DllImport(Lib)]
+    internal static extern IntPtr futhark_context_report(Context ctx);
+
+    /// <summary>Convert a string allocated by Futhark and free it</summary>
+    internal static string? TakeString(IntPtr s)
+    {{
+        if (s == IntPtr.Zero)
+        {{
+            return null;
+        }}
+        string? str = Marshal.PtrToStringUTF8(s);
+        // On Unix `FreeHGlobal` calls `free`, which matches Futhark's allocator
+        Marshal.FreeHGlobal(s);
+        return str;
+    }}
+}}
+
+/// <summary>
+/// Futhark context, values created using the context keep it alive until they are disposed or
+/// finalized
+/// </summary>
+public sealed partial class Context : SafeHandle
+{{
+    private readonly IntPtr config;
+
+    // Futhark keeps pointers to the strings passed to the config
+    private readonly IntPtr cacheFile;
+    private readonly IntPtr device = IntPtr.Zero;
+
+    /// <summary>Sync the context after copying data to or from the device</summary>
+    public bool AutoSync {{ get; set; }}
+
+    public override bool IsInvalid => handle == IntPtr.Zero;
+
+    public Context(Options? options = null) : base(IntPtr.Zero, true)
+    {{
+        options ??= new Options();
+        config = Native.futhark_context_config_new();
+        if (config == IntPtr.Zero)
+        {{
+            throw new FutharkException(0, "Unable to create context config");
+        }}
+        Native.futhark_context_config_set_debugging(config, options.Debug ? 1 : 0);
+        Native.futhark_context_config_set_profiling(config, options.Profile ? 1 : 0);
+        Native.futhark_context_config_set_logging(config, options.Log ? 1 : 0);
+        if (options.CacheFile != null)
+        {{
+            cacheFile = Marshal.StringToCoTaskMemUTF8(options.CacheFile);
+            Native.futhark_context_config_set_cache_file(config, cacheFile);
+        }}{backend_config}
+        IntPtr ctx = Native.futhark_context_new(config);
+        if (ctx == IntPtr.Zero)
+        {{
+            FreeConfig();
+            throw new FutharkException(0, "Unable to create context");
+        }}
+        SetHandle(ctx);
+        AutoSync = options.AutoSync;
+    }}
+
+    private void FreeConfig()
+    {{
+        Native.futhark_context_config_free(config);
+        Marshal.FreeCoTaskMem(cacheFile);
+        Marshal.FreeCoTaskMem(device);
+    }}
+
+    protected override bool ReleaseHandle()
+    {{
+        Native.futhark_context_sync(handle);
+        Native.futhark_context_free(handle);
+        FreeConfig();
+        return true;
+    }}
+
+    /// <summary>Throw a <see cref="FutharkException"/> if <paramref name="rc"/> is non-zero</summary>
+    internal void Check(int rc)
+    {{
+        if (rc != 0)
+        {{
+            throw new FutharkException(rc, GetError() ?? "");
+        }}
+    }}
+
+    internal void AutoSyncContext()
+    {{
+        if (AutoSync)
+        {{
+            Sync();
+        }}
+    }}
+
+    /// <summary>Wait for all pending operations to finish, if <see cref="AutoSync"/> is enabled this shouldn't be needed</summary>
+    public void Sync() => Check(Native.futhark_context_sync(this));
+
+    /// <summary>Clear Futhark's internal caches</summary>
+    public void ClearCaches() => Check(Native.futhark_context_clear_caches(this));
+
+    public void PauseProfiling() => Native.futhark_context_pause_profiling(this);
+
+    public void UnpauseProfiling() => Native.futhark_context_unpause_profiling(this);
+
+    /// <summary>Get the last error message</summary>
+    public string? GetError() => Native.TakeString(Native.futhark_context_get_error(this));
+
+    /// <summary>Get the profiling report</summary>
+    public string? Report() => Native.TakeString(Native.futhark_context_report(this));
+}}
+
+/// <summary>Base class for Futhark arrays and opaque values</summary>
+public abstract class FutharkValue : SafeHandle
+{{
+    /// <summary>Context the value belongs to</summary>
+    public Context Context {{ get; }}
+
+    public override bool IsInvalid => handle == IntPtr.Zero;
+
+    internal FutharkValue(Context ctx, IntPtr ptr) : base(IntPtr.Zero, true)
+    {{
+        if (ptr == IntPtr.Zero)
+        {{
+            throw new FutharkException(0, "NULL pointer encountered");
+        }}
+        Context = ctx;
+        // Keep the context alive until the value has been released
+        bool success = false;
+        ctx.DangerousAddRef(ref success);
+        SetHandle(ptr);
+    }}
+
+    internal abstract int Free(IntPtr ctx, IntPtr ptr);
+
+    protected override bool ReleaseHandle()
+    {{
+        // The context may already be disposed, but it will not be released until this reference
+        // is dropped
+        Free(Context.DangerousGetHandle(), handle);
+        Context.DangerousRelease();
+        return true;
+    }}
+}}
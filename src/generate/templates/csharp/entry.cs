@@ -0,0 +1,16 @@
+
+internal static partial class Native
+{{
+    [DllImport(Lib)]
+    internal static extern int {entry_fn}({extern_params});
+}}
+
+public sealed partial class Context
+{{
+    /// <summary>Entry point: {name}</summary>
+    public {return_type} {entry_name}({entry_params})
+    {{
+        Check(Native.{entry_fn}({call_args}));
+        {entry_return}
+    }}
+}}
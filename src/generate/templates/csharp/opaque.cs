@@ -0,0 +1,16 @@
+
+internal static partial class Native
+{{
+    [DllImport(Lib)]
+    internal static extern int {free_fn}(IntPtr ctx, IntPtr obj);
+}}
+
+/// <summary>Futhark type: {name}</summary>
+public sealed partial class {cs_type} : FutharkValue
+{{
+    internal {cs_type}(Context ctx, IntPtr ptr) : base(ctx, ptr)
+    {{
+    }}
+
+    internal override int Free(IntPtr ctx, IntPtr ptr) => Native.{free_fn}(ctx, ptr);
+}}
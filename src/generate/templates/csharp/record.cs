@@ -0,0 +1,21 @@
+
+internal static partial class Native
+{{
+    [DllImport(Lib)]
+    internal static extern int {new_fn}(Context ctx, out IntPtr obj, {new_extern_params});
+{project_imports}}}
+
+public sealed partial class {cs_type}
+{{
+    /// <summary>Create a new {cs_type} from its fields</summary>
+    public {cs_type}(Context ctx, {new_params}) : this(ctx, New(ctx, {new_args}))
+    {{
+        ctx.AutoSyncContext();
+    }}
+
+    private static IntPtr New(Context ctx, {new_params})
+    {{
+        ctx.Check(Native.{new_fn}(ctx, out IntPtr obj, {new_call_args}));
+        return obj;
+    }}
+{project_functions}}}
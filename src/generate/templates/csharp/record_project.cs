@@ -0,0 +1,8 @@
+
+    /// <summary>Get the {name} field</summary>
+    public {cs_field_type} Get{field_name}()
+    {{
+        Context.Check(Native.{project_fn}(Context, out {out_type} output, this));
+        Context.AutoSyncContext();
+        return {output};
+    }}
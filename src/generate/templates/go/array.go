@@ -0,0 +1,71 @@
+
+// {go_type} is an array with {rank} dimensions and {elemtype} elements
+type {go_type} struct {{
+	ptr   *C.struct_{futhark_type}
+	shape [{rank}]int64
+	ctx   *Context
+}}
+
+// New{go_type} creates a new array with the given dimensions and initializes it with data
+func New{go_type}(ctx *Context, data []{go_elemtype}, dims [{rank}]int64) (*{go_type}, error) {{
+	if int64(len(data)) != shapeSize(dims[:]) {{
+		return nil, ErrInvalidShape
+	}}
+	ptr := C.{new_fn}(ctx.ctx, (*{c_elemtype})(slicePtr(data)), {dim_args})
+	runtime.KeepAlive(data)
+	if ptr == nil {{
+		return nil, ErrNullPtr
+	}}
+	if err := ctx.autoSyncCtx(); err != nil {{
+		C.{free_fn}(ctx.ctx, ptr)
+		return nil, err
+	}}
+	return new{go_type}FromPtr(ctx, ptr), nil
+}}
+
+func new{go_type}FromPtr(ctx *Context, ptr *C.struct_{futhark_type}) *{go_type} {{
+	a := &{go_type}{{ptr: ptr, ctx: ctx}}
+	shape := unsafe.Slice((*int64)(unsafe.Pointer(C.{shape_fn}(ctx.ctx, ptr))), {rank})
+	copy(a.shape[:], shape)
+	runtime.SetFinalizer(a, (*{go_type}).Free)
+	return a
+}}
+
+// Shape returns the array dimensions
+func (a *{go_type}) Shape() [{rank}]int64 {{
+	return a.shape
+}}
+
+// Values copies the array into out, which must have the same number of elements as the array
+//
+// The context is always synced before returning since Futhark may write to out asynchronously
+func (a *{go_type}) Values(out []{go_elemtype}) error {{
+	if int64(len(out)) != shapeSize(a.shape[:]) {{
+		return ErrInvalidShape
+	}}
+	if rc := C.{values_fn}(a.ctx.ctx, a.ptr, (*{c_elemtype})(slicePtr(out))); rc != 0 {{
+		return a.ctx.error(rc)
+	}}
+	err := a.ctx.Sync()
+	runtime.KeepAlive(out)
+	return err
+}}
+
+// Get returns a new slice containing the array values
+func (a *{go_type}) Get() ([]{go_elemtype}, error) {{
+	out := make([]{go_elemtype}, shapeSize(a.shape[:]))
+	if err := a.Values(out); err != nil {{
+		return nil, err
+	}}
+	return out, nil
+}}
+
+// Free releases the array, it is called automatically by the garbage collector
+func (a *{go_type}) Free() {{
+	if a.ptr == nil || a.ctx.ctx == nil {{
+		return
+	}}
+	C.{free_fn}(a.ctx.ctx, a.ptr)
+	a.ptr = nil
+	runtime.SetFinalizer(a, nil)
+}}
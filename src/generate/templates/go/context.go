@@ -0,0 +1,181 @@
+package {package_name}
+
+/*
+#cgo CFLAGS: -O3 -std=c99
+#cgo LDFLAGS: {ldflags}
+#include <stdlib.h>
+#include "{header}"
+*/
+import "C"
+
+import (
+	"errors"
+	"fmt"
+	"runtime"
+	"unsafe"
+)
+
+// ErrNullPtr is returned when Futhark returns a NULL pointer
+var ErrNullPtr = errors.New("futhark: NULL pointer encountered")
+
+// ErrInvalidShape is returned when the data passed to an array does not match its shape
+var ErrInvalidShape = errors.New("futhark: invalid shape")
+
+// Error is returned when a Futhark function fails
+type Error struct {{
+	Code    int
+	Message string
+}}
+
+func (e *Error) Error() string {{
+	if e.Message == "" {{
+		return fmt.Sprintf("futhark: error code %d", e.Code)
+	}}
+	return fmt.Sprintf("futhark: error code %d: %s", e.Code, e.Message)
+}}
+
+// Options are used to configure a new Context
+type Options struct {{
+	Debug     bool
+	Profile   bool
+	Log       bool
+	CacheFile string
+	AutoSync  bool
+{backend_options}
+}}
+
+// DefaultOptions returns the options used by NewContext when nil is passed
+func DefaultOptions() *Options {{
+	return &Options{{AutoSync: true}}
+}}
+
+// Context wraps a Futhark context and its configuration
+type Context struct {{
+	config    *C.struct_futhark_context_config
+	ctx       *C.struct_futhark_context
+	cacheFile *C.char
+	device    *C.char
+	autoSync  bool
+}}
+
+// NewContext creates a new Futhark context, if opts is nil then DefaultOptions is used
+func NewContext(opts *Options) (*Context, error) {{
+	if opts == nil {{
+		opts = DefaultOptions()
+	}}
+	config := C.futhark_context_config_new()
+	if config == nil {{
+		return nil, ErrNullPtr
+	}}
+	C.futhark_context_config_set_debugging(config, boolToInt(opts.Debug))
+	C.futhark_context_config_set_profiling(config, boolToInt(opts.Profile))
+	C.futhark_context_config_set_logging(config, boolToInt(opts.Log))
+	var cacheFile *C.char
+	if opts.CacheFile != "" {{
+		cacheFile = C.CString(opts.CacheFile)
+		C.futhark_context_config_set_cache_file(config, cacheFile)
+	}}
+	var device *C.char
+{backend_config}
+	ctx := C.futhark_context_new(config)
+	if ctx == nil {{
+		C.futhark_context_config_free(config)
+		C.free(unsafe.Pointer(cacheFile))
+		C.free(unsafe.Pointer(device))
+		return nil, ErrNullPtr
+	}}
+	c := &Context{{config: config, ctx: ctx, cacheFile: cacheFile, device: device, autoSync: opts.AutoSync}}
+	runtime.SetFinalizer(c, (*Context).Free)
+	return c, nil
+}}
+
+// Free releases the context, it is called automatically by the garbage collector
+func (c *Context) Free() {{
+	if c.ctx == nil {{
+		return
+	}}
+	C.futhark_context_sync(c.ctx)
+	C.futhark_context_free(c.ctx)
+	C.futhark_context_config_free(c.config)
+	C.free(unsafe.Pointer(c.cacheFile))
+	C.free(unsafe.Pointer(c.device))
+	c.ctx = nil
+	runtime.SetFinalizer(c, nil)
+}}
+
+// Sync waits for all pending operations to finish, if AutoSync is enabled this shouldn't be needed
+func (c *Context) Sync() error {{
+	if rc := C.futhark_context_sync(c.ctx); rc != 0 {{
+		return c.error(rc)
+	}}
+	return nil
+}}
+
+func (c *Context) autoSyncCtx() error {{
+	if c.autoSync {{
+		return c.Sync()
+	}}
+	return nil
+}}
+
+// ClearCaches clears Futhark's internal caches
+func (c *Context) ClearCaches() error {{
+	if rc := C.futhark_context_clear_caches(c.ctx); rc != 0 {{
+		return c.error(rc)
+	}}
+	return nil
+}}
+
+// PauseProfiling pauses profiling
+func (c *Context) PauseProfiling() {{
+	C.futhark_context_pause_profiling(c.ctx)
+}}
+
+// UnpauseProfiling resumes profiling
+func (c *Context) UnpauseProfiling() {{
+	C.futhark_context_unpause_profiling(c.ctx)
+}}
+
+// Report returns the profiling report
+func (c *Context) Report() string {{
+	return takeString(C.futhark_context_report(c.ctx))
+}}
+
+// LastError returns the last error message or an empty string
+func (c *Context) LastError() string {{
+	return takeString(C.futhark_context_get_error(c.ctx))
+}}
+
+func (c *Context) error(rc C.int) error {{
+	return &Error{{Code: int(rc), Message: c.LastError()}}
+}}
+
+func takeString(s *C.char) string {{
+	if s == nil {{
+		return ""
+	}}
+	defer C.free(unsafe.Pointer(s))
+	return C.GoString(s)
+}}
+
+func boolToInt(b bool) C.int {{
+	if b {{
+		return 1
+	}}
+	return 0
+}}
+
+func slicePtr[T any](s []T) unsafe.Pointer {{
+	if len(s) == 0 {{
+		return nil
+	}}
+	return unsafe.Pointer(&s[0])
+}}
+
+func shapeSize(shape []int64) int64 {{
+	size := int64(1)
+	for _, n := range shape {{
+		size *= n
+	}}
+	return size
+}}
@@ -0,0 +1,10 @@
+
+// {entry_go_name} calls the {entry_name} entry point
+func (c *Context) {entry_go_name}({entry_params}) ({entry_return_type}) {{
+	{out_decl}
+	{keep_alive}
+	if rc := C.{entry_fn}(c.ctx, {call_args}); rc != 0 {{
+		return {error_return}
+	}}
+	return {entry_return}
+}}
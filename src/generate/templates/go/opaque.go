@@ -0,0 +1,22 @@
+
+// {go_type} wraps the Futhark type {name}
+type {go_type} struct {{
+	ptr *C.struct_{futhark_type}
+	ctx *Context
+}}
+
+func new{go_type}FromPtr(ctx *Context, ptr *C.struct_{futhark_type}) *{go_type} {{
+	t := &{go_type}{{ptr: ptr, ctx: ctx}}
+	runtime.SetFinalizer(t, (*{go_type}).Free)
+	return t
+}}
+
+// Free releases the value, it is called automatically by the garbage collector
+func (t *{go_type}) Free() {{
+	if t.ptr == nil || t.ctx.ctx == nil {{
+		return
+	}}
+	C.{free_fn}(t.ctx.ctx, t.ptr)
+	t.ptr = nil
+	runtime.SetFinalizer(t, nil)
+}}
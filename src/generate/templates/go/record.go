@@ -0,0 +1,14 @@
+
+// New{go_type} creates a new {go_type} from its fields
+func New{go_type}(ctx *Context, {new_params}) (*{go_type}, error) {{
+	var out *C.struct_{futhark_type}
+	{keep_alive}
+	if rc := C.{new_fn}(ctx.ctx, &out, {new_call_args}); rc != 0 {{
+		return nil, ctx.error(rc)
+	}}
+	if err := ctx.autoSyncCtx(); err != nil {{
+		C.{free_fn}(ctx.ctx, out)
+		return nil, err
+	}}
+	return new{go_type}FromPtr(ctx, out), nil
+}}
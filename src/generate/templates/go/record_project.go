@@ -0,0 +1,13 @@
+
+// Get{field_name} returns the {name} field
+func (t *{go_type}) Get{field_name}() ({go_field_type}, error) {{
+	var out {c_field_type}
+	if rc := C.{project_fn}(t.ctx.ctx, &out, t.ptr); rc != 0 {{
+		return {zero}, t.ctx.error(rc)
+	}}
+	runtime.KeepAlive(t)
+	if err := t.ctx.autoSyncCtx(); err != nil {{
+		return {zero}, err
+	}}
+	return {output}, nil
+}}
@@ -0,0 +1,53 @@
+
+data Raw{hs_type}
+
+foreign import ccall unsafe "{new_fn}"
+  {new_fn} :: Ptr FutharkContext -> Ptr {c_elemtype} -> {dim_types} -> IO (Ptr Raw{hs_type})
+
+foreign import ccall unsafe "{free_fn}"
+  {free_fn} :: Ptr FutharkContext -> Ptr Raw{hs_type} -> IO CInt
+
+foreign import ccall safe "{values_fn}"
+  {values_fn} :: Ptr FutharkContext -> Ptr Raw{hs_type} -> Ptr {c_elemtype} -> IO CInt
+
+foreign import ccall unsafe "{shape_fn}"
+  {shape_fn} :: Ptr FutharkContext -> Ptr Raw{hs_type} -> IO (Ptr Int64)
+
+-- | Array with {rank} dimensions and {elemtype} elements
+data {hs_type} = {hs_type}
+  {{ {prefix}Ptr :: ForeignPtr Raw{hs_type},
+    {prefix}Context :: Context,
+    -- | Array dimensions
+    {prefix}Shape :: [Int]
+  }}
+
+with{hs_type} :: {hs_type} -> (Ptr Raw{hs_type} -> IO a) -> IO a
+with{hs_type} a = withForeignPtr ({prefix}Ptr a)
+
+{prefix}FromPtr :: Context -> Ptr Raw{hs_type} -> IO {hs_type}
+{prefix}FromPtr ctx ptr = do
+  fptr <- newValuePtr ctx {free_fn} ptr
+  shape <- withContext ctx $ \c -> {shape_fn} c ptr >>= peekArray {rank}
+  return ({hs_type} fptr ctx (map fromIntegral shape))
+
+-- | Create a new array from a vector of values in row-major order
+new{hs_type} :: Context -> V.Vector {c_elemtype} -> [Int] -> IO {hs_type}
+new{hs_type} ctx xs shape = case shape of
+  [{dim_names}] | product shape == V.length xs -> do
+    ptr <- withContext ctx $ \c -> V.unsafeWith xs $ \p -> {new_fn} c p {dim_args}
+    arr <- {prefix}FromPtr ctx ptr
+    autoSync ctx
+    return arr
+  _ -> throwIO (InvalidShape (product shape) (V.length xs))
+
+-- | Copy the array values into a new vector in row-major order
+{prefix}Values :: {hs_type} -> IO (V.Vector {c_elemtype})
+{prefix}Values a = do
+  let ctx = {prefix}Context a
+  out <- VM.new (product ({prefix}Shape a))
+  withContext ctx $ \c ->
+    with{hs_type} a $ \p ->
+      VM.unsafeWith out $ \o -> {values_fn} c p o >>= check c
+  -- Futhark may write to the output asynchronously
+  sync ctx
+  V.unsafeFreeze out
@@ -0,0 +1,171 @@
+{{-# LANGUAGE ForeignFunctionInterface #-}}
+
+module {module_name} where
+
+import Control.Exception (Exception, throwIO)
+import Control.Monad (void, when)
+import Foreign
+import qualified Foreign.Concurrent as FC
+import Foreign.C
+import qualified Data.Vector.Storable as V
+import qualified Data.Vector.Storable.Mutable as VM
+
+-- | Errors thrown by Futhark functions
+data FutharkError
+  = -- | A Futhark function failed with the given error code and message
+    FutharkError Int String
+  | -- | The data does not match the shape of an array, contains the expected and actual number of elements
+    InvalidShape Int Int
+  | -- | A NULL pointer was returned
+    NullPtr
+  deriving (Show)
+
+instance Exception FutharkError
+
+data FutharkContextConfig
+
+data FutharkContext
+
+foreign import ccall unsafe "futhark_context_config_new"
+  futhark_context_config_new :: IO (Ptr FutharkContextConfig)
+
+foreign import ccall unsafe "futhark_context_config_free"
+  futhark_context_config_free :: Ptr FutharkContextConfig -> IO ()
+
+foreign import ccall unsafe "futhark_context_config_set_debugging"
+  futhark_context_config_set_debugging :: Ptr FutharkContextConfig -> CInt -> IO ()
+
+foreign import ccall unsafe "futhark_context_config_set_profiling"
+  futhark_context_config_set_profiling :: Ptr FutharkContextConfig -> CInt -> IO ()
+
+foreign import ccall unsafe "futhark_context_config_set_logging"
+  futhark_context_config_set_logging :: Ptr FutharkContextConfig -> CInt -> IO ()
+
+foreign import ccall unsafe "futhark_context_config_set_cache_file"
+  futhark_context_config_set_cache_file :: Ptr FutharkContextConfig -> CString -> IO ()
+{backend_imports}
+foreign import ccall safe "futhark_context_new"
+  futhark_context_new :: Ptr FutharkContextConfig -> IO (Ptr FutharkContext)
+
+foreign import ccall safe "futhark_context_free"
+  futhark_context_free :: Ptr FutharkContext -> IO ()
+
+foreign import ccall safe "futhark_context_sync"
+  futhark_context_sync :: Ptr FutharkContext -> IO CInt
+
+foreign import ccall unsafe "futhark_context_clear_caches"
+  futhark_context_clear_caches :: Ptr FutharkContext -> IO CInt
+
+foreign import ccall unsafe "futhark_context_pause_profiling"
+  futhark_context_pause_profiling :: Ptr FutharkContext -> IO ()
+
+foreign import ccall unsafe "futhark_context_unpause_profiling"
+  futhark_context_unpause_profiling :: Ptr FutharkContext -> IO ()
+
+foreign import ccall unsafe "futhark_context_get_error"
+  futhark_context_get_error :: Ptr FutharkContext -> IO CString
+
+foreign import ccall unsafe "futhark_context_report"
+  futhark_context_report :: Ptr FutharkContext -> IO CString
+
+-- | Options used to configure a new 'Context'
+data Options = Options
+  {{ optDebug :: Bool,
+    optProfile :: Bool,
+    optLog :: Bool,
+    optCacheFile :: Maybe FilePath,
+    -- | Sync the context after copying data to or from the device
+    optAutoSync :: Bool{backend_options}
+  }}
+
+-- | Default context options
+defaultOptions :: Options
+defaultOptions =
+  Options
+    {{ optDebug = False,
+      optProfile = False,
+      optLog = False,
+      optCacheFile = Nothing,
+      optAutoSync = True{backend_defaults}
+    }}
+
+-- | Futhark context, freed automatically once the context and all values created
+-- using it are garbage collected
+data Context = Context
+  {{ contextPtr :: ForeignPtr FutharkContext,
+    contextAutoSync :: Bool
+  }}
+
+-- | Create a new context
+newContext :: Options -> IO Context
+newContext opts = do
+  config <- futhark_context_config_new
+  when (config == nullPtr) $ throwIO NullPtr
+  futhark_context_config_set_debugging config (fromBool (optDebug opts))
+  futhark_context_config_set_profiling config (fromBool (optProfile opts))
+  futhark_context_config_set_logging config (fromBool (optLog opts))
+  -- Futhark keeps pointers to the strings passed to the config, they are freed with the context
+  cacheFile <- maybe (return nullPtr) newCString (optCacheFile opts)
+  when (cacheFile /= nullPtr) $ futhark_context_config_set_cache_file config cacheFile
+{backend_config}
+  ctx <- futhark_context_new config
+  let freeConfig = do
+        futhark_context_config_free config
+        free cacheFile{backend_free}
+  when (ctx == nullPtr) $ do
+    freeConfig
+    throwIO NullPtr
+  ptr <- FC.newForeignPtr ctx $ do
+    void (futhark_context_sync ctx)
+    futhark_context_free ctx
+    freeConfig
+  return (Context ptr (optAutoSync opts))
+
+withContext :: Context -> (Ptr FutharkContext -> IO a) -> IO a
+withContext ctx = withForeignPtr (contextPtr ctx)
+
+takeString :: CString -> IO (Maybe String)
+takeString s
+  | s == nullPtr = return Nothing
+  | otherwise = do
+      str <- peekCString s
+      free s
+      return (Just str)
+
+check :: Ptr FutharkContext -> CInt -> IO ()
+check ctx rc = when (rc /= 0) $ do
+  msg <- futhark_context_get_error ctx >>= takeString
+  throwIO (FutharkError (fromIntegral rc) (maybe "" id msg))
+
+-- | Wait for all pending operations to finish, if 'optAutoSync' is enabled this shouldn't be needed
+sync :: Context -> IO ()
+sync ctx = withContext ctx $ \c -> futhark_context_sync c >>= check c
+
+autoSync :: Context -> IO ()
+autoSync ctx = when (contextAutoSync ctx) (sync ctx)
+
+-- | Clear Futhark's internal caches
+clearCaches :: Context -> IO ()
+clearCaches ctx = withContext ctx $ \c -> futhark_context_clear_caches c >>= check c
+
+-- | Pause profiling
+pauseProfiling :: Context -> IO ()
+pauseProfiling ctx = withContext ctx futhark_context_pause_profiling
+
+-- | Resume profiling
+unpauseProfiling :: Context -> IO ()
+unpauseProfiling ctx = withContext ctx futhark_context_unpause_profiling
+
+-- | Get the profiling report
+report :: Context -> IO (Maybe String)
+report ctx = withContext ctx $ \c -> futhark_context_report c >>= takeString
+
+-- | Get the last error message
+lastError :: Context -> IO (Maybe String)
+lastError ctx = withContext ctx $ \c -> futhark_context_get_error c >>= takeString
+
+-- | Create a 'ForeignPtr' that frees a Futhark value, the context is kept alive until the value is freed
+newValuePtr :: Context -> (Ptr FutharkContext -> Ptr a -> IO CInt) -> Ptr a -> IO (ForeignPtr a)
+newValuePtr ctx freeFn ptr = do
+  when (ptr == nullPtr) $ throwIO NullPtr
+  FC.newForeignPtr ptr (withContext ctx $ \c -> void (freeFn c ptr))
@@ -0,0 +1,10 @@
+
+foreign import ccall safe "{entry_fn}"
+  {entry_fn} :: {foreign_type}
+
+-- | Entry point: {name}
+{entry_name} :: {entry_type}
+{entry_name} {entry_params} =
+  {wrappers} do
+    {entry_fn} c {call_args} >>= check c
+    {read_outputs}
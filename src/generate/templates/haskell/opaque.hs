@@ -0,0 +1,19 @@
+
+data Raw{hs_type}
+
+foreign import ccall unsafe "{free_fn}"
+  {free_fn} :: Ptr FutharkContext -> Ptr Raw{hs_type} -> IO CInt
+{record_imports}
+-- | Futhark type: {name}
+data {hs_type} = {hs_type}
+  {{ {prefix}Ptr :: ForeignPtr Raw{hs_type},
+    {prefix}Context :: Context
+  }}
+
+with{hs_type} :: {hs_type} -> (Ptr Raw{hs_type} -> IO a) -> IO a
+with{hs_type} t = withForeignPtr ({prefix}Ptr t)
+
+{prefix}FromPtr :: Context -> Ptr Raw{hs_type} -> IO {hs_type}
+{prefix}FromPtr ctx ptr = do
+  fptr <- newValuePtr ctx {free_fn} ptr
+  return ({hs_type} fptr ctx)
@@ -0,0 +1,9 @@
+
+-- | Create a new {hs_type} from its fields
+new{hs_type} :: Context -> {new_param_types} -> IO {hs_type}
+new{hs_type} ctx {new_params} =
+  {wrappers} do
+    {new_fn} c out {new_call_args} >>= check c
+    t <- peek out >>= {prefix}FromPtr ctx
+    autoSync ctx
+    return t
@@ -0,0 +1,12 @@
+
+-- | Get the {name} field of a {hs_type}
+{getter} :: {hs_type} -> IO {hs_field_type}
+{getter} t = do
+  let ctx = {prefix}Context t
+  x <- withContext ctx $ \c ->
+    with{hs_type} t $ \p ->
+      alloca $ \out -> do
+        {project_fn} c out p >>= check c
+        {output}
+  autoSync ctx
+  return x
@@ -0,0 +1,52 @@
+"""
+Array with {rank} dimensions and {elemtype} elements
+"""
+mutable struct {julia_type} <: FutharkArray{{{julia_elemtype},{rank}}}
+    ptr::Ptr{{Cvoid}}
+    ctx::Context
+    shape::NTuple{{{rank},Int}}
+
+    function {julia_type}(ctx::Context, ptr::Ptr{{Cvoid}})
+        ptr == C_NULL && throw(FutharkError(0, "NULL pointer encountered"))
+        dims = ccall((:{shape_fn}, libfuthark), Ptr{{Int64}}, (Ptr{{Cvoid}}, Ptr{{Cvoid}}), handle(ctx), ptr)
+        shape = ntuple(i -> Int(unsafe_load(dims, i)), {rank})
+        finalizer(free!, new(ptr, ctx, shape))
+    end
+end
+
+"""
+Create a new array from a Julia `Array`, the Futhark array has the same dimensions and
+indexing as `data`
+"""
+function {julia_type}(ctx::Context, data::AbstractArray{{{julia_elemtype},{rank}}})
+    # Futhark expects row-major data, reversing the order of the dimensions converts
+    # from Julia's column-major layout
+    buf = {rank} == 1 ? Array(data) : permutedims(data, {rank}:-1:1)
+    dims = size(data)
+    ptr = ccall((:{new_fn}, libfuthark), Ptr{{Cvoid}}, (Ptr{{Cvoid}}, Ptr{{{julia_elemtype}}}, {dim_types}), handle(ctx), buf, {dim_args})
+    arr = {julia_type}(ctx, ptr)
+    auto_sync(ctx)
+    arr
+end
+
+"""
+Copy the array values into `out` in row-major order, `out` must have the same number of
+elements as the array
+"""
+function values!(a::{julia_type}, out::Array{{{julia_elemtype}}})
+    length(out) == prod(a.shape) || throw(DimensionMismatch("expected $(prod(a.shape)) elements, got $(length(out))"))
+    a.ptr == C_NULL && throw(FutharkError(0, "array has already been freed"))
+    check(a.ctx, ccall((:{values_fn}, libfuthark), Cint, (Ptr{{Cvoid}}, Ptr{{Cvoid}}, Ptr{{{julia_elemtype}}}), handle(a.ctx), a.ptr, out))
+    sync(a.ctx)
+    out
+end
+
+"""
+Free the array
+"""
+function free!(a::{julia_type})
+    (a.ptr == C_NULL || a.ctx.handle == C_NULL) && return
+    ccall((:{free_fn}, libfuthark), Cint, (Ptr{{Cvoid}}, Ptr{{Cvoid}}), a.ctx.handle, a.ptr)
+    a.ptr = C_NULL
+    nothing
+end
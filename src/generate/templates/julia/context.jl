@@ -0,0 +1,151 @@
+module {module_name}
+
+import Libdl
+
+"""
+Path to the shared library containing the compiled Futhark program
+"""
+const libfuthark = joinpath(@__DIR__, "lib{library_name}." * Libdl.dlext)
+
+"""
+Error returned by Futhark functions, `code` is the Futhark error code or `0` when
+the error was raised by the bindings
+"""
+struct FutharkError <: Exception
+    code::Int
+    msg::String
+end
+
+Base.showerror(io::IO, e::FutharkError) = print(io, "FutharkError(", e.code, "): ", e.msg)
+
+"""
+Futhark context
+
+Values created using a context keep a reference to it, the context is freed when it is
+garbage collected or when `free!` is called
+"""
+mutable struct Context
+    config::Ptr{{Cvoid}}
+    handle::Ptr{{Cvoid}}
+    auto_sync::Bool
+    # Futhark keeps pointers to the strings passed to the config
+    strings::Vector{{String}}
+
+    function Context(; debug::Bool=false, profile::Bool=false, log::Bool=false,
+                     cache_file::Union{{String,Nothing}}=nothing, auto_sync::Bool=true{backend_options})
+        config = ccall((:futhark_context_config_new, libfuthark), Ptr{{Cvoid}}, ())
+        config == C_NULL && throw(FutharkError(0, "unable to create context config"))
+        strings = String[]
+        ccall((:futhark_context_config_set_debugging, libfuthark), Cvoid, (Ptr{{Cvoid}}, Cint), config, debug)
+        ccall((:futhark_context_config_set_profiling, libfuthark), Cvoid, (Ptr{{Cvoid}}, Cint), config, profile)
+        ccall((:futhark_context_config_set_logging, libfuthark), Cvoid, (Ptr{{Cvoid}}, Cint), config, log)
+        if cache_file !== nothing
+            push!(strings, cache_file)
+            ccall((:futhark_context_config_set_cache_file, libfuthark), Cvoid, (Ptr{{Cvoid}}, Cstring), config, cache_file)
+        end
+{backend_config}
+        handle = ccall((:futhark_context_new, libfuthark), Ptr{{Cvoid}}, (Ptr{{Cvoid}},), config)
+        if handle == C_NULL
+            ccall((:futhark_context_config_free, libfuthark), Cvoid, (Ptr{{Cvoid}},), config)
+            throw(FutharkError(0, "unable to create context"))
+        end
+        finalizer(free!, new(config, handle, auto_sync, strings))
+    end
+end
+
+"""
+Free the context, all values created using the context become invalid
+"""
+function free!(ctx::Context)
+    ctx.handle == C_NULL && return
+    ccall((:futhark_context_sync, libfuthark), Cint, (Ptr{{Cvoid}},), ctx.handle)
+    ccall((:futhark_context_free, libfuthark), Cvoid, (Ptr{{Cvoid}},), ctx.handle)
+    ccall((:futhark_context_config_free, libfuthark), Cvoid, (Ptr{{Cvoid}},), ctx.config)
+    ctx.handle = C_NULL
+    nothing
+end
+
+function handle(ctx::Context)
+    ctx.handle == C_NULL && throw(FutharkError(0, "context has already been freed"))
+    ctx.handle
+end
+
+function take_string(s::Ptr{{Cchar}})
+    s == C_NULL && return nothing
+    str = unsafe_string(s)
+    Libc.free(s)
+    str
+end
+
+"""
+Get the last error message, or `nothing` if there is no error
+"""
+last_error(ctx::Context) =
+    take_string(ccall((:futhark_context_get_error, libfuthark), Ptr{{Cchar}}, (Ptr{{Cvoid}},), handle(ctx)))
+
+"""
+Get the profiling report
+"""
+report(ctx::Context) =
+    take_string(ccall((:futhark_context_report, libfuthark), Ptr{{Cchar}}, (Ptr{{Cvoid}},), handle(ctx)))
+
+function check(ctx::Context, rc::Cint)
+    rc == 0 && return nothing
+    msg = last_error(ctx)
+    throw(FutharkError(rc, msg === nothing ? "" : msg))
+end
+
+"""
+Wait for all pending operations to finish, if `auto_sync` is enabled this shouldn't be needed
+"""
+sync(ctx::Context) = check(ctx, ccall((:futhark_context_sync, libfuthark), Cint, (Ptr{{Cvoid}},), handle(ctx)))
+
+auto_sync(ctx::Context) = ctx.auto_sync ? sync(ctx) : nothing
+
+"""
+Clear Futhark's internal caches
+"""
+clear_caches(ctx::Context) =
+    check(ctx, ccall((:futhark_context_clear_caches, libfuthark), Cint, (Ptr{{Cvoid}},), handle(ctx)))
+
+"""
+Pause profiling
+"""
+pause_profiling(ctx::Context) =
+    ccall((:futhark_context_pause_profiling, libfuthark), Cvoid, (Ptr{{Cvoid}},), handle(ctx))
+
+"""
+Resume profiling
+"""
+unpause_profiling(ctx::Context) =
+    ccall((:futhark_context_unpause_profiling, libfuthark), Cvoid, (Ptr{{Cvoid}},), handle(ctx))
+
+"""
+Abstract supertype of all Futhark arrays
+"""
+abstract type FutharkArray{{T,N}} end
+
+"""
+Abstract supertype of all Futhark opaque values
+"""
+abstract type FutharkOpaque end
+
+Base.size(a::FutharkArray) = a.shape
+Base.ndims(::FutharkArray{{T,N}}) where {{T,N}} = N
+Base.eltype(::Type{{<:FutharkArray{{T}}}}) where {{T}} = T
+
+"""
+Copy the array into a Julia `Array`
+
+Futhark arrays are stored in row-major order while Julia arrays are column-major, the
+result has the same dimensions and indexing as the Futhark array
+"""
+function Base.Array(a::FutharkArray{{T,N}}) where {{T,N}}
+    # Reading the row-major data into a buffer with reversed dimensions gives the
+    # transpose of the array, permuting the dimensions back restores Futhark's layout
+    buf = Array{{T}}(undef, reverse(a.shape))
+    values!(a, buf)
+    N == 1 ? buf : permutedims(buf, N:-1:1)
+end
+
+Base.collect(a::FutharkArray) = Array(a)
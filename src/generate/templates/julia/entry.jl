@@ -0,0 +1,10 @@
+"""
+Entry point: {name}
+"""
+function {entry_name}({entry_params})
+    {out_decl}
+    GC.@preserve {preserve} begin
+        check(ctx, ccall((:{entry_fn}, libfuthark), Cint, ({arg_types}), {call_args}))
+    end
+    {entry_return}
+end
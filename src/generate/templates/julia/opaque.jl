@@ -0,0 +1,22 @@
+"""
+Futhark type: {name}
+"""
+mutable struct {julia_type} <: FutharkOpaque
+    ptr::Ptr{{Cvoid}}
+    ctx::Context
+
+    function {julia_type}(ctx::Context, ptr::Ptr{{Cvoid}})
+        ptr == C_NULL && throw(FutharkError(0, "NULL pointer encountered"))
+        finalizer(free!, new(ptr, ctx))
+    end
+end
+
+"""
+Free the value
+"""
+function free!(t::{julia_type})
+    (t.ptr == C_NULL || t.ctx.handle == C_NULL) && return
+    ccall((:{free_fn}, libfuthark), Cint, (Ptr{{Cvoid}}, Ptr{{Cvoid}}), t.ctx.handle, t.ptr)
+    t.ptr = C_NULL
+    nothing
+end
@@ -0,0 +1,12 @@
+"""
+Create a new {julia_type} from its fields
+"""
+function {julia_type}(ctx::Context, {new_params})
+    out = Ref{{Ptr{{Cvoid}}}}(C_NULL)
+    GC.@preserve {preserve} begin
+        check(ctx, ccall((:{new_fn}, libfuthark), Cint, (Ptr{{Cvoid}}, Ref{{Ptr{{Cvoid}}}}, {new_arg_types}), handle(ctx), out, {new_call_args}))
+    end
+    t = {julia_type}(ctx, out[])
+    auto_sync(ctx)
+    t
+end
@@ -0,0 +1,11 @@
+"""
+Get the {name} field of a {julia_type}
+"""
+function get_{field_name}(t::{julia_type})
+    out = Ref{{{out_type}}}({out_init})
+    GC.@preserve t begin
+        check(t.ctx, ccall((:{project_fn}, libfuthark), Cint, (Ptr{{Cvoid}}, Ref{{{out_type}}}, Ptr{{Cvoid}}), handle(t.ctx), out, t.ptr))
+    end
+    auto_sync(t.ctx)
+    {output}
+end
@@ -0,0 +1,88 @@
+
+ffi.cdef[[
+struct {futhark_type} *{new_fn}(struct futhark_context *ctx, const {c_elemtype} *data, {new_dim_params});
+int {free_fn}(struct futhark_context *ctx, struct {futhark_type} *arr);
+int {values_fn}(struct futhark_context *ctx, struct {futhark_type} *arr, {c_elemtype} *data);
+const int64_t *{shape_fn}(struct futhark_context *ctx, struct {futhark_type} *arr);
+]]
+
+--- Array with {rank} dimensions and {elemtype} elements
+local {lua_type} = value_class()
+M.{lua_type} = {lua_type}
+
+function {lua_type}.from_ptr(ctx, ptr)
+  local arr = wrap_value({lua_type}, ctx, ptr, lib.{free_fn})
+  local dims = lib.{shape_fn}(ctx:get_handle(), ptr)
+  arr.dims = {{}}
+  for i = 1, {rank} do
+    arr.dims[i] = tonumber(dims[i - 1])
+  end
+  return arr
+end
+
+--- Create a new array from `data`, either a flat table or a `{c_elemtype}` cdata buffer in
+--- row-major order, `dims` is a table containing the size of each dimension, it can be omitted
+--- for one-dimensional tables
+function {lua_type}.new(ctx, data, dims)
+  if dims == nil and {rank} == 1 and type(data) == "table" then
+    dims = {{ #data }}
+  end
+  if dims == nil or #dims ~= {rank} then
+    error("expected {rank} dimensions", 2)
+  end
+  local buf = data
+  if type(data) == "table" then
+    local len = 1
+    for i = 1, {rank} do
+      len = len * dims[i]
+    end
+    if #data ~= len then
+      error("expected " .. len .. " elements, got " .. #data, 2)
+    end
+    buf = ffi.new("{c_elemtype}[?]", len)
+    for i = 1, len do
+      buf[i - 1] = data[i]
+    end
+  end
+  local ptr = lib.{new_fn}(ctx:get_handle(), buf, {dim_args})
+  if ptr == nil then
+    error(ctx:get_error() or "unable to create array", 2)
+  end
+  local arr = {lua_type}.from_ptr(ctx, ptr)
+  ctx:auto_sync_context()
+  return arr
+end
+
+--- Get the size of each dimension
+function {lua_type}:shape()
+  return {{ unpack(self.dims) }}
+end
+
+--- Number of elements in the array
+function {lua_type}:len()
+  local len = 1
+  for i = 1, {rank} do
+    len = len * self.dims[i]
+  end
+  return len
+end
+
+--- Copy the array values into `buf`, a `{c_elemtype}` cdata buffer with at least `len()`
+--- elements, in row-major order
+function {lua_type}:copy_to(buf)
+  self.ctx:check(lib.{values_fn}(self.ctx:get_handle(), self:get_ptr(), buf))
+  -- Futhark may write to the buffer asynchronously
+  self.ctx:sync()
+  return buf
+end
+
+--- Copy the array values into a flat table in row-major order
+function {lua_type}:values()
+  local len = self:len()
+  local buf = self:copy_to(ffi.new("{c_elemtype}[?]", len))
+  local t = {{}}
+  for i = 1, len do
+    t[i] = buf[i - 1]
+  end
+  return t
+end
@@ -0,0 +1,193 @@
+local ffi = require("ffi")
+
+ffi.cdef[[
+struct futhark_context_config;
+struct futhark_context;
+
+void free(void *ptr);
+
+struct futhark_context_config *futhark_context_config_new(void);
+void futhark_context_config_free(struct futhark_context_config *cfg);
+void futhark_context_config_set_debugging(struct futhark_context_config *cfg, int flag);
+void futhark_context_config_set_profiling(struct futhark_context_config *cfg, int flag);
+void futhark_context_config_set_logging(struct futhark_context_config *cfg, int flag);
+void futhark_context_config_set_cache_file(struct futhark_context_config *cfg, const char *f);{backend_cdef}
+struct futhark_context *futhark_context_new(struct futhark_context_config *cfg);
+void futhark_context_free(struct futhark_context *ctx);
+int futhark_context_sync(struct futhark_context *ctx);
+int futhark_context_clear_caches(struct futhark_context *ctx);
+void futhark_context_pause_profiling(struct futhark_context *ctx);
+void futhark_context_unpause_profiling(struct futhark_context *ctx);
+char *futhark_context_get_error(struct futhark_context *ctx);
+char *futhark_context_report(struct futhark_context *ctx);
+]]
+
+local M = {{}}
+
+local function library_path()
+  -- Load the library from the directory containing this file
+  local dir = debug.getinfo(1, "S").source:match("^@(.*[/\\])") or ""
+  local ext = ffi.os == "OSX" and "dylib" or ffi.os == "Windows" and "dll" or "so"
+  return dir .. "lib{library_name}." .. ext
+end
+
+local lib = ffi.load(library_path())
+M.lib = lib
+
+local function take_string(s)
+  if s == nil then
+    return nil
+  end
+  local str = ffi.string(s)
+  ffi.C.free(s)
+  return str
+end
+
+-- The context is only released once the context and all of the values created using it
+-- have been freed, since the order finalizers run in isn't specified
+local function release(state)
+  state.refs = state.refs - 1
+  if state.refs == 0 then
+    lib.futhark_context_sync(state.handle)
+    lib.futhark_context_free(state.handle)
+    lib.futhark_context_config_free(state.config)
+  end
+end
+
+--- Futhark context
+local Context = {{}}
+Context.__index = Context
+M.Context = Context
+
+--- Create a new context, `options` is an optional table with the fields `debug`, `profile`,
+--- `log`, `cache_file`, `auto_sync`{backend_option_names}
+function Context.new(options)
+  options = options or {{}}
+  local config = lib.futhark_context_config_new()
+  if config == nil then
+    error("unable to create context config", 2)
+  end
+  -- Futhark keeps pointers to the strings passed to the config
+  local strings = {{}}
+  lib.futhark_context_config_set_debugging(config, options.debug and 1 or 0)
+  lib.futhark_context_config_set_profiling(config, options.profile and 1 or 0)
+  lib.futhark_context_config_set_logging(config, options.log and 1 or 0)
+  if options.cache_file ~= nil then
+    table.insert(strings, options.cache_file)
+    lib.futhark_context_config_set_cache_file(config, options.cache_file)
+  end{backend_config}
+  local handle = lib.futhark_context_new(config)
+  if handle == nil then
+    lib.futhark_context_config_free(config)
+    error("unable to create context", 2)
+  end
+  local state = {{ handle = handle, config = config, strings = strings, refs = 1 }}
+  local ctx = setmetatable({{
+    state = state,
+    handle = ffi.gc(handle, function() release(state) end),
+    auto_sync = options.auto_sync ~= false,
+  }}, Context)
+  return ctx
+end
+
+--- Free the context, it will be released once all values created using it have been freed
+function Context:free()
+  if self.handle == nil then
+    return
+  end
+  ffi.gc(self.handle, nil)
+  self.handle = nil
+  release(self.state)
+end
+
+function Context:get_handle()
+  if self.handle == nil then
+    error("context has already been freed", 3)
+  end
+  return self.handle
+end
+
+--- Get the last error message, or `nil` if there is no error
+function Context:get_error()
+  return take_string(lib.futhark_context_get_error(self:get_handle()))
+end
+
+--- Get the profiling report
+function Context:report()
+  return take_string(lib.futhark_context_report(self:get_handle()))
+end
+
+--- Raise an error containing the Futhark error message if `rc` is non-zero
+function Context:check(rc)
+  if rc ~= 0 then
+    error(self:get_error() or ("futhark error code " .. tostring(rc)), 3)
+  end
+end
+
+--- Wait for all pending operations to finish, if `auto_sync` is enabled this shouldn't be needed
+function Context:sync()
+  self:check(lib.futhark_context_sync(self:get_handle()))
+end
+
+function Context:auto_sync_context()
+  if self.auto_sync then
+    self:sync()
+  end
+end
+
+--- Clear Futhark's internal caches
+function Context:clear_caches()
+  self:check(lib.futhark_context_clear_caches(self:get_handle()))
+end
+
+function Context:pause_profiling()
+  lib.futhark_context_pause_profiling(self:get_handle())
+end
+
+function Context:unpause_profiling()
+  lib.futhark_context_unpause_profiling(self:get_handle())
+end
+
+-- Wrap a value returned by Futhark, the value keeps the context alive until it is freed
+local function wrap_value(class, ctx, ptr, free_fn)
+  if ptr == nil then
+    error("NULL pointer encountered", 3)
+  end
+  local state = ctx.state
+  state.refs = state.refs + 1
+  return setmetatable({{
+    ctx = ctx,
+    free_fn = free_fn,
+    ptr = ffi.gc(ptr, function(p)
+      free_fn(state.handle, p)
+      release(state)
+    end),
+  }}, class)
+end
+
+-- Methods shared by arrays and opaque values
+local Value = {{}}
+
+--- Free the value
+function Value:free()
+  if self.ptr == nil then
+    return
+  end
+  local ptr = ffi.gc(self.ptr, nil)
+  self.ptr = nil
+  self.free_fn(self.ctx.state.handle, ptr)
+  release(self.ctx.state)
+end
+
+function Value:get_ptr()
+  if self.ptr == nil then
+    error("value has already been freed", 3)
+  end
+  return self.ptr
+end
+
+local function value_class()
+  local class = setmetatable({{}}, {{ __index = Value }})
+  class.__index = class
+  return class
+end
@@ -0,0 +1,10 @@
+
+ffi.cdef[[
+int {entry_fn}({cdef_params});
+]]
+
+--- Entry point: {name}
+{entry_header}
+{out_decl}  self:check(lib.{entry_fn}({call_args}))
+  self:auto_sync_context(){entry_return}
+end
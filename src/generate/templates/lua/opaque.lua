@@ -0,0 +1,12 @@
+
+ffi.cdef[[
+int {free_fn}(struct futhark_context *ctx, struct {futhark_type} *obj);
+]]
+
+--- Futhark type: {name}
+local {lua_type} = value_class()
+M.{lua_type} = {lua_type}
+
+function {lua_type}.from_ptr(ctx, ptr)
+  return wrap_value({lua_type}, ctx, ptr, lib.{free_fn})
+end
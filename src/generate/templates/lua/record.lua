@@ -0,0 +1,13 @@
+
+ffi.cdef[[
+int {new_fn}(struct futhark_context *ctx, struct {futhark_type} **out, {new_cdef_params});
+{project_cdef}]]
+
+--- Create a new {lua_type} from its fields
+function {lua_type}.new(ctx, {new_params})
+  local out = ffi.new("struct {futhark_type} *[1]")
+  ctx:check(lib.{new_fn}(ctx:get_handle(), out, {new_call_args}))
+  local obj = {lua_type}.from_ptr(ctx, out[0])
+  ctx:auto_sync_context()
+  return obj
+end
@@ -0,0 +1,8 @@
+
+--- Get the {name} field
+function {lua_type}:get_{field_name}()
+  local out = ffi.new("{out_type}[1]")
+  self.ctx:check(lib.{project_fn}(self.ctx:get_handle(), out, self:get_ptr()))
+  self.ctx:auto_sync_context()
+  return {output}
+end
@@ -0,0 +1,82 @@
+
+// new {class_name}(ctx, data, shape)
+static napi_value {class_name}_new(napi_env env, napi_callback_info info) {{
+  size_t argc = 3;
+  napi_value argv[3], this;
+  NAPI_CALL(env, napi_get_cb_info(env, info, &argc, argv, &this, NULL));
+  switch (value_construct(env, this, argc, argv, &{class_name}_tag)) {{
+  case 1:
+    return this;
+  case -1:
+    return NULL;
+  }}
+  if (argc < 2) {{
+    napi_throw_type_error(env, NULL, "Expected a context and data");
+    return NULL;
+  }}
+  Context *ctx = unwrap_context(env, argv[0]);
+  if (ctx == NULL)
+    return NULL;
+  void *data;
+  size_t len;
+  int64_t dims[{rank}];
+  if (!get_typed_array(env, argv[1], {napi_array_type}, &data, &len) ||
+      !get_shape(env, argc > 2 ? argv[2] : NULL, dims, {rank}, len))
+    return NULL;
+  struct {futhark_type} *ptr = {new_fn}(ctx->ctx, data, {dim_args});
+  if (ptr == NULL) {{
+    napi_throw_error(env, NULL, "Unable to create array");
+    return NULL;
+  }}
+  if (!context_auto_sync(env, ctx) ||
+      !wrap_this(env, this, &{class_name}_tag,
+                 new_value(ctx, ptr, {class_name}_free_fn)))
+    return NULL;
+  return this;
+}}
+
+static Value *{class_name}_this(napi_env env, napi_callback_info info) {{
+  napi_value this;
+  size_t argc = 0;
+  if (!check_napi(env, napi_get_cb_info(env, info, &argc, NULL, &this, NULL)))
+    return NULL;
+  return unwrap_value(env, this, &{class_name}_tag, "Expected {class_name}");
+}}
+
+static napi_value {class_name}_shape(napi_env env, napi_callback_info info) {{
+  Value *v = {class_name}_this(env, info);
+  if (v == NULL)
+    return NULL;
+  return new_shape(env, {shape_fn}(v->ctx->ctx, v->ptr), {rank});
+}}
+
+static napi_value {class_name}_values(napi_env env, napi_callback_info info) {{
+  Value *v = {class_name}_this(env, info);
+  if (v == NULL)
+    return NULL;
+  const int64_t *dims = {shape_fn}(v->ctx->ctx, v->ptr);
+  size_t len = 1;
+  for (size_t i = 0; i < {rank}; i++)
+    len *= (size_t)dims[i];
+  void *data;
+  napi_value out =
+      new_typed_array(env, {napi_array_type}, sizeof({c_elemtype}), len, &data);
+  if (out == NULL)
+    return NULL;
+  int rc = {values_fn}(v->ctx->ctx, v->ptr, data);
+  // Futhark may write to the output asynchronously
+  if (rc == 0)
+    rc = futhark_context_sync(v->ctx->ctx);
+  if (rc != 0) {{
+    throw_futhark_error(env, v->ctx, rc);
+    return NULL;
+  }}
+  return out;
+}}
+
+static const napi_property_descriptor {class_name}_properties[] = {{
+    {{"shape", NULL, {class_name}_shape, NULL, NULL, NULL, napi_default, NULL}},
+    {{"values", NULL, {class_name}_values, NULL, NULL, NULL, napi_default, NULL}},
+    {{"free", NULL, value_free, NULL, NULL, NULL, napi_default,
+     (void *)&{class_name}_tag}},
+}};
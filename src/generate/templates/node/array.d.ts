@@ -0,0 +1,9 @@
+/** Array with {rank} dimensions and {elemtype} elements */
+export class {class_name} {{
+  /** Create a new array from row-major `data`, `shape` may be omitted for one dimensional arrays */
+  constructor(ctx: Context, data: {ts_array_type}, shape?: number[]);
+  shape(): number[];
+  /** Copy the array values into a new typed array in row-major order */
+  values(): {ts_array_type};
+  free(): void;
+}}
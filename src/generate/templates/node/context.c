@@ -0,0 +1,602 @@
+#define NAPI_VERSION 8
+#include <node_api.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include "{header}"
+
+#define NAPI_CALL(env, call)                                                   \
+  do {{                                                                         \
+    if (!check_napi((env), (call)))                                            \
+      return NULL;                                                             \
+  }} while (0)
+
+#define NAPI_CALL_BOOL(env, call)                                              \
+  do {{                                                                         \
+    if (!check_napi((env), (call)))                                            \
+      return false;                                                            \
+  }} while (0)
+
+static bool check_napi(napi_env env, napi_status status) {{
+  if (status == napi_ok)
+    return true;
+  const napi_extended_error_info *info = NULL;
+  napi_get_last_error_info(env, &info);
+  const char *msg = info != NULL && info->error_message != NULL
+                        ? info->error_message
+                        : "N-API call failed";
+  bool pending = false;
+  napi_is_exception_pending(env, &pending);
+  if (!pending)
+    napi_throw_error(env, NULL, msg);
+  return false;
+}}
+
+// Futhark context, this is reference counted because values keep the context
+// alive and may be garbage collected after the JavaScript `Context` object
+typedef struct {{
+  struct futhark_context_config *cfg;
+  struct futhark_context *ctx;
+  // Futhark keeps pointers to the strings passed to the config
+  char *cache_file;
+  char *device;
+  bool auto_sync;
+  size_t refs;
+}} Context;
+
+// Array or opaque value
+typedef struct {{
+  void *ptr;
+  Context *ctx;
+  int (*free_fn)(struct futhark_context *, void *);
+}} Value;
+
+static const napi_type_tag context_tag = {{0x6675746861726b00ULL, 0}};
+
+static void context_free_futhark(Context *c) {{
+  if (c->ctx == NULL)
+    return;
+  futhark_context_sync(c->ctx);
+  futhark_context_free(c->ctx);
+  futhark_context_config_free(c->cfg);
+  free(c->cache_file);
+  free(c->device);
+  c->ctx = NULL;
+}}
+
+static void context_release(Context *c) {{
+  if (--c->refs > 0)
+    return;
+  context_free_futhark(c);
+  free(c);
+}}
+
+static void throw_futhark_error(napi_env env, Context *c, int rc) {{
+  char code[16];
+  snprintf(code, sizeof(code), "%d", rc);
+  char *msg = futhark_context_get_error(c->ctx);
+  napi_throw_error(env, code, msg != NULL ? msg : "Futhark error");
+  free(msg);
+}}
+
+static bool context_auto_sync(napi_env env, Context *c) {{
+  if (!c->auto_sync)
+    return true;
+  int rc = futhark_context_sync(c->ctx);
+  if (rc != 0) {{
+    throw_futhark_error(env, c, rc);
+    return false;
+  }}
+  return true;
+}}
+
+static Context *unwrap_context(napi_env env, napi_value v) {{
+  bool ok = false;
+  if (napi_check_object_type_tag(env, v, &context_tag, &ok) != napi_ok || !ok) {{
+    napi_throw_type_error(env, NULL, "Expected a Context");
+    return NULL;
+  }}
+  Context *c = NULL;
+  NAPI_CALL(env, napi_unwrap(env, v, (void **)&c));
+  if (c->ctx == NULL) {{
+    napi_throw_error(env, NULL, "Context has already been freed");
+    return NULL;
+  }}
+  return c;
+}}
+
+static bool get_bool_option(napi_env env, napi_value obj, const char *name,
+                            bool *out) {{
+  bool has = false;
+  NAPI_CALL_BOOL(env, napi_has_named_property(env, obj, name, &has));
+  if (!has)
+    return true;
+  napi_value v;
+  NAPI_CALL_BOOL(env, napi_get_named_property(env, obj, name, &v));
+  NAPI_CALL_BOOL(env, napi_get_value_bool(env, v, out));
+  return true;
+}}
+
+static bool get_string_option(napi_env env, napi_value obj, const char *name,
+                              char **out) {{
+  bool has = false;
+  NAPI_CALL_BOOL(env, napi_has_named_property(env, obj, name, &has));
+  if (!has)
+    return true;
+  napi_value v;
+  size_t len = 0;
+  NAPI_CALL_BOOL(env, napi_get_named_property(env, obj, name, &v));
+  NAPI_CALL_BOOL(env, napi_get_value_string_utf8(env, v, NULL, 0, &len));
+  *out = malloc(len + 1);
+  NAPI_CALL_BOOL(env, napi_get_value_string_utf8(env, v, *out, len + 1, &len));
+  return true;
+}}
+
+static inline bool get_int_option(napi_env env, napi_value obj,
+                                  const char *name, int32_t *out) {{
+  bool has = false;
+  NAPI_CALL_BOOL(env, napi_has_named_property(env, obj, name, &has));
+  if (!has)
+    return true;
+  napi_value v;
+  NAPI_CALL_BOOL(env, napi_get_named_property(env, obj, name, &v));
+  NAPI_CALL_BOOL(env, napi_get_value_int32(env, v, out));
+  return true;
+}}
+
+static void context_finalize(napi_env env, void *data, void *hint) {{
+  (void)env;
+  (void)hint;
+  context_release(data);
+}}
+
+static napi_value Context_new(napi_env env, napi_callback_info info) {{
+  size_t argc = 1;
+  napi_value argv[1], this;
+  NAPI_CALL(env, napi_get_cb_info(env, info, &argc, argv, &this, NULL));
+
+  bool debug = false, profile = false, log = false;
+{backend_decls}  Context *c = calloc(1, sizeof(Context));
+  c->auto_sync = true;
+  c->refs = 1;
+  if (argc > 0) {{
+    napi_valuetype t;
+    NAPI_CALL(env, napi_typeof(env, argv[0], &t));
+    if (t == napi_object &&
+        !(get_bool_option(env, argv[0], "debug", &debug) &&
+          get_bool_option(env, argv[0], "profile", &profile) &&
+          get_bool_option(env, argv[0], "log", &log) &&
+          get_bool_option(env, argv[0], "autoSync", &c->auto_sync) &&
+          get_string_option(env, argv[0], "cacheFile", &c->cache_file){backend_options})) {{
+      free(c->cache_file);
+      free(c->device);
+      free(c);
+      return NULL;
+    }}
+  }}
+
+  c->cfg = futhark_context_config_new();
+  futhark_context_config_set_debugging(c->cfg, debug);
+  futhark_context_config_set_profiling(c->cfg, profile);
+  futhark_context_config_set_logging(c->cfg, log);
+  if (c->cache_file != NULL)
+    futhark_context_config_set_cache_file(c->cfg, c->cache_file);
+{backend_config}
+  c->ctx = futhark_context_new(c->cfg);
+  if (c->ctx == NULL) {{
+    futhark_context_config_free(c->cfg);
+    free(c->cache_file);
+    free(c->device);
+    free(c);
+    napi_throw_error(env, NULL, "Unable to create Futhark context");
+    return NULL;
+  }}
+
+  if (napi_wrap(env, this, c, context_finalize, NULL, NULL) != napi_ok) {{
+    context_release(c);
+    check_napi(env, napi_generic_failure);
+    return NULL;
+  }}
+  NAPI_CALL(env, napi_type_tag_object(env, this, &context_tag));
+  return this;
+}}
+
+static Context *context_this(napi_env env, napi_callback_info info) {{
+  napi_value this;
+  size_t argc = 0;
+  if (!check_napi(env, napi_get_cb_info(env, info, &argc, NULL, &this, NULL)))
+    return NULL;
+  return unwrap_context(env, this);
+}}
+
+static napi_value Context_sync(napi_env env, napi_callback_info info) {{
+  Context *c = context_this(env, info);
+  if (c == NULL)
+    return NULL;
+  int rc = futhark_context_sync(c->ctx);
+  if (rc != 0)
+    throw_futhark_error(env, c, rc);
+  return NULL;
+}}
+
+static napi_value Context_clearCaches(napi_env env, napi_callback_info info) {{
+  Context *c = context_this(env, info);
+  if (c == NULL)
+    return NULL;
+  int rc = futhark_context_clear_caches(c->ctx);
+  if (rc != 0)
+    throw_futhark_error(env, c, rc);
+  return NULL;
+}}
+
+static napi_value Context_pauseProfiling(napi_env env,
+                                         napi_callback_info info) {{
+  Context *c = context_this(env, info);
+  if (c != NULL)
+    futhark_context_pause_profiling(c->ctx);
+  return NULL;
+}}
+
+static napi_value Context_unpauseProfiling(napi_env env,
+                                           napi_callback_info info) {{
+  Context *c = context_this(env, info);
+  if (c != NULL)
+    futhark_context_unpause_profiling(c->ctx);
+  return NULL;
+}}
+
+static napi_value Context_report(napi_env env, napi_callback_info info) {{
+  Context *c = context_this(env, info);
+  if (c == NULL)
+    return NULL;
+  char *s = futhark_context_report(c->ctx);
+  napi_value out;
+  napi_status status =
+      napi_create_string_utf8(env, s != NULL ? s : "", NAPI_AUTO_LENGTH, &out);
+  free(s);
+  NAPI_CALL(env, status);
+  return out;
+}}
+
+// Free the Futhark context, values created using the context can no longer be
+// used
+static napi_value Context_free(napi_env env, napi_callback_info info) {{
+  Context *c = context_this(env, info);
+  if (c != NULL)
+    context_free_futhark(c);
+  return NULL;
+}}
+
+static void value_finalize(napi_env env, void *data, void *hint) {{
+  (void)env;
+  (void)hint;
+  Value *v = data;
+  if (v->ptr != NULL && v->ctx->ctx != NULL)
+    v->free_fn(v->ctx->ctx, v->ptr);
+  context_release(v->ctx);
+  free(v);
+}}
+
+static Value *new_value(Context *ctx, void *ptr,
+                        int (*free_fn)(struct futhark_context *, void *)) {{
+  Value *v = malloc(sizeof(Value));
+  v->ptr = ptr;
+  v->ctx = ctx;
+  v->free_fn = free_fn;
+  ctx->refs += 1;
+  return v;
+}}
+
+// Create a new instance of the class at `index`, the constructor wraps `ptr`
+// using `value_construct`
+static napi_value wrap_value(napi_env env, size_t index, Context *ctx,
+                             void *ptr,
+                             int (*free_fn)(struct futhark_context *, void *)) {{
+  napi_ref *classes = NULL;
+  napi_value cons, ext, out;
+  Value *v = new_value(ctx, ptr, free_fn);
+  if (napi_get_instance_data(env, (void **)&classes) != napi_ok ||
+      napi_get_reference_value(env, classes[index], &cons) != napi_ok ||
+      napi_create_external(env, v, NULL, NULL, &ext) != napi_ok ||
+      napi_new_instance(env, cons, 1, &ext, &out) != napi_ok) {{
+    value_finalize(env, v, NULL);
+    check_napi(env, napi_generic_failure);
+    return NULL;
+  }}
+  return out;
+}}
+
+// Wrap a value created by `wrap_value` or a constructor, returns false if an
+// exception has been thrown
+static bool wrap_this(napi_env env, napi_value this, const napi_type_tag *tag,
+                      Value *v) {{
+  if (napi_wrap(env, this, v, value_finalize, NULL, NULL) != napi_ok) {{
+    value_finalize(env, v, NULL);
+    return check_napi(env, napi_generic_failure);
+  }}
+  NAPI_CALL_BOOL(env, napi_type_tag_object(env, this, tag));
+  return true;
+}}
+
+// Handles constructor calls made by `wrap_value`, returns 1 if the value was
+// wrapped, 0 if the constructor was called from JavaScript and -1 on error
+static int value_construct(napi_env env, napi_value this, size_t argc,
+                           napi_value *argv, const napi_type_tag *tag) {{
+  napi_valuetype t;
+  if (argc != 1 || napi_typeof(env, argv[0], &t) != napi_ok ||
+      t != napi_external)
+    return 0;
+  Value *v = NULL;
+  if (!check_napi(env, napi_get_value_external(env, argv[0], (void **)&v)))
+    return -1;
+  return wrap_this(env, this, tag, v) ? 1 : -1;
+}}
+
+static Value *unwrap_value(napi_env env, napi_value obj,
+                           const napi_type_tag *tag, const char *name) {{
+  bool ok = false;
+  if (napi_check_object_type_tag(env, obj, tag, &ok) != napi_ok || !ok) {{
+    napi_throw_type_error(env, NULL, name);
+    return NULL;
+  }}
+  Value *v = NULL;
+  NAPI_CALL(env, napi_unwrap(env, obj, (void **)&v));
+  if (v->ptr == NULL || v->ctx->ctx == NULL) {{
+    napi_throw_error(env, NULL, "Value has already been freed");
+    return NULL;
+  }}
+  return v;
+}}
+
+// Get the Futhark pointer from a value, checking that it belongs to `ctx`
+static void *value_ptr(napi_env env, napi_value obj, const napi_type_tag *tag,
+                       const char *name, Context *ctx) {{
+  Value *v = unwrap_value(env, obj, tag, name);
+  if (v == NULL)
+    return NULL;
+  if (v->ctx != ctx) {{
+    napi_throw_error(env, NULL, "Value belongs to a different context");
+    return NULL;
+  }}
+  return v->ptr;
+}}
+
+// Free the value, the tag is passed as callback data
+static napi_value value_free(napi_env env, napi_callback_info info) {{
+  napi_value this;
+  size_t argc = 0;
+  void *tag;
+  NAPI_CALL(env, napi_get_cb_info(env, info, &argc, NULL, &this, &tag));
+  bool ok = false;
+  NAPI_CALL(env, napi_check_object_type_tag(env, this, tag, &ok));
+  if (!ok) {{
+    napi_throw_type_error(env, NULL, "Invalid object");
+    return NULL;
+  }}
+  Value *v = NULL;
+  NAPI_CALL(env, napi_unwrap(env, this, (void **)&v));
+  if (v->ptr != NULL && v->ctx->ctx != NULL)
+    v->free_fn(v->ctx->ctx, v->ptr);
+  v->ptr = NULL;
+  return NULL;
+}}
+
+static bool get_shape(napi_env env, napi_value arr, int64_t *dims, size_t rank,
+                      size_t len) {{
+  if (arr == NULL) {{
+    if (rank != 1) {{
+      napi_throw_type_error(env, NULL, "Expected an array shape");
+      return false;
+    }}
+    dims[0] = (int64_t)len;
+    return true;
+  }}
+  uint32_t n = 0;
+  NAPI_CALL_BOOL(env, napi_get_array_length(env, arr, &n));
+  if (n != rank) {{
+    napi_throw_range_error(env, NULL, "Invalid number of dimensions");
+    return false;
+  }}
+  int64_t size = 1;
+  for (uint32_t i = 0; i < n; i++) {{
+    napi_value d;
+    NAPI_CALL_BOOL(env, napi_get_element(env, arr, i, &d));
+    NAPI_CALL_BOOL(env, napi_get_value_int64(env, d, &dims[i]));
+    size *= dims[i];
+  }}
+  if (size < 0 || (size_t)size != len) {{
+    napi_throw_range_error(env, NULL, "Shape does not match the data");
+    return false;
+  }}
+  return true;
+}}
+
+static bool get_typed_array(napi_env env, napi_value v,
+                            napi_typedarray_type type, void **data,
+                            size_t *len) {{
+  bool is_typed_array = false;
+  napi_typedarray_type t;
+  NAPI_CALL_BOOL(env, napi_is_typedarray(env, v, &is_typed_array));
+  if (is_typed_array)
+    NAPI_CALL_BOOL(env, napi_get_typedarray_info(env, v, &t, len, data, NULL,
+                                                 NULL));
+  if (!is_typed_array || t != type) {{
+    napi_throw_type_error(env, NULL, "Invalid typed array type");
+    return false;
+  }}
+  return true;
+}}
+
+static napi_value new_typed_array(napi_env env, napi_typedarray_type type,
+                                  size_t elem_size, size_t len, void **data) {{
+  napi_value buf, out;
+  NAPI_CALL(env, napi_create_arraybuffer(env, len * elem_size, data, &buf));
+  NAPI_CALL(env, napi_create_typedarray(env, type, len, buf, 0, &out));
+  return out;
+}}
+
+static napi_value new_shape(napi_env env, const int64_t *dims, size_t rank) {{
+  napi_value out, d;
+  NAPI_CALL(env, napi_create_array_with_length(env, rank, &out));
+  for (size_t i = 0; i < rank; i++) {{
+    NAPI_CALL(env, napi_create_int64(env, dims[i], &d));
+    NAPI_CALL(env, napi_set_element(env, out, (uint32_t)i, d));
+  }}
+  return out;
+}}
+
+static inline bool get_i8(napi_env env, napi_value v, int8_t *out) {{
+  int32_t x;
+  NAPI_CALL_BOOL(env, napi_get_value_int32(env, v, &x));
+  *out = (int8_t)x;
+  return true;
+}}
+
+static inline bool get_i16(napi_env env, napi_value v, int16_t *out) {{
+  int32_t x;
+  NAPI_CALL_BOOL(env, napi_get_value_int32(env, v, &x));
+  *out = (int16_t)x;
+  return true;
+}}
+
+static inline bool get_i32(napi_env env, napi_value v, int32_t *out) {{
+  NAPI_CALL_BOOL(env, napi_get_value_int32(env, v, out));
+  return true;
+}}
+
+static inline bool get_i64(napi_env env, napi_value v, int64_t *out) {{
+  napi_valuetype t;
+  bool lossless;
+  NAPI_CALL_BOOL(env, napi_typeof(env, v, &t));
+  if (t == napi_bigint)
+    NAPI_CALL_BOOL(env, napi_get_value_bigint_int64(env, v, out, &lossless));
+  else
+    NAPI_CALL_BOOL(env, napi_get_value_int64(env, v, out));
+  return true;
+}}
+
+static inline bool get_u8(napi_env env, napi_value v, uint8_t *out) {{
+  uint32_t x;
+  NAPI_CALL_BOOL(env, napi_get_value_uint32(env, v, &x));
+  *out = (uint8_t)x;
+  return true;
+}}
+
+static inline bool get_u16(napi_env env, napi_value v, uint16_t *out) {{
+  uint32_t x;
+  NAPI_CALL_BOOL(env, napi_get_value_uint32(env, v, &x));
+  *out = (uint16_t)x;
+  return true;
+}}
+
+static inline bool get_u32(napi_env env, napi_value v, uint32_t *out) {{
+  NAPI_CALL_BOOL(env, napi_get_value_uint32(env, v, out));
+  return true;
+}}
+
+static inline bool get_u64(napi_env env, napi_value v, uint64_t *out) {{
+  napi_valuetype t;
+  bool lossless;
+  NAPI_CALL_BOOL(env, napi_typeof(env, v, &t));
+  if (t == napi_bigint) {{
+    NAPI_CALL_BOOL(env, napi_get_value_bigint_uint64(env, v, out, &lossless));
+  }} else {{
+    int64_t x;
+    NAPI_CALL_BOOL(env, napi_get_value_int64(env, v, &x));
+    *out = (uint64_t)x;
+  }}
+  return true;
+}}
+
+static inline bool get_f16(napi_env env, napi_value v, uint16_t *out) {{
+  return get_u16(env, v, out);
+}}
+
+static inline bool get_f32(napi_env env, napi_value v, float *out) {{
+  double x;
+  NAPI_CALL_BOOL(env, napi_get_value_double(env, v, &x));
+  *out = (float)x;
+  return true;
+}}
+
+static inline bool get_f64(napi_env env, napi_value v, double *out) {{
+  NAPI_CALL_BOOL(env, napi_get_value_double(env, v, out));
+  return true;
+}}
+
+static inline bool get_bool(napi_env env, napi_value v, bool *out) {{
+  NAPI_CALL_BOOL(env, napi_get_value_bool(env, v, out));
+  return true;
+}}
+
+static inline napi_value new_i8(napi_env env, int8_t x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_int32(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_i16(napi_env env, int16_t x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_int32(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_i32(napi_env env, int32_t x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_int32(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_i64(napi_env env, int64_t x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_bigint_int64(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_u8(napi_env env, uint8_t x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_uint32(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_u16(napi_env env, uint16_t x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_uint32(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_u32(napi_env env, uint32_t x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_uint32(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_u64(napi_env env, uint64_t x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_bigint_uint64(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_f16(napi_env env, uint16_t x) {{
+  return new_u16(env, x);
+}}
+
+static inline napi_value new_f32(napi_env env, float x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_double(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_f64(napi_env env, double x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_create_double(env, x, &out));
+  return out;
+}}
+
+static inline napi_value new_bool(napi_env env, bool x) {{
+  napi_value out;
+  NAPI_CALL(env, napi_get_boolean(env, x, &out));
+  return out;
+}}
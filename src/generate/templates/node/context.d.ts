@@ -0,0 +1,24 @@
+export interface Options {{
+  debug?: boolean;
+  profile?: boolean;
+  log?: boolean;
+  cacheFile?: string;
+  /** Sync the context after copying data to or from the device, enabled by default */
+  autoSync?: boolean;{backend_options}
+}}
+
+/** Futhark context, freed when it is garbage collected or when `free` is called */
+export class Context {{
+  constructor(options?: Options);
+  /** Wait for all pending operations to finish, if `autoSync` is enabled this shouldn't be needed */
+  sync(): void;
+  /** Clear Futhark's internal caches */
+  clearCaches(): void;
+  pauseProfiling(): void;
+  unpauseProfiling(): void;
+  /** Get the profiling report */
+  report(): string;
+  /** Free the context, values created using the context can no longer be used */
+  free(): void;
+{entry_methods}
+}}
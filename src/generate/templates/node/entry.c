@@ -0,0 +1,21 @@
+
+// Entry point: {name}
+static napi_value entry_{name}(napi_env env, napi_callback_info info) {{
+  size_t argc = {argc};
+  napi_value argv[{argv_len}], this;
+  NAPI_CALL(env, napi_get_cb_info(env, info, &argc, argv, &this, NULL));
+  Context *ctx = unwrap_context(env, this);
+  if (ctx == NULL)
+    return NULL;
+  if (argc != {argc}) {{
+    napi_throw_type_error(env, NULL, "Expected {argc} arguments");
+    return NULL;
+  }}
+  {decls}
+  int rc = {entry_fn}({call_args});
+  if (rc != 0) {{
+    throw_futhark_error(env, ctx, rc);
+    return NULL;
+  }}
+  {entry_return}
+}}
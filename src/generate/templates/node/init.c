@@ -0,0 +1,51 @@
+
+static const napi_property_descriptor Context_properties[] = {{
+    {{"sync", NULL, Context_sync, NULL, NULL, NULL, napi_default, NULL}},
+    {{"clearCaches", NULL, Context_clearCaches, NULL, NULL, NULL, napi_default,
+     NULL}},
+    {{"pauseProfiling", NULL, Context_pauseProfiling, NULL, NULL, NULL,
+     napi_default, NULL}},
+    {{"unpauseProfiling", NULL, Context_unpauseProfiling, NULL, NULL, NULL,
+     napi_default, NULL}},
+    {{"report", NULL, Context_report, NULL, NULL, NULL, napi_default, NULL}},
+    {{"free", NULL, Context_free, NULL, NULL, NULL, napi_default, NULL}},
+{entry_properties}
+}};
+
+static bool define_class(napi_env env, napi_value exports, const char *name,
+                         napi_callback constructor, size_t n,
+                         const napi_property_descriptor *properties,
+                         napi_ref *ref) {{
+  napi_value cons;
+  NAPI_CALL_BOOL(env, napi_define_class(env, name, NAPI_AUTO_LENGTH,
+                                        constructor, NULL, n, properties,
+                                        &cons));
+  NAPI_CALL_BOOL(env, napi_set_named_property(env, exports, name, cons));
+  if (ref != NULL)
+    NAPI_CALL_BOOL(env, napi_create_reference(env, cons, 1, ref));
+  return true;
+}}
+
+static void classes_finalize(napi_env env, void *data, void *hint) {{
+  (void)hint;
+  napi_ref *classes = data;
+  for (size_t i = 0; i < {class_count}; i++)
+    if (classes[i] != NULL)
+      napi_delete_reference(env, classes[i]);
+  free(classes);
+}}
+
+#define PROPERTIES(x) sizeof(x) / sizeof(x[0]), x
+
+NAPI_MODULE_INIT() {{
+  // Class constructors are stored as instance data, indexed by the second
+  // half of each type tag
+  napi_ref *classes = calloc({class_count}, sizeof(napi_ref));
+  if (!check_napi(env, napi_set_instance_data(env, classes, classes_finalize,
+                                              NULL)))
+    return NULL;
+  if (!(define_class(env, exports, "Context", Context_new,
+                     PROPERTIES(Context_properties), NULL){define_classes}))
+    return NULL;
+  return exports;
+}}
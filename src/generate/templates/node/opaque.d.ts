@@ -0,0 +1,5 @@
+/** Futhark type: {name} */
+export class {class_name} {{
+  {constructor}
+  free(): void;
+}}
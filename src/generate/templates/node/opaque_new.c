@@ -0,0 +1,10 @@
+
+// Values of this type can only be returned by entry points
+static napi_value {class_name}_new(napi_env env, napi_callback_info info) {{
+  size_t argc = 1;
+  napi_value argv[1], this;
+  NAPI_CALL(env, napi_get_cb_info(env, info, &argc, argv, &this, NULL));
+  if (value_construct(env, this, argc, argv, &{class_name}_tag) == 0)
+    napi_throw_type_error(env, NULL, "{class_name} can't be constructed directly");
+  return this;
+}}
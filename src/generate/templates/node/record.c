@@ -0,0 +1,32 @@
+
+// new {class_name}(ctx, {field_names})
+static napi_value {class_name}_new(napi_env env, napi_callback_info info) {{
+  size_t argc = {argc};
+  napi_value argv[{argc}], this;
+  NAPI_CALL(env, napi_get_cb_info(env, info, &argc, argv, &this, NULL));
+  switch (value_construct(env, this, argc, argv, &{class_name}_tag)) {{
+  case 1:
+    return this;
+  case -1:
+    return NULL;
+  }}
+  if (argc != {argc}) {{
+    napi_throw_type_error(env, NULL, "Expected {argc} arguments");
+    return NULL;
+  }}
+  Context *ctx = unwrap_context(env, argv[0]);
+  if (ctx == NULL)
+    return NULL;
+  {field_decls}
+  struct {futhark_type} *ptr = NULL;
+  int rc = {new_fn}(ctx->ctx, &ptr, {new_call_args});
+  if (rc != 0) {{
+    throw_futhark_error(env, ctx, rc);
+    return NULL;
+  }}
+  if (!context_auto_sync(env, ctx) ||
+      !wrap_this(env, this, &{class_name}_tag,
+                 new_value(ctx, ptr, {class_name}_free_fn)))
+    return NULL;
+  return this;
+}}
@@ -0,0 +1,20 @@
+
+// Getter for the {name} field
+static napi_value {class_name}_get_{field_name}(napi_env env,
+                                              napi_callback_info info) {{
+  napi_value this;
+  size_t argc = 0;
+  NAPI_CALL(env, napi_get_cb_info(env, info, &argc, NULL, &this, NULL));
+  Value *v = unwrap_value(env, this, &{class_name}_tag, "Expected {class_name}");
+  if (v == NULL)
+    return NULL;
+  {out_decl}
+  int rc = {project_fn}(v->ctx->ctx, &out, v->ptr);
+  if (rc != 0) {{
+    throw_futhark_error(env, v->ctx, rc);
+    return NULL;
+  }}
+  if (!context_auto_sync(env, v->ctx))
+    return NULL;
+  return {output};
+}}
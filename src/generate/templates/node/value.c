@@ -0,0 +1,6 @@
+
+static const napi_type_tag {class_name}_tag = {{0x6675746861726b00ULL, {index}}};
+
+static int {class_name}_free_fn(struct futhark_context *ctx, void *ptr) {{
+  return {free_fn}(ctx, ptr);
+}}
@@ -71,7 +71,7 @@ module {module_name} = struct
     if is_null ptr then raise (Error NullPtr);
     let shape = ptr_shape ctx.Context.handle ptr in
     {{ ptr = Ctypes.allocate ~finalise:(free ctx) (Ctypes.ptr Ctypes.void) ptr; ctx; shape }}
-
+{extra_fns}
   let free t = free t.ctx t.ptr
     
   let _ = of_ptr
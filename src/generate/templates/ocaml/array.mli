@@ -33,4 +33,5 @@ module {module_name}: sig
 
   val free: t -> unit
   (** Free the array *)
+{extra_mli}
 end
@@ -0,0 +1,42 @@
+  (* [futhark_index_*] can finish asynchronously, the context must be synced before [out] is read *)
+  let index_unsynced t idx out =
+    let rc = Bindings.{index_fn} t.ctx.Context.handle out (get_ptr t) {index_args} in
+    if rc <> 0 then raise (Error (Code rc))
+
+  let index t idx =
+    check_use_after_free `context t.ctx.Context.context_free;
+    if Array.length idx <> {rank} then raise (Error InvalidIndex);
+    Array.iteri (fun i x -> if x < 0 || x >= t.shape.(i) then raise (Error InvalidIndex)) idx;
+    let out = allocate_n {ocaml_ctype} ~count:1 in
+    index_unsynced t idx out;
+    Context.sync t.ctx;
+    !@out
+
+  let slice t start stop =
+    check_use_after_free `context t.ctx.Context.context_free;
+    if Array.length start <> {rank} || Array.length stop <> {rank} then raise (Error InvalidIndex);
+    Array.iteri (fun i s ->
+      if s < 0 || s > stop.(i) || stop.(i) > t.shape.(i) then raise (Error InvalidIndex)) start;
+    let len = Array.fold_left ( * ) 1 (Array.mapi (fun i s -> stop.(i) - s) start) in
+    let idx = Array.copy start in
+    let rec next d =
+      if d >= 0 then
+        let () = idx.(d) <- idx.(d) + 1 in
+        if idx.(d) >= stop.(d) then
+          let () = idx.(d) <- start.(d) in
+          next (d - 1)
+    in
+    let out = allocate_n {ocaml_ctype} ~count:(max len 1) in
+    let () =
+      try
+        for i = 0 to len - 1 do
+          index_unsynced t idx (out +@ i);
+          next ({rank} - 1)
+        done
+      with e ->
+        (* Earlier reads may still be writing into [out] *)
+        ignore (Bindings.futhark_context_sync t.ctx.Context.handle);
+        raise e
+    in
+    Context.sync t.ctx;
+    Array.init len (fun i -> !@(out +@ i))
@@ -0,0 +1,8 @@
+  val index: t -> int array -> {ocaml_type}
+  (** Read a single element without loading the entire array, the context is always synced *)
+
+  val slice: t -> int array -> int array -> {ocaml_type} array
+  (** [slice t start stop] reads the elements from [start] (inclusive) to [stop] (exclusive)
+      in each dimension, in row-major order. Each element is read using a separate
+      [futhark_index_*] call, so reading [n] elements takes [n] calls and this is intended for
+      small regions of large arrays. The context is synced once after all the calls *)
@@ -0,0 +1,6 @@
+  val of_raw: Context.t -> int array -> {raw_type} -> t
+  (** Create an array using existing device memory without copying, the memory is not
+      freed by Futhark and must outlive the array *)
+
+  val to_raw: t -> {raw_type}
+  (** Get the device memory backing the array, this is only valid while the array is alive *)
@@ -24,6 +24,12 @@ module Bindings = struct
   let futhark_context_report = fn "futhark_context_report" (context @-> returning (ptr char))
   let free = fn "free" (ptr void @-> returning void)
   let strlen = fn "strlen" (ptr char @-> returning size_t)
+  let tmpfile = fn "tmpfile" (void @-> returning (ptr void))
+  let fclose = fn "fclose" (ptr void @-> returning int)
+  let fflush = fn "fflush" (ptr void @-> returning int)
+  let fseek = fn "fseek" (ptr void @-> long @-> int @-> returning int)
+  let ftell = fn "ftell" (ptr void @-> returning long)
+  let fgets = fn "fgets" (ptr char @-> int @-> ptr void @-> returning (ptr char))
 
 {generated_foreign_functions}
 end
@@ -31,6 +37,7 @@ end
 type error =
   | InvalidShape of int * int
   | NullPtr
+  | InvalidIndex
   | Code of int
   | UseAfterFree of [`context | `array | `opaque]
 
@@ -45,6 +52,7 @@ let check_use_after_free t b = if b then raise (Error (UseAfterFree t))
 let () = Printexc.register_printer (function
   | Error (InvalidShape (a, b)) -> Some (Printf.sprintf "futhark error: invalid shape, expected %d but got %d" a b)
   | Error NullPtr -> Some "futhark error: null pointer"
+  | Error InvalidIndex -> Some "futhark error: index out of bounds"
   | Error (Code c) -> Some (Printf.sprintf "futhark error: code %d" c) 
   | Error (UseAfterFree `context) -> Some "futhark: context used after beeing freed"
   | Error (UseAfterFree `array) -> Some "futhark: array used after beeing freed"
@@ -4,6 +4,7 @@ open! Unsigned
 type error = 
   | InvalidShape of int * int 
   | NullPtr 
+  | InvalidIndex
   | Code of int
   | UseAfterFree of [`context | `array | `opaque]
 
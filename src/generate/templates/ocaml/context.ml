@@ -2,19 +2,46 @@ open Bigarray
 
 module Context = struct
   [@@@ocaml.warning "-69"]
-  type t = {{ handle: unit ptr; config: unit ptr; cache_file: string option; auto_sync: bool; mutable context_free: bool }}
+  type t = {{ handle: unit ptr; config: unit ptr; cache_file: string option; auto_sync: bool; mutable context_free: bool; log: (unit ptr * (string -> unit)) option; mutable log_pos: int }}
   [@@@ocaml.warning "+69"]
 
+  let flush_log t =
+    match t.log with
+    | None -> ()
+    | Some (file, callback) ->
+      let _ = Bindings.fflush file in
+      let _ = Bindings.fseek file (Long.of_int t.log_pos) 0 in
+      let buf = allocate_n char ~count:4096 in
+      let line = Buffer.create 128 in
+      let rec loop () =
+        if not (is_null (Bindings.fgets buf 4096 file)) then
+          let len = Bindings.strlen buf |> Unsigned.Size_t.to_int in
+          let () = Buffer.add_string line (string_from_ptr buf ~length:len) in
+          let () =
+            if len > 0 && !@(buf +@ (len - 1)) = '\n' then
+              let () = callback (Buffer.sub line 0 (Buffer.length line - 1)) in
+              Buffer.clear line
+          in
+          loop ()
+      in
+      loop ();
+      if Buffer.length line > 0 then callback (Buffer.contents line);
+      t.log_pos <- Long.to_int (Bindings.ftell file);
+      ignore (Bindings.fseek file Long.zero 2)
+
   let free t =
     if not t.context_free then
       let () = ignore (Bindings.futhark_context_sync t.handle) in
       let () = ignore (Bindings.futhark_context_free t.handle) in
       let () = ignore (Bindings.futhark_context_config_free t.config) in
+      let () = flush_log t in
+      let () = Option.iter (fun (file, _) -> ignore (Bindings.fclose file)) t.log in
       t.context_free <- true
 
-  let v ?(debug = false) ?(log = false) ?(profile = false) ?cache_file ?(auto_sync = true) {extra_param} () =
+  let v ?(debug = false) ?(log = false) {log_callback_param}?(profile = false) ?cache_file ?(auto_sync = true) {extra_param} () =
     let config = Bindings.futhark_context_config_new () in
     if is_null config then raise (Error NullPtr);
+    {enable_log}
     Bindings.futhark_context_config_set_debugging config (if debug then 1 else 0);
     Bindings.futhark_context_config_set_profiling config (if profile then 1 else 0);
     Bindings.futhark_context_config_set_logging config (if log then 1 else 0);
@@ -25,7 +52,8 @@ module Context = struct
       let () = ignore @@ Bindings.futhark_context_config_free config in
       raise (Error NullPtr)
     else
-      let t = {{ handle; config; cache_file; auto_sync; context_free = false }} in
+      {open_log}
+      let t = {{ handle; config; cache_file; auto_sync; context_free = false; log; log_pos = 0 }} in
       set_managed handle t; 
       let () = Gc.finalise free t in
       t
@@ -33,6 +61,7 @@ module Context = struct
   let sync t =
     check_use_after_free `context t.context_free;
     let rc = Bindings.futhark_context_sync t.handle in
+    flush_log t;
     if rc <> 0 then raise (Error (Code rc))
 
   let auto_sync t =
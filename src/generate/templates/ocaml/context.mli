@@ -2,11 +2,16 @@ module Context: sig
   type t
   (** Futhark context *)
 
-  val v: ?debug:bool -> ?log:bool -> ?profile:bool -> ?cache_file:string -> ?auto_sync:bool -> {extra_mli} unit -> t
-  (** Create a new context *)
+  val v: ?debug:bool -> ?log:bool -> {log_callback_mli}?profile:bool -> ?cache_file:string -> ?auto_sync:bool -> {extra_mli} unit -> t
+  (** Create a new context, when [log_callback] is set logging is enabled and each log line
+      is passed to the callback instead of being written to stderr *)
   
   val sync: t -> unit
   (** Sync the context, if auto_sync is enabled this is not needed *)
+
+  val flush_log: t -> unit
+  (** Pass any pending log messages to [log_callback], this happens automatically when the
+      context is synced or freed *)
   
   val free: t -> unit
   (** Free the context *)
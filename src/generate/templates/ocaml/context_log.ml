@@ -0,0 +1,12 @@
+      let log = match log_callback with
+        | None -> None
+        | Some callback ->
+          let file = Bindings.tmpfile () in
+          if is_null file then
+            let () = ignore @@ Bindings.futhark_context_free handle in
+            let () = ignore @@ Bindings.futhark_context_config_free config in
+            raise (Error NullPtr)
+          else
+            let () = Bindings.futhark_context_set_logging_file handle file in
+            Some (file, callback)
+      in
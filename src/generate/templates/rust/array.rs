@@ -8,12 +8,17 @@ struct {futhark_type} {{
 pub struct {rust_type}<'a> {{
     ptr: *mut {futhark_type},
     pub shape: [i64; {rank}],
-    ctx: &'a Context,
+    ctx: ContextRef<'a>,
 }}
 
+// Arrays are only used through the context, which is locked by Futhark
+unsafe impl<'a> Send for {rust_type}<'a> {{}}
+unsafe impl<'a> Sync for {rust_type}<'a> {{}}
+
 impl<'a> {rust_type}<'a> {{
     /// Create a new array of `dims` dimensions and initialize it with the values from `data`
-    pub fn new(ctx: &'a Context, dims: [i64; {rank}], data: impl AsRef<[{elemtype}]>) -> std::result::Result<Self, Error> {{
+    pub fn new(ctx: impl Into<ContextRef<'a>>, dims: [i64; {rank}], data: impl AsRef<[{elemtype}]>) -> std::result::Result<Self, Error> {{
+        let ctx = ctx.into();
         let size: i64 = dims.iter().product();
         let data = data.as_ref();
         if data.len() as i64 != size {{
@@ -63,7 +68,8 @@ impl<'a> {rust_type}<'a> {{
 
 
     #[allow(unused)]
-    fn from_ptr(ctx: &'a Context, ptr: *mut {futhark_type}) -> Self {{
+    fn from_ptr(ctx: impl Into<ContextRef<'a>>, ptr: *mut {futhark_type}) -> Self {{
+        let ctx = ctx.into();
         let len_ptr = unsafe {{ futhark_shape_{elemtype}_{rank}d(ctx.context, ptr) }};
         let mut shape = [0i64; {rank}];
         unsafe {{
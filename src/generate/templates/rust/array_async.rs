@@ -0,0 +1,30 @@
+impl {rust_type}<'static> {{
+    /// Asynchronously load values into `data`, which is returned once the copy is finished
+    ///
+    /// The copy and `futhark_context_sync` are called on a worker thread
+    pub fn values_async<D: AsMut<[{elemtype}]> + Send + 'static>(self: &std::sync::Arc<Self>, mut data: D) -> Blocking<std::result::Result<D, Error>> {{
+        let array = self.clone();
+        spawn_blocking(move || {{
+            let size: i64 = array.shape.iter().product();
+            let out = data.as_mut();
+            if out.len() as i64 != size {{
+                return Err(Error::InvalidShape);
+            }}
+            let rc = unsafe {{
+                let rc = {values_fn}(array.ctx.context, array.ptr, out.as_mut_ptr());
+                if rc != 0 {{ rc }} else {{ futhark_context_sync(array.ctx.context) }}
+            }};
+            array.ctx.flush_log();
+            if rc != 0 {{
+                return Err(Error::Code(rc));
+            }}
+            Ok(data)
+        }})
+    }}
+
+    /// Asynchronously load values into a `Vec`
+    pub fn get_async(self: &std::sync::Arc<Self>) -> Blocking<std::result::Result<Vec<{elemtype}>, Error>> {{
+        let size: i64 = self.shape.iter().product();
+        self.values_async(vec![{elemtype}::default(); size as usize])
+    }}
+}}
@@ -0,0 +1,47 @@
+impl<'a> {rust_type}<'a> {{
+    /// Encode the array using the Futhark binary data format, as used by `futhark test`,
+    /// `futhark bench` and server mode
+    pub fn to_futhark_data(&self) -> std::result::Result<Vec<u8>, Error> {{
+        let data = self.get()?;
+        let mut out = Vec::with_capacity(7 + {rank} * 8 + data.len() * {elem_size});
+        out.extend_from_slice(&[b'b', 2, {rank}]);
+        out.extend_from_slice(b"{binary_name}");
+        for dim in &self.shape {{
+            out.extend_from_slice(&dim.to_le_bytes());
+        }}
+        for x in data {{
+            {write_elem};
+        }}
+        Ok(out)
+    }}
+
+    /// Create a new array from a value in the Futhark binary data format
+    pub fn from_futhark_data(ctx: impl Into<ContextRef<'a>>, data: &[u8]) -> std::result::Result<Self, Error> {{
+        let header = 7 + {rank} * 8;
+        if data.len() < header || data[0] != b'b' || data[1] != 2 {{
+            return Err(Error::InvalidData("expected a binary value".to_string()));
+        }}
+        if data[2] != {rank} || &data[3..7] != b"{binary_name}" {{
+            return Err(Error::InvalidData("expected rank {rank} and type {elemtype}".to_string()));
+        }}
+        let mut dims = [0i64; {rank}];
+        for (dim, b) in dims.iter_mut().zip(data[7..header].chunks_exact(8)) {{
+            *dim = i64::from_le_bytes(b.try_into().unwrap());
+        }}
+        // Negative dimensions and sizes that overflow are rejected
+        let end = dims
+            .iter()
+            .try_fold(1usize, |n, d| n.checked_mul(usize::try_from(*d).ok()?))
+            .and_then(|n| n.checked_mul({elem_size}))
+            .and_then(|n| n.checked_add(header))
+            .ok_or(Error::InvalidShape)?;
+        if data.len() < end {{
+            return Err(Error::InvalidData("truncated data".to_string()));
+        }}
+        let values: Vec<{elemtype}> = data[header..end]
+            .chunks_exact({elem_size})
+            .map(|b| {read_elem})
+            .collect();
+        Self::new(ctx, dims, values)
+    }}
+}}
@@ -0,0 +1,81 @@
+impl<'a> {rust_type}<'a> {{
+    /// Start reading the element at `index` into `out`, the context must be synced before `out`
+    /// is read because `futhark_index_*` can finish asynchronously
+    unsafe fn read_index(&self, index: [i64; {rank}], out: *mut {elemtype}) -> std::os::raw::c_int {{
+        {index_fn}(self.ctx.context, out, self.ptr, {index_args})
+    }}
+
+    /// Read a single element without loading the entire array, the context is always synced
+    pub fn index(&self, index: [i64; {rank}]) -> std::result::Result<{elemtype}, Error> {{
+        if index.iter().zip(self.shape.iter()).any(|(i, n)| *i < 0 || *i >= *n) {{
+            return Err(Error::InvalidIndex);
+        }}
+        let mut out = {elemtype}::default();
+        let rc = unsafe {{ self.read_index(index, &mut out) }};
+        if rc != 0 {{
+            return Err(Error::Code(rc));
+        }}
+        let rc = unsafe {{ futhark_context_sync(self.ctx.context) }};
+        self.ctx.flush_log();
+        if rc != 0 {{
+            return Err(Error::Code(rc));
+        }}
+        Ok(out)
+    }}
+
+    /// Read the elements from `start` (inclusive) to `end` (exclusive) in each dimension, the
+    /// values are returned in row-major order
+    ///
+    /// Each element is read using a separate `futhark_index_*` call, so reading `n` elements
+    /// takes `n` calls and this is intended for small regions of large arrays. The context is
+    /// synced once after all the calls
+    pub fn slice(&self, start: [i64; {rank}], end: [i64; {rank}]) -> std::result::Result<Vec<{elemtype}>, Error> {{
+        for i in 0..{rank} {{
+            if start[i] < 0 || start[i] > end[i] || end[i] > self.shape[i] {{
+                return Err(Error::InvalidIndex);
+            }}
+        }}
+        let size = start.iter().zip(end.iter()).map(|(s, e)| (e - s) as usize).product::<usize>();
+        let mut out: Vec<{elemtype}> = Vec::with_capacity(size);
+        let mut index = start;
+        let mut rc = 0;
+        for i in 0..size {{
+            // The elements are written directly into the capacity of `out`, which isn't
+            // reallocated before the sync below
+            rc = unsafe {{ self.read_index(index, out.as_mut_ptr().add(i)) }};
+            if rc != 0 {{
+                break;
+            }}
+            let mut d = {rank};
+            while d > 0 {{
+                d -= 1;
+                index[d] += 1;
+                if index[d] < end[d] {{
+                    break;
+                }}
+                index[d] = start[d];
+            }}
+        }}
+
+        // Sync even when a call failed, earlier reads may still be writing into `out`
+        let sync_rc = unsafe {{ futhark_context_sync(self.ctx.context) }};
+        self.ctx.flush_log();
+        if rc != 0 {{
+            return Err(Error::Code(rc));
+        }}
+        if sync_rc != 0 {{
+            return Err(Error::Code(sync_rc));
+        }}
+        unsafe {{ out.set_len(size) }};
+        Ok(out)
+    }}
+}}
+
+extern "C" {{
+    fn {index_fn}(
+        _: *mut futhark_context,
+        _: *mut {elemtype},
+        _: *mut {futhark_type},
+        {index_params}
+    ) -> std::os::raw::c_int;
+}}
@@ -0,0 +1,42 @@
+impl<'a> {rust_type}<'a> {{
+    /// Create a new array of `dims` dimensions using existing device memory, without copying
+    ///
+    /// # Safety
+    ///
+    /// `data` must be a valid device allocation containing at least as many elements as specified
+    /// by `dims`. It is not freed by Futhark and must outlive the returned array
+    pub unsafe fn from_raw(ctx: impl Into<ContextRef<'a>>, dims: [i64; {rank}], data: RawPtr) -> std::result::Result<Self, Error> {{
+        let ctx = ctx.into();
+        let ptr = {new_raw_fn}(ctx.context, data, {dim_params});
+        if ptr.is_null() {{ return Err(Error::NullPtr); }}
+        ctx.auto_sync();
+        Ok(Self {{
+            ptr: ptr as *mut _,
+            shape: dims,
+            ctx,
+        }})
+    }}
+
+    /// Get the device memory backing the array
+    ///
+    /// # Safety
+    ///
+    /// The returned memory is owned by Futhark and is only valid while the array is alive, the
+    /// context should be synced before accessing it from another library
+    pub unsafe fn as_raw(&self) -> RawPtr {{
+        {values_raw_fn}(self.ctx.context, self.ptr)
+    }}
+}}
+
+extern "C" {{
+    fn {new_raw_fn}(
+        _: *mut futhark_context,
+        _: RawPtr,
+        {new_dim_args}
+    ) -> *mut {futhark_type};
+
+    fn {values_raw_fn}(
+        _: *mut futhark_context,
+        _: *mut {futhark_type}
+    ) -> RawPtr;
+}}
@@ -0,0 +1,79 @@
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Worker threads shared by all contexts, used to run blocking Futhark calls
+fn blocking_pool() -> &'static std::sync::Mutex<std::sync::mpsc::Sender<Job>> {{
+    static POOL: std::sync::OnceLock<std::sync::Mutex<std::sync::mpsc::Sender<Job>>> = std::sync::OnceLock::new();
+    POOL.get_or_init(|| {{
+        let (sender, receiver) = std::sync::mpsc::channel::<Job>();
+        let receiver = std::sync::Arc::new(std::sync::Mutex::new(receiver));
+        let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        for _ in 0..threads {{
+            let receiver = receiver.clone();
+            std::thread::spawn(move || loop {{
+                let job = match receiver.lock().unwrap().recv() {{
+                    Ok(job) => job,
+                    Err(_) => return,
+                }};
+                job();
+            }});
+        }}
+        std::sync::Mutex::new(sender)
+    }})
+}}
+
+struct BlockingState<T> {{
+    value: std::option::Option<std::thread::Result<T>>,
+    waker: std::option::Option<std::task::Waker>,
+}}
+
+/// Future returned by the `async` methods, the work runs on a pool of worker threads
+///
+/// The work owns everything it uses, so the future is `Send + 'static` and can be passed to
+/// `tokio::spawn`. Dropping the future doesn't cancel the work, the result is dropped once the
+/// work is finished
+pub struct Blocking<T> {{
+    state: std::sync::Arc<std::sync::Mutex<BlockingState<T>>>,
+}}
+
+impl<T> std::future::Future for Blocking<T> {{
+    type Output = T;
+
+    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<T> {{
+        let mut state = self.state.lock().unwrap();
+        match state.value.take() {{
+            Some(Ok(x)) => std::task::Poll::Ready(x),
+            Some(Err(e)) => {{
+                drop(state);
+                std::panic::resume_unwind(e)
+            }}
+            None => {{
+                state.waker = Some(cx.waker().clone());
+                std::task::Poll::Pending
+            }}
+        }}
+    }}
+}}
+
+/// Run `f` on a worker thread, returning a future that resolves to the result
+fn spawn_blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Blocking<T> {{
+    let state = std::sync::Arc::new(std::sync::Mutex::new(BlockingState {{
+        value: None,
+        waker: None,
+    }}));
+    let job_state = state.clone();
+    let job: Job = Box::new(move || {{
+        let value = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+        let waker = {{
+            let mut state = job_state.lock().unwrap();
+            state.value = Some(value);
+            state.waker.take()
+        }};
+        if let Some(waker) = waker {{
+            waker.wake();
+        }}
+    }});
+    if let Err(std::sync::mpsc::SendError(job)) = blocking_pool().lock().unwrap().send(job) {{
+        job();
+    }}
+    Blocking {{ state }}
+}}
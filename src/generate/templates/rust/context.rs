@@ -3,6 +3,8 @@ pub enum Error {{
     Code(std::os::raw::c_int),
     NullPtr,
     InvalidShape,
+    InvalidIndex,
+    InvalidData(String),
 }}
 
 impl std::fmt::Display for Error {{
@@ -11,6 +13,8 @@ impl std::fmt::Display for Error {{
             Error::Code(code) => write!(fmt, "Futhark error code: {{code}}"),
             Error::NullPtr => write!(fmt, "NULL pointer encountered"),
             Error::InvalidShape => write!(fmt, "Invalid image shape"),
+            Error::InvalidIndex => write!(fmt, "Index out of bounds"),
+            Error::InvalidData(msg) => write!(fmt, "Invalid Futhark data: {{msg}}"),
         }}
     }} 
 }}
@@ -22,6 +26,7 @@ pub struct Options {{
     debug: bool,
     profile: bool,
     logging: bool,
+    {log_options_field}
     num_threads: u32,
     cache_file: std::option::Option<std::ffi::CString>,
     device: std::option::Option<std::ffi::CString>,
@@ -41,6 +46,7 @@ impl Options {{
             debug: false,
             profile: false,
             logging: false,
+            {log_options_init}
             num_threads: 0,
             cache_file: None,
             device: None,
@@ -60,7 +66,7 @@ impl Options {{
         self
     }}
 
-    /// Enable logging
+    /// Enable logging, messages are written to `stderr`
     pub fn log(mut self) -> Self {{
         self.logging = true;
         self
@@ -87,6 +93,51 @@ pub struct Context {{
     context: *mut futhark_context,
     auto_sync: bool,
     _cache_file: std::option::Option<std::ffi::CString>,
+    {log_context_field}
+}}
+
+// The Futhark C API locks the context in every function that uses it, so a context can be
+// shared between threads
+unsafe impl Send for Context {{}}
+unsafe impl Sync for Context {{}}
+
+/// Context used by arrays and opaque values, either borrowed or shared using an `Arc`
+///
+/// Values created with a shared context aren't tied to a borrow, so they can be moved to other
+/// threads and tasks
+#[derive(Clone)]
+pub enum ContextRef<'a> {{
+    Borrowed(&'a Context),
+    Shared(std::sync::Arc<Context>),
+}}
+
+impl<'a> std::ops::Deref for ContextRef<'a> {{
+    type Target = Context;
+
+    fn deref(&self) -> &Context {{
+        match self {{
+            ContextRef::Borrowed(ctx) => ctx,
+            ContextRef::Shared(ctx) => ctx,
+        }}
+    }}
+}}
+
+impl<'a> From<&'a Context> for ContextRef<'a> {{
+    fn from(ctx: &'a Context) -> Self {{
+        ContextRef::Borrowed(ctx)
+    }}
+}}
+
+impl<'a> From<&'a std::sync::Arc<Context>> for ContextRef<'a> {{
+    fn from(ctx: &'a std::sync::Arc<Context>) -> Self {{
+        ContextRef::Borrowed(ctx)
+    }}
+}}
+
+impl From<std::sync::Arc<Context>> for ContextRef<'static> {{
+    fn from(ctx: std::sync::Arc<Context>) -> Self {{
+        ContextRef::Shared(ctx)
+    }}
 }}
 
 impl Context {{
@@ -100,7 +151,7 @@ impl Context {{
                 futhark_context_config_free(config);
                 return Err(Error::NullPtr);
             }}
-            Ok(Context {{ config, context, auto_sync: true, _cache_file: None }})
+            Ok(Context {{ config, context, auto_sync: true, _cache_file: None, {log_context_init} }})
         }}
     }}
 
@@ -126,13 +177,21 @@ impl Context {{
                 futhark_context_config_free(config);
                 return Err(Error::NullPtr);
             }}
-            Ok(Context {{ config, context, auto_sync: options.auto_sync, _cache_file: options.cache_file }})
+
+            {configure_log}
+            Ok(Context {{ config, context, auto_sync: options.auto_sync, _cache_file: options.cache_file, {log_context_value} }})
         }}
     }}
 
     /// Sync the context, if `auto_sync` is enabled this shouldn't be needed
     pub fn sync(&self) {{
         unsafe {{ futhark_context_sync(self.context); }}
+        self.flush_log();
+    }}
+
+    /// Pass any pending log messages to the callback set using `Options::log_callback`
+    pub fn flush_log(&self) {{
+        {flush_log}
     }}
 
     /// Sync if `auto_sync` is enabled, otherwise this is a noop
@@ -194,6 +253,7 @@ impl Drop for Context {{
             futhark_context_free(self.context);
             futhark_context_config_free(self.config);
         }}
+        self.flush_log();
     }}
 }}
 
@@ -1,6 +1,6 @@
 impl Context {{
     /// Entry point: {entry_name}
-    pub fn {entry_name}(&self, {entry_params}) -> Result<{entry_return_type}, Error> {{
+    pub fn {entry_name}{entry_lifetimes}(&self, {entry_params}) -> Result<{entry_return_type}, Error> {{
         {out_decl}
         let rc = unsafe {{
             futhark_entry_{entry_name}(self.context, {call_args})
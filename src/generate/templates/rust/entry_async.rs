@@ -0,0 +1,25 @@
+impl Context {{
+    /// Entry point: {entry_name}
+    ///
+    /// The entry point and `futhark_context_sync` are called on a worker thread. Array and opaque
+    /// inputs are moved or shared using an `Arc` and the outputs hold a shared context, so the
+    /// returned future can be passed to `tokio::spawn`
+    pub fn {entry_name}_async(self: &std::sync::Arc<Self>, {entry_params}) -> Blocking<Result<{entry_return_type}, Error>> {{
+        let ctx = self.clone();
+        {shared_inputs}
+        spawn_blocking(move || {{
+            {out_decl}
+            let rc = unsafe {{
+                let rc = {entry_fn}(ctx.context, {call_args});
+                if rc != 0 {{ rc }} else {{ futhark_context_sync(ctx.context) }}
+            }};
+            ctx.flush_log();
+            if rc != 0 {{ return Err(Error::Code(rc)); }}
+
+            #[allow(unused_unsafe)]
+            unsafe {{
+                Ok({entry_return})
+            }}
+        }})
+    }}
+}}
@@ -0,0 +1,83 @@
+/// Callback used to receive Futhark log messages, one line at a time
+#[derive(Clone)]
+pub struct LogCallback(std::sync::Arc<dyn Fn(&str) + Send + Sync>);
+
+impl std::fmt::Debug for LogCallback {{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {{
+        write!(fmt, "LogCallback")
+    }}
+}}
+
+impl Options {{
+    /// Enable logging and send each log line to `f` instead of `stderr`
+    ///
+    /// Messages are delivered when the context is synced, when `Context::flush_log` is called
+    /// and when the context is dropped
+    pub fn log_callback(mut self, f: impl Fn(&str) + Send + Sync + 'static) -> Self {{
+        self.logging = true;
+        self.log_callback = Some(LogCallback(std::sync::Arc::new(f)));
+        self
+    }}
+}}
+
+/// Temporary file the Futhark log is redirected to
+struct Log {{
+    file: *mut std::ffi::c_void,
+    pos: std::sync::Mutex<std::os::raw::c_long>,
+    callback: LogCallback,
+}}
+
+impl Log {{
+    unsafe fn new(context: *mut futhark_context, callback: LogCallback) -> std::option::Option<Log> {{
+        let file = tmpfile();
+        if file.is_null() {{
+            return None;
+        }}
+        futhark_context_set_logging_file(context, file);
+        Some(Log {{ file, pos: std::sync::Mutex::new(0), callback }})
+    }}
+
+    fn flush(&self) {{
+        let mut buf = [0 as std::os::raw::c_char; 4096];
+        let mut line = Vec::new();
+        // The lock also keeps threads from reading the file at the same time
+        let mut pos = self.pos.lock().unwrap();
+        unsafe {{
+            fflush(self.file);
+            fseek(self.file, *pos, 0);
+            while !fgets(buf.as_mut_ptr(), buf.len() as std::os::raw::c_int, self.file).is_null() {{
+                line.extend_from_slice(std::ffi::CStr::from_ptr(buf.as_ptr()).to_bytes());
+                if line.last() == Some(&b'\n') {{
+                    line.pop();
+                    (self.callback.0)(&String::from_utf8_lossy(&line));
+                    line.clear();
+                }}
+            }}
+            if !line.is_empty() {{
+                (self.callback.0)(&String::from_utf8_lossy(&line));
+            }}
+            *pos = ftell(self.file);
+            fseek(self.file, 0, 2);
+        }}
+    }}
+}}
+
+impl Drop for Log {{
+    fn drop(&mut self) {{
+        unsafe {{ fclose(self.file); }}
+    }}
+}}
+
+extern "C" {{
+    fn futhark_context_set_logging_file(
+        _: *mut futhark_context,
+        _: *mut std::ffi::c_void
+    );
+
+    fn tmpfile() -> *mut std::ffi::c_void;
+    fn fclose(_: *mut std::ffi::c_void) -> std::os::raw::c_int;
+    fn fflush(_: *mut std::ffi::c_void) -> std::os::raw::c_int;
+    fn fseek(_: *mut std::ffi::c_void, _: std::os::raw::c_long, _: std::os::raw::c_int) -> std::os::raw::c_int;
+    fn ftell(_: *mut std::ffi::c_void) -> std::os::raw::c_long;
+    fn fgets(_: *mut std::os::raw::c_char, _: std::os::raw::c_int, _: *mut std::ffi::c_void) -> *mut std::os::raw::c_char;
+}}
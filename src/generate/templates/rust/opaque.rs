@@ -14,13 +14,17 @@ extern "C" {{
 /// Futhark type
 pub struct {rust_type}<'a> {{
     data: *mut {futhark_type},
-    ctx: &'a Context,
+    ctx: ContextRef<'a>,
 }}
 
+// Opaque values are only used through the context, which is locked by Futhark
+unsafe impl<'a> Send for {rust_type}<'a> {{}}
+unsafe impl<'a> Sync for {rust_type}<'a> {{}}
+
 impl<'a> {rust_type}<'a> {{
     #[allow(unused)]
-    fn from_ptr(ctx: &'a Context, data: *mut {futhark_type}) -> Self {{
-        Self {{ ctx, data }}
+    fn from_ptr(ctx: impl Into<ContextRef<'a>>, data: *mut {futhark_type}) -> Self {{
+        Self {{ ctx: ctx.into(), data }}
     }}
 }}
 
@@ -0,0 +1,67 @@
+/// Entry point result that may still be computing on the device
+///
+/// Call `wait` to sync the context before reading the value on the host, or pass it directly to
+/// another entry point to keep working on the device without syncing
+///
+/// Entry points with multiple outputs return a tuple with a `Pending` for each output, so each
+/// output can be passed to another entry point separately. The outputs share one sync, after
+/// the first `wait` the others return their values without syncing again
+#[must_use]
+pub struct Pending<'a, T> {{
+    value: T,
+    ctx: &'a Context,
+    synced: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}}
+
+impl<'a, T> Pending<'a, T> {{
+    fn new(value: T, ctx: &'a Context, synced: &std::sync::Arc<std::sync::atomic::AtomicBool>) -> Self {{
+        Pending {{
+            value,
+            ctx,
+            synced: synced.clone(),
+        }}
+    }}
+
+    /// Sync the context and return the value
+    pub fn wait(self) -> std::result::Result<T, Error> {{
+        if !self.synced.load(std::sync::atomic::Ordering::Acquire) {{
+            let rc = unsafe {{ futhark_context_sync(self.ctx.context) }};
+            self.ctx.flush_log();
+            if rc != 0 {{
+                return Err(Error::Code(rc));
+            }}
+            self.synced.store(true, std::sync::atomic::Ordering::Release);
+        }}
+        Ok(self.value)
+    }}
+}}
+
+mod sealed {{
+    /// Only the generated code can create this, so `Sealed::input` can't be called elsewhere
+    pub struct Private;
+
+    pub trait Sealed<T> {{
+        fn input(&self, _: Private) -> &T;
+    }}
+}}
+
+/// Values that can be used as entry point inputs, this includes `Pending` results
+///
+/// This trait is sealed, the value in a `Pending` result can only be read using `Pending::wait`
+pub trait Input<T>: sealed::Sealed<T> {{}}
+
+impl<T> sealed::Sealed<T> for T {{
+    fn input(&self, _: sealed::Private) -> &T {{
+        self
+    }}
+}}
+
+impl<T> Input<T> for T {{}}
+
+impl<'a, T> sealed::Sealed<T> for Pending<'a, T> {{
+    fn input(&self, _: sealed::Private) -> &T {{
+        &self.value
+    }}
+}}
+
+impl<'a, T> Input<T> for Pending<'a, T> {{}}
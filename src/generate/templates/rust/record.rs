@@ -1,6 +1,7 @@
 impl<'a> {rust_type}<'a> {{
     /// Create new {rust_type}
-    pub fn new(ctx: &'a Context, {new_params}) -> std::result::Result<Self, Error> {{
+    pub fn new(ctx: impl Into<ContextRef<'a>>, {new_params}) -> std::result::Result<Self, Error> {{
+        let ctx = ctx.into();
         unsafe {{
             let mut out = std::ptr::null_mut();
             let rc = {new_fn}(ctx.context, &mut out, {new_call_args});
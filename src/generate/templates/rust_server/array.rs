@@ -0,0 +1,54 @@
+/// Array type with {rank} dimensions and {elemtype} elements, stored in the server
+pub struct {rust_type}<'a> {{
+    var: Var<'a>,
+    shape: std::cell::OnceCell<[i64; {rank}]>,
+}}
+
+impl<'a> {rust_type}<'a> {{
+    /// Create a new array of `dims` dimensions and initialize it with the values from `data`
+    pub fn new(server: &'a Server, dims: [i64; {rank}], data: impl AsRef<[{elemtype}]>) -> std::result::Result<Self, Error> {{
+        let size: i64 = dims.iter().product();
+        let data = data.as_ref();
+        if data.len() as i64 != size {{
+            return Err(Error::InvalidShape)
+        }}
+        let var = server.restore({type_name}, &encode(&dims, data))?;
+        Ok(Self {{
+            var,
+            shape: std::cell::OnceCell::from(dims),
+        }})
+    }}
+
+    /// Get the array shape, the first call reads the array from the server if it was returned
+    /// by an entry point
+    pub fn shape(&self) -> std::result::Result<[i64; {rank}], Error> {{
+        if let Some(shape) = self.shape.get() {{
+            return Ok(*shape);
+        }}
+        self.get()?;
+        Ok(self.shape.get().copied().unwrap())
+    }}
+
+    /// Load values back into a slice
+    pub fn values(&self, mut data: impl AsMut<[{elemtype}]>) -> std::result::Result<(), Error> {{
+        let values = self.get()?;
+        let data = data.as_mut();
+        if data.len() != values.len() {{
+            return Err(Error::InvalidShape);
+        }}
+        data.copy_from_slice(&values);
+        Ok(())
+    }}
+
+    /// Load values into a `Vec`
+    pub fn get(&self) -> std::result::Result<Vec<{elemtype}>, Error> {{
+        let (dims, data) = self.var.get::<{elemtype}>({rank})?;
+        let _ = self.shape.set(dims.try_into().unwrap());
+        Ok(data)
+    }}
+
+    #[allow(unused)]
+    fn from_var(var: Var<'a>) -> Self {{
+        Self {{ var, shape: std::cell::OnceCell::new() }}
+    }}
+}}
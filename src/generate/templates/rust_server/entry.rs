@@ -0,0 +1,9 @@
+impl Server {{
+    /// Entry point: {entry_name}
+    pub fn {entry_name}(&self, {entry_params}) -> std::result::Result<{entry_return_type}, Error> {{
+        {input_vars}
+        {out_decl}
+        self.call("{entry_name}", &[{outputs}], &[{inputs}])?;
+        Ok({entry_return})
+    }}
+}}
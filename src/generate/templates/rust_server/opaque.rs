@@ -0,0 +1,22 @@
+/// Futhark type stored in the server
+pub struct {rust_type}<'a> {{
+    var: Var<'a>,
+}}
+
+impl<'a> {rust_type}<'a> {{
+    /// Create a value from the bytes returned by `to_bytes`
+    pub fn from_bytes(server: &'a Server, bytes: impl AsRef<[u8]>) -> std::result::Result<Self, Error> {{
+        let var = server.restore({type_name}, bytes.as_ref())?;
+        Ok(Self {{ var }})
+    }}
+
+    /// Serialize the value using the Futhark opaque binary format
+    pub fn to_bytes(&self) -> std::result::Result<Vec<u8>, Error> {{
+        self.var.server.store(&self.var.name)
+    }}
+
+    #[allow(unused)]
+    fn from_var(var: Var<'a>) -> Self {{
+        Self {{ var }}
+    }}
+}}
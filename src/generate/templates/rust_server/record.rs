@@ -0,0 +1,9 @@
+impl<'a> {rust_type}<'a> {{
+    /// Create new {rust_type}
+    pub fn new(server: &'a Server, {new_params}) -> std::result::Result<Self, Error> {{
+        {field_vars}
+        let var = Var::output(server);
+        server.command(&["new", &var.name, {type_name}, {new_args}].join(" "))?;
+        Ok(Self {{ var }})
+    }}
+}}
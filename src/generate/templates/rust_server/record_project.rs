@@ -0,0 +1,9 @@
+impl<'a> {rust_type}<'a> {{
+    /// Get {field_name} field
+    pub fn get_{field_name}(&self) -> std::result::Result<{rust_field_type}, Error> {{
+        let server = self.var.server;
+        let out = Var::output(server);
+        server.command(&["project", &out.name, &self.var.name, "{field_name}"].join(" "))?;
+        {output}
+    }}
+}}
@@ -0,0 +1,332 @@
+#[derive(Debug)]
+pub enum Error {{
+    Io(std::io::Error),
+    Server(String),
+    InvalidShape,
+    InvalidData(String),
+}}
+
+impl std::fmt::Display for Error {{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {{
+        match self {{
+            Error::Io(e) => write!(fmt, "Futhark server I/O error: {{e}}"),
+            Error::Server(msg) => write!(fmt, "Futhark server error: {{msg}}"),
+            Error::InvalidShape => write!(fmt, "Invalid array shape"),
+            Error::InvalidData(msg) => write!(fmt, "Invalid Futhark data: {{msg}}"),
+        }}
+    }}
+}}
+
+impl std::error::Error for Error {{}}
+
+impl From<std::io::Error> for Error {{
+    fn from(e: std::io::Error) -> Self {{
+        Error::Io(e)
+    }}
+}}
+
+/// Element types that can be transferred using the Futhark binary data format
+pub trait Elem: Copy + Default {{
+    /// Type name, padded to 4 bytes
+    const TYPE: &'static [u8; 4];
+
+    /// Size in bytes
+    const SIZE: usize;
+
+    fn write(self, out: &mut Vec<u8>);
+    fn read(bytes: &[u8]) -> Self;
+}}
+
+macro_rules! elem {{
+    ($t:ty, $name:expr) => {{
+        impl Elem for $t {{
+            const TYPE: &'static [u8; 4] = $name;
+            const SIZE: usize = std::mem::size_of::<$t>();
+
+            fn write(self, out: &mut Vec<u8>) {{
+                out.extend_from_slice(&self.to_le_bytes());
+            }}
+
+            fn read(bytes: &[u8]) -> Self {{
+                <$t>::from_le_bytes(bytes.try_into().unwrap())
+            }}
+        }}
+    }};
+}}
+
+elem!(i8, b"  i8");
+elem!(i16, b" i16");
+elem!(i32, b" i32");
+elem!(i64, b" i64");
+elem!(u8, b"  u8");
+elem!(u16, b" u16");
+elem!(u32, b" u32");
+elem!(u64, b" u64");
+elem!(f32, b" f32");
+elem!(f64, b" f64");
+{f16_elem}
+impl Elem for bool {{
+    const TYPE: &'static [u8; 4] = b"bool";
+    const SIZE: usize = 1;
+
+    fn write(self, out: &mut Vec<u8>) {{
+        out.push(self as u8);
+    }}
+
+    fn read(bytes: &[u8]) -> Self {{
+        bytes[0] != 0
+    }}
+}}
+
+/// Encode a value in the Futhark binary data format
+fn encode<T: Elem>(dims: &[i64], data: &[T]) -> Vec<u8> {{
+    let mut out = Vec::with_capacity(7 + dims.len() * 8 + data.len() * T::SIZE);
+    out.extend_from_slice(&[b'b', 2, dims.len() as u8]);
+    out.extend_from_slice(T::TYPE);
+    for dim in dims {{
+        out.extend_from_slice(&dim.to_le_bytes());
+    }}
+    for x in data {{
+        x.write(&mut out);
+    }}
+    out
+}}
+
+/// Decode a value in the Futhark binary data format, returns the shape and the elements
+fn decode<T: Elem>(bytes: &[u8], rank: usize) -> std::result::Result<(Vec<i64>, Vec<T>), Error> {{
+    let bytes = match bytes.iter().position(|b| !b.is_ascii_whitespace()) {{
+        Some(start) => &bytes[start..],
+        None => return Err(Error::InvalidData("empty value".to_string())),
+    }};
+    if bytes.len() < 7 || bytes[0] != b'b' || bytes[1] != 2 {{
+        return Err(Error::InvalidData("expected a binary value".to_string()));
+    }}
+    if bytes[2] as usize != rank || &bytes[3..7] != T::TYPE {{
+        return Err(Error::InvalidData(format!(
+            "expected rank {{rank}} and type {{}}",
+            String::from_utf8_lossy(T::TYPE).trim()
+        )));
+    }}
+
+    let header = 7 + rank * 8;
+    if bytes.len() < header {{
+        return Err(Error::InvalidData("truncated shape".to_string()));
+    }}
+    let dims: Vec<i64> = bytes[7..header]
+        .chunks_exact(8)
+        .map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    // Negative dimensions and sizes that overflow are rejected
+    let end = dims
+        .iter()
+        .try_fold(1usize, |n, d| n.checked_mul(usize::try_from(*d).ok()?))
+        .and_then(|n| n.checked_mul(T::SIZE))
+        .and_then(|n| n.checked_add(header))
+        .ok_or(Error::InvalidShape)?;
+    if bytes.len() < end {{
+        return Err(Error::InvalidData("truncated data".to_string()));
+    }}
+    let data = bytes[header..end]
+        .chunks_exact(T::SIZE)
+        .map(T::read)
+        .collect();
+    Ok((dims, data))
+}}
+
+struct Pipes {{
+    stdin: std::option::Option<std::process::ChildStdin>,
+    stdout: std::io::BufReader<std::process::ChildStdout>,
+}}
+
+/// Futhark server process, values are stored in the server and referred to by name
+pub struct Server {{
+    child: std::cell::RefCell<std::process::Child>,
+    pipes: std::cell::RefCell<Pipes>,
+    counter: std::cell::Cell<u64>,
+    dir: std::path::PathBuf,
+}}
+
+impl Server {{
+    /// Start the server executable built using `futhark <backend> --server`
+    pub fn new(exe: impl AsRef<std::ffi::OsStr>) -> std::result::Result<Self, Error> {{
+        Server::new_with_args(exe, std::iter::empty::<&str>())
+    }}
+
+    /// Start the server executable with extra arguments, for example `--device` or `--cache-file`
+    pub fn new_with_args(
+        exe: impl AsRef<std::ffi::OsStr>,
+        args: impl IntoIterator<Item = impl AsRef<std::ffi::OsStr>>,
+    ) -> std::result::Result<Self, Error> {{
+        static SERVERS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+        let mut child = std::process::Command::new(exe)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()?;
+        let pipes = Pipes {{
+            stdin: child.stdin.take(),
+            stdout: std::io::BufReader::new(child.stdout.take().unwrap()),
+        }};
+        let dir = std::env::temp_dir().join(format!(
+            "futhark-server-{{}}-{{}}",
+            std::process::id(),
+            SERVERS.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir)?;
+
+        let server = Server {{
+            child: std::cell::RefCell::new(child),
+            pipes: std::cell::RefCell::new(pipes),
+            counter: std::cell::Cell::new(0),
+            dir,
+        }};
+
+        // The server prints `%%% OK` once it's ready
+        server.response()?;
+        Ok(server)
+    }}
+
+    fn response(&self) -> std::result::Result<Vec<String>, Error> {{
+        use std::io::BufRead;
+
+        let mut pipes = self.pipes.borrow_mut();
+        let mut lines = Vec::new();
+        let mut failed = false;
+        loop {{
+            let mut line = String::new();
+            if pipes.stdout.read_line(&mut line)? == 0 {{
+                return Err(Error::Server("server exited".to_string()));
+            }}
+            let line = line.trim_end_matches(['\r', '\n']);
+            match line {{
+                "%%% OK" => break,
+                "%%% FAILURE" => failed = true,
+                _ => lines.push(line.to_string()),
+            }}
+        }}
+
+        if failed {{
+            return Err(Error::Server(lines.join("\n")));
+        }}
+        Ok(lines)
+    }}
+
+    /// Send a command to the server, returns the lines printed in response
+    pub fn command(&self, cmd: &str) -> std::result::Result<Vec<String>, Error> {{
+        use std::io::Write;
+
+        {{
+            let mut pipes = self.pipes.borrow_mut();
+            let stdin = match &mut pipes.stdin {{
+                Some(stdin) => stdin,
+                None => return Err(Error::Server("server exited".to_string())),
+            }};
+            writeln!(stdin, "{{cmd}}")?;
+            stdin.flush()?;
+        }}
+        self.response()
+    }}
+
+    fn new_name(&self) -> String {{
+        let n = self.counter.get();
+        self.counter.set(n + 1);
+        format!("v{{n}}")
+    }}
+
+    /// Load a value in the Futhark binary data format into a new variable
+    fn restore(&self, ty: &str, bytes: &[u8]) -> std::result::Result<Var<'_>, Error> {{
+        let var = Var {{ name: self.new_name(), server: self }};
+        let path = self.dir.join(&var.name);
+        std::fs::write(&path, bytes)?;
+        // The path is quoted because the temporary directory may contain spaces
+        let res = self.command(&format!("restore \"{{}}\" {{}} {{ty}}", path.display(), var.name));
+        let _ = std::fs::remove_file(&path);
+        res?;
+        Ok(var)
+    }}
+
+    /// Get the value of a variable in the Futhark binary data format
+    fn store(&self, name: &str) -> std::result::Result<Vec<u8>, Error> {{
+        let path = self.dir.join(format!("{{name}}.out"));
+        let res = self
+            .command(&format!("store \"{{}}\" {{name}}", path.display()))
+            .and_then(|_| Ok(std::fs::read(&path)?));
+        let _ = std::fs::remove_file(&path);
+        res
+    }}
+
+    fn scalar<T: Elem>(&self, ty: &str, value: T) -> std::result::Result<Var<'_>, Error> {{
+        self.restore(ty, &encode(&[], &[value]))
+    }}
+
+    fn call(&self, entry: &str, outputs: &[&Var], inputs: &[&Var]) -> std::result::Result<(), Error> {{
+        let mut cmd = format!("call {{entry}}");
+        for var in outputs.iter().chain(inputs) {{
+            cmd.push(' ');
+            cmd.push_str(&var.name);
+        }}
+        self.command(&cmd)?;
+        Ok(())
+    }}
+
+    /// Clear Futhark caches
+    pub fn clear_caches(&self) -> std::result::Result<(), Error> {{
+        self.command("clear")?;
+        Ok(())
+    }}
+
+    /// Pause Futhark profiling
+    pub fn pause_profiling(&self) -> std::result::Result<(), Error> {{
+        self.command("pause_profiling")?;
+        Ok(())
+    }}
+
+    /// Resume profiling
+    pub fn unpause_profiling(&self) -> std::result::Result<(), Error> {{
+        self.command("unpause_profiling")?;
+        Ok(())
+    }}
+
+    /// Get a report of the time and memory used by the server
+    pub fn report(&self) -> std::result::Result<String, Error> {{
+        Ok(self.command("report")?.join("\n"))
+    }}
+}}
+
+impl Drop for Server {{
+    fn drop(&mut self) {{
+        // The server exits when stdin is closed
+        self.pipes.get_mut().stdin.take();
+        let _ = self.child.get_mut().wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }}
+}}
+
+/// Variable stored in the server, freed when dropped
+struct Var<'a> {{
+    name: String,
+    server: &'a Server,
+}}
+
+impl<'a> Var<'a> {{
+    /// Reserve a name for an entry point output
+    fn output(server: &'a Server) -> Self {{
+        Var {{ name: server.new_name(), server }}
+    }}
+
+    fn get<T: Elem>(&self, rank: usize) -> std::result::Result<(Vec<i64>, Vec<T>), Error> {{
+        decode(&self.server.store(&self.name)?, rank)
+    }}
+
+    fn get_scalar<T: Elem>(&self) -> std::result::Result<T, Error> {{
+        let (_, data) = self.get::<T>(0)?;
+        Ok(data[0])
+    }}
+}}
+
+impl<'a> Drop for Var<'a> {{
+    fn drop(&mut self) {{
+        let _ = self.server.command(&format!("free {{}}", self.name));
+    }}
+}}
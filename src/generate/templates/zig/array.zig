@@ -0,0 +1,50 @@
+
+pub const {futhark_type} = opaque {{}};
+
+extern fn {new_fn}(ctx: *futhark_context, data: [*]const {elemtype}, {new_dim_params}) ?*{futhark_type};
+extern fn {free_fn}(ctx: *futhark_context, arr: *{futhark_type}) c_int;
+extern fn {values_fn}(ctx: *futhark_context, arr: *{futhark_type}, data: [*]{elemtype}) c_int;
+extern fn {shape_fn}(ctx: *futhark_context, arr: *{futhark_type}) [*]const i64;
+
+/// Array with {rank} dimensions and {elemtype} elements
+pub const {zig_type} = struct {{
+    ptr: *{futhark_type},
+    ctx: *const Context,
+    shape: [{rank}]i64,
+
+    /// Create a new array of `dims` dimensions and initialize it with `data`
+    pub fn init(ctx: *const Context, data: []const {elemtype}, dims: [{rank}]i64) Error!{zig_type} {{
+        if (data.len != shapeSize(&dims)) return error.InvalidShape;
+        const ptr = {new_fn}(ctx.handle, data.ptr, {dim_args}) orelse return error.NullPtr;
+        errdefer _ = {free_fn}(ctx.handle, ptr);
+        try ctx.autoSync();
+        return .{{ .ptr = ptr, .ctx = ctx, .shape = dims }};
+    }}
+
+    fn fromPtr(ctx: *const Context, ptr: *{futhark_type}) {zig_type} {{
+        const dims = {shape_fn}(ctx.handle, ptr);
+        var shape: [{rank}]i64 = undefined;
+        for (&shape, 0..) |*n, i| n.* = dims[i];
+        return .{{ .ptr = ptr, .ctx = ctx, .shape = shape }};
+    }}
+
+    /// Free the array
+    pub fn deinit(self: *{zig_type}) void {{
+        _ = {free_fn}(self.ctx.handle, self.ptr);
+    }}
+
+    /// Copy the values into `out`, which must have the same number of elements as the array
+    pub fn values(self: *const {zig_type}, out: []{elemtype}) Error!void {{
+        if (out.len != shapeSize(&self.shape)) return error.InvalidShape;
+        try check({values_fn}(self.ctx.handle, self.ptr, out.ptr));
+        try self.ctx.autoSync();
+    }}
+
+    /// Copy the values into a new slice allocated using `allocator`, the result is owned by the caller
+    pub fn get(self: *const {zig_type}, allocator: std.mem.Allocator) (Error || std.mem.Allocator.Error)![]{elemtype} {{
+        const out = try allocator.alloc({elemtype}, shapeSize(&self.shape));
+        errdefer allocator.free(out);
+        try self.values(out);
+        return out;
+    }}
+}};
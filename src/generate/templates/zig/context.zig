@@ -0,0 +1,143 @@
+const std = @import("std");
+
+pub const futhark_context_config = opaque {{}};
+pub const futhark_context = opaque {{}};
+
+extern fn futhark_context_config_new() ?*futhark_context_config;
+extern fn futhark_context_config_free(cfg: *futhark_context_config) void;
+extern fn futhark_context_config_set_debugging(cfg: *futhark_context_config, flag: c_int) void;
+extern fn futhark_context_config_set_profiling(cfg: *futhark_context_config, flag: c_int) void;
+extern fn futhark_context_config_set_logging(cfg: *futhark_context_config, flag: c_int) void;
+extern fn futhark_context_config_set_cache_file(cfg: *futhark_context_config, path: [*:0]const u8) void;
+extern fn futhark_context_new(cfg: *futhark_context_config) ?*futhark_context;
+extern fn futhark_context_free(ctx: *futhark_context) void;
+extern fn futhark_context_sync(ctx: *futhark_context) c_int;
+extern fn futhark_context_clear_caches(ctx: *futhark_context) c_int;
+extern fn futhark_context_pause_profiling(ctx: *futhark_context) void;
+extern fn futhark_context_unpause_profiling(ctx: *futhark_context) void;
+extern fn futhark_context_get_error(ctx: *futhark_context) ?[*:0]u8;
+extern fn futhark_context_report(ctx: *futhark_context) ?[*:0]u8;
+extern fn free(ptr: ?*anyopaque) void;
+extern fn strdup(s: [*:0]const u8) ?[*:0]u8;
+{backend_extern_functions}
+
+/// Errors returned by Futhark functions
+pub const Error = error{{
+    /// A NULL pointer was returned
+    NullPtr,
+    /// The data does not match the shape of an array
+    InvalidShape,
+    /// The Futhark program failed, for example because of a bounds check
+    ProgramError,
+    /// Futhark ran out of memory
+    OutOfMemory,
+    /// Any other Futhark error
+    Futhark,
+}};
+
+fn check(rc: c_int) Error!void {{
+    return switch (rc) {{
+        0 => {{}},
+        2 => error.ProgramError,
+        3 => error.OutOfMemory,
+        else => error.Futhark,
+    }};
+}}
+
+fn shapeSize(shape: []const i64) usize {{
+    var size: i64 = 1;
+    for (shape) |n| size *= n;
+    return @intCast(size);
+}}
+
+/// Options used to configure a `Context`
+pub const Options = struct {{
+    debug: bool = false,
+    profile: bool = false,
+    log: bool = false,
+    /// Path to the Futhark cache file
+    cache_file: ?[*:0]const u8 = null,
+    /// Sync the context after copying data to or from the device
+    auto_sync: bool = true,
+{backend_options}
+}};
+
+/// Futhark context
+pub const Context = struct {{
+    config: *futhark_context_config,
+    handle: *futhark_context,
+    auto_sync: bool,
+    // Futhark keeps pointers to the strings passed to the config, so the context owns copies
+    cache_file: ?[*:0]u8 = null,
+    device: ?[*:0]u8 = null,
+
+    /// Create a new context
+    pub fn init(options: Options) Error!Context {{
+        const config = futhark_context_config_new() orelse return error.NullPtr;
+        var self = Context{{ .config = config, .handle = undefined, .auto_sync = options.auto_sync }};
+        errdefer self.freeConfig();
+        futhark_context_config_set_debugging(config, @intFromBool(options.debug));
+        futhark_context_config_set_profiling(config, @intFromBool(options.profile));
+        futhark_context_config_set_logging(config, @intFromBool(options.log));
+        if (options.cache_file) |path| {{
+            self.cache_file = strdup(path) orelse return error.NullPtr;
+            futhark_context_config_set_cache_file(config, self.cache_file.?);
+        }}
+{backend_config}
+        self.handle = futhark_context_new(config) orelse return error.NullPtr;
+        return self;
+    }}
+
+    fn freeConfig(self: *Context) void {{
+        futhark_context_config_free(self.config);
+        free(self.cache_file);
+        free(self.device);
+    }}
+
+    /// Free the context, all values created using the context must be freed first
+    pub fn deinit(self: *Context) void {{
+        _ = futhark_context_sync(self.handle);
+        futhark_context_free(self.handle);
+        self.freeConfig();
+    }}
+
+    /// Sync the context, if `auto_sync` is enabled this shouldn't be needed
+    pub fn sync(self: *const Context) Error!void {{
+        return check(futhark_context_sync(self.handle));
+    }}
+
+    fn autoSync(self: *const Context) Error!void {{
+        if (self.auto_sync) try self.sync();
+    }}
+
+    /// Clear Futhark caches
+    pub fn clearCaches(self: *const Context) Error!void {{
+        return check(futhark_context_clear_caches(self.handle));
+    }}
+
+    /// Pause profiling
+    pub fn pauseProfiling(self: *const Context) void {{
+        futhark_context_pause_profiling(self.handle);
+    }}
+
+    /// Resume profiling
+    pub fn unpauseProfiling(self: *const Context) void {{
+        futhark_context_unpause_profiling(self.handle);
+    }}
+
+    /// Get the last error message, the result is owned by the caller
+    pub fn getError(self: *const Context, allocator: std.mem.Allocator) std.mem.Allocator.Error!?[]u8 {{
+        return takeString(allocator, futhark_context_get_error(self.handle));
+    }}
+
+    /// Get the profiling report, the result is owned by the caller
+    pub fn report(self: *const Context, allocator: std.mem.Allocator) std.mem.Allocator.Error!?[]u8 {{
+        return takeString(allocator, futhark_context_report(self.handle));
+    }}
+}};
+
+fn takeString(allocator: std.mem.Allocator, s: ?[*:0]u8) std.mem.Allocator.Error!?[]u8 {{
+    const ptr = s orelse return null;
+    defer free(ptr);
+    return try allocator.dupe(u8, std.mem.span(ptr));
+}}
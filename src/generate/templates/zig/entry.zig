@@ -0,0 +1,9 @@
+
+extern fn {entry_fn}({extern_params}) c_int;
+
+/// Entry point: {name}
+pub fn {entry_name}({entry_params}) Error!{entry_return_type} {{
+    {out_decl}
+    try check({entry_fn}(ctx.handle, {call_args}));
+    return {entry_return};
+}}
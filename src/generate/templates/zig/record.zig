@@ -0,0 +1,10 @@
+
+    /// Create a new {zig_type} from its fields
+    pub fn init(ctx: *const Context, {new_params}) Error!{zig_type} {{
+        var out: ?*{futhark_type} = null;
+        try check({new_fn}(ctx.handle, &out, {new_call_args}));
+        const ptr = out orelse return error.NullPtr;
+        errdefer _ = {free_fn}(ctx.handle, ptr);
+        try ctx.autoSync();
+        return fromPtr(ctx, ptr);
+    }}
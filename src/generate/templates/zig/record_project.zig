@@ -0,0 +1,8 @@
+
+    /// Get the {name} field
+    pub fn get{field_name}(self: *const {zig_type}) Error!{zig_field_type} {{
+        var out: {out_type} = {out_init};
+        try check({project_fn}(self.ctx.handle, &out, self.ptr));
+        try self.ctx.autoSync();
+        return {output};
+    }}
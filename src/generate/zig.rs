@@ -0,0 +1,334 @@
+use crate::codegen::first_uppercase;
+use crate::*;
+use std::io::Write;
+
+/// Zig codegen
+#[derive(Default)]
+pub struct Zig;
+
+enum ZigType {
+    /// Zig wrapper type and the name of the underlying Futhark struct
+    Array(String, String),
+
+    /// Zig wrapper type and the name of the underlying Futhark struct
+    Opaque(String, String),
+
+    /// Scalar type, Futhark and Zig use the same names for scalar types
+    Scalar(String),
+}
+
+// Reserved names that need to be escaped using `@"name"` syntax
+const ZIG_KEYWORDS: &[&str] = &[
+    "addrspace",
+    "align",
+    "and",
+    "anyframe",
+    "anytype",
+    "asm",
+    "async",
+    "await",
+    "break",
+    "callconv",
+    "catch",
+    "comptime",
+    "const",
+    "continue",
+    "defer",
+    "else",
+    "enum",
+    "errdefer",
+    "error",
+    "export",
+    "extern",
+    "fn",
+    "for",
+    "if",
+    "inline",
+    "noalias",
+    "nosuspend",
+    "opaque",
+    "or",
+    "orelse",
+    "packed",
+    "pub",
+    "resume",
+    "return",
+    "struct",
+    "suspend",
+    "switch",
+    "test",
+    "threadlocal",
+    "try",
+    "type",
+    "union",
+    "unreachable",
+    "usingnamespace",
+    "var",
+    "volatile",
+    "while",
+];
+
+fn identifier(s: &str) -> String {
+    if ZIG_KEYWORDS.contains(&s) {
+        format!("@\"{s}\"")
+    } else {
+        s.to_string()
+    }
+}
+
+/// Convert `snake_case` names into `camelCase` function names
+fn function_name(s: &str) -> String {
+    let mut parts = s.split('_');
+    let mut name = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        name += &first_uppercase(part);
+    }
+    identifier(&name)
+}
+
+impl Zig {
+    fn get_type(pkg: &Package, t: &str) -> ZigType {
+        match pkg.types().resolved(t) {
+            codegen::Type::Scalar(_) => ZigType::Scalar(t.to_string()),
+            codegen::Type::Array(a) => ZigType::Array(a.type_name(), a.c_name),
+            ty => ZigType::Opaque(ty.type_name().unwrap(), ty.c_name().unwrap().to_string()),
+        }
+    }
+}
+
+impl Generate for Zig {
+    fn required_features(&self) -> &'static [Feature] {
+        &[Feature::CacheFile, Feature::PauseProfiling]
+    }
+
+    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
+        writeln!(config.output_file, "// Generated by futhark-bindgen\n")?;
+
+        let (backend_extern_functions, backend_options, backend_config) = match pkg.manifest.backend {
+            Backend::Multicore => (
+                "extern fn futhark_context_config_set_num_threads(cfg: *futhark_context_config, n: c_int) void;",
+                "    num_threads: c_int = 0,",
+                "        futhark_context_config_set_num_threads(config, options.num_threads);",
+            ),
+            Backend::CUDA | Backend::OpenCL => (
+                "extern fn futhark_context_config_set_device(cfg: *futhark_context_config, device: [*:0]const u8) void;",
+                "    /// Name of the device to use\n    device: ?[*:0]const u8 = null,",
+                "        if (options.device) |device| {\n            self.device = strdup(device) orelse return error.NullPtr;\n            futhark_context_config_set_device(config, self.device.?);\n        }",
+            ),
+            _ => ("", "", ""),
+        };
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/zig/context.zig"),
+            backend_extern_functions = backend_extern_functions,
+            backend_options = backend_options,
+            backend_config = backend_config,
+        )?;
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        a: &manifest::ArrayType,
+    ) -> Result<(), Error> {
+        let array = pkg.types().resolved(name);
+        let new_dim_params: Vec<_> = (0..a.rank).map(|i| format!("dim{i}: i64")).collect();
+        let dim_args: Vec<_> = (0..a.rank).map(|i| format!("dims[{i}]")).collect();
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/zig/array.zig"),
+            zig_type = array.type_name().unwrap(),
+            futhark_type = array.c_name().unwrap(),
+            rank = a.rank,
+            elemtype = a.elemtype.to_str(),
+            new_fn = a.ops.new,
+            free_fn = a.ops.free,
+            values_fn = a.ops.values,
+            shape_fn = a.ops.shape,
+            new_dim_params = new_dim_params.join(", "),
+            dim_args = dim_args.join(", "),
+        )?;
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        ty: &manifest::OpaqueType,
+    ) -> Result<(), Error> {
+        let opaque = pkg.types().resolved(name);
+        let zig_type = opaque.type_name().unwrap();
+        let futhark_type = opaque.c_name().unwrap();
+
+        let mut record_extern_functions = String::new();
+        let mut record_functions = String::new();
+
+        if let Some(record) = &ty.record {
+            let mut new_params = Vec::new();
+            let mut new_call_args = Vec::new();
+            let mut new_extern_params = Vec::new();
+
+            for field in &record.fields {
+                let param = format!("field{}", field.name);
+                let (zig_field_type, out_type, out_init, output) =
+                    match Self::get_type(pkg, &field.r#type) {
+                        ZigType::Array(t, s) | ZigType::Opaque(t, s) => {
+                            new_params.push(format!("{param}: *const {t}"));
+                            new_call_args.push(format!("{param}.ptr"));
+                            new_extern_params.push(format!("{param}: *const {s}"));
+                            (
+                                t.clone(),
+                                format!("?*{s}"),
+                                "null",
+                                format!("{t}.fromPtr(self.ctx, out orelse return error.NullPtr)"),
+                            )
+                        }
+                        ZigType::Scalar(t) => {
+                            new_params.push(format!("{param}: {t}"));
+                            new_call_args.push(param.clone());
+                            new_extern_params.push(format!("{param}: {t}"));
+                            (t.clone(), t, "undefined", "out".to_string())
+                        }
+                    };
+
+                record_extern_functions += &format!(
+                    "extern fn {}(ctx: *futhark_context, out: *{out_type}, obj: *const {futhark_type}) c_int;\n",
+                    field.project
+                );
+
+                record_functions += &format!(
+                    include_str!("templates/zig/record_project.zig"),
+                    zig_type = zig_type,
+                    name = field.name,
+                    field_name = first_uppercase(&field.name),
+                    zig_field_type = zig_field_type,
+                    out_type = out_type,
+                    out_init = out_init,
+                    project_fn = field.project,
+                    output = output,
+                );
+            }
+
+            record_extern_functions += &format!(
+                "extern fn {}(ctx: *futhark_context, out: *?*{futhark_type}, {}) c_int;\n",
+                record.new,
+                new_extern_params.join(", ")
+            );
+
+            record_functions += &format!(
+                include_str!("templates/zig/record.zig"),
+                zig_type = zig_type,
+                futhark_type = futhark_type,
+                new_fn = record.new,
+                free_fn = ty.ops.free,
+                new_params = new_params.join(", "),
+                new_call_args = new_call_args.join(", "),
+            );
+        }
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/zig/opaque.zig"),
+            zig_type = zig_type,
+            name = name,
+            futhark_type = futhark_type,
+            free_fn = ty.ops.free,
+            record_extern_functions = record_extern_functions,
+            record_functions = record_functions,
+        )?;
+
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+        name: &str,
+        entry: &manifest::Entry,
+    ) -> Result<(), Error> {
+        let mut extern_params = vec!["ctx: *futhark_context".to_string()];
+        let mut entry_params = vec!["ctx: *const Context".to_string()];
+        let mut out_decl = Vec::new();
+        let mut call_args = Vec::new();
+        let mut return_type = Vec::new();
+        let mut entry_return = Vec::new();
+
+        for (i, out) in entry.outputs.iter().enumerate() {
+            let name = format!("out{i}");
+            call_args.push(format!("&{name}"));
+            match Self::get_type(pkg, &out.r#type) {
+                ZigType::Array(t, s) | ZigType::Opaque(t, s) => {
+                    extern_params.push(format!("{name}: *?*{s}"));
+                    out_decl.push(format!("var {name}: ?*{s} = null;"));
+                    entry_return.push(format!(
+                        "{t}.fromPtr(ctx, {name} orelse return error.NullPtr)"
+                    ));
+                    return_type.push(t);
+                }
+                ZigType::Scalar(t) => {
+                    extern_params.push(format!("{name}: *{t}"));
+                    out_decl.push(format!("var {name}: {t} = undefined;"));
+                    entry_return.push(name);
+                    return_type.push(t);
+                }
+            }
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let name = format!("input{i}");
+            match Self::get_type(pkg, &input.r#type) {
+                ZigType::Array(t, s) | ZigType::Opaque(t, s) => {
+                    extern_params.push(format!("{name}: *const {s}"));
+                    entry_params.push(format!("{name}: *const {t}"));
+                    call_args.push(format!("{name}.ptr"));
+                }
+                ZigType::Scalar(t) => {
+                    extern_params.push(format!("{name}: {t}"));
+                    entry_params.push(format!("{name}: {t}"));
+                    call_args.push(name);
+                }
+            }
+        }
+
+        let (entry_return_type, entry_return) = match entry.outputs.len() {
+            0 => ("void".to_string(), "{}".to_string()),
+            1 => (return_type.join(", "), entry_return.join(", ")),
+            _ => (
+                format!("struct {{ {} }}", return_type.join(", ")),
+                format!(".{{ {} }}", entry_return.join(", ")),
+            ),
+        };
+
+        writeln!(
+            config.output_file,
+            include_str!("templates/zig/entry.zig"),
+            entry_fn = entry.cfun,
+            name = name,
+            entry_name = function_name(name),
+            extern_params = extern_params.join(", "),
+            entry_params = entry_params.join(", "),
+            entry_return_type = entry_return_type,
+            out_decl = out_decl.join("\n    "),
+            call_args = call_args.join(", "),
+            entry_return = entry_return,
+        )?;
+
+        Ok(())
+    }
+
+    fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
+        let _ = std::process::Command::new("zig")
+            .arg("fmt")
+            .arg(path)
+            .status();
+        Ok(())
+    }
+}
@@ -1,19 +1,31 @@
 pub(crate) use std::collections::BTreeMap;
 
+mod build_config;
+pub mod codegen;
 mod compiler;
+pub mod data;
 mod error;
 pub(crate) mod generate;
 pub mod manifest;
 mod package;
+mod sdk;
+mod target;
+mod version;
 
+pub use build_config::BuildConfig;
 pub use compiler::Compiler;
 pub use error::Error;
-pub use generate::{Config, Generate, OCaml, Rust};
+pub use generate::{
+    CSharp, Config, External, Generate, Go, Haskell, Julia, Lua, Node, OCaml, Rust, RustServer,
+    Zig, C,
+};
 pub use manifest::Manifest;
 pub use package::Package;
+pub use sdk::Sdk;
+pub use version::{Feature, Version};
 
 /// `Backend` is used to select a backend when running the `futhark` executable
-#[derive(Debug, serde::Deserialize, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy)]
 pub enum Backend {
     /// Sequential C backend: `futhark c`
     ///
@@ -111,6 +123,19 @@ pub fn build(
     backend: Backend,
     src: impl AsRef<std::path::Path>,
     dest: impl AsRef<std::path::Path>,
+) {
+    build_with_config(backend, src, dest, &BuildConfig::default())
+}
+
+#[cfg(feature = "build")]
+/// Generate the bindings and link the Futhark C code, using `config` to compile the C code
+///
+/// See [`build`] for a description of the other arguments
+pub fn build_with_config(
+    backend: Backend,
+    src: impl AsRef<std::path::Path>,
+    dest: impl AsRef<std::path::Path>,
+    config: &BuildConfig,
 ) {
     let out = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
     let dest = std::path::PathBuf::from(&out).join(dest);
@@ -119,9 +144,10 @@ pub fn build(
         .compile()
         .expect("Compilation failed");
 
-    let mut config = Config::new(&dest).expect("Unable to configure codegen");
-    let mut gen = config.detect().expect("Invalid output language");
-    gen.generate(&lib, &mut config)
+    lib.check_output(&dest).expect("Invalid output file");
+    let mut codegen_config = Config::new(&dest).expect("Unable to configure codegen");
+    let mut gen = codegen_config.detect().expect("Invalid output language");
+    gen.generate(&lib, &mut codegen_config)
         .expect("Code generation failed");
-    lib.link();
+    lib.link_with_config(config);
 }
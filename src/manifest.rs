@@ -1,7 +1,7 @@
 use crate::*;
 
 /// Scalar types
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub enum ElemType {
     /// Signed 8 bit integer
     #[serde(rename = "i8")]
@@ -69,37 +69,114 @@ impl ElemType {
             ElemType::Bool => "bool",
         }
     }
+
+    /// Parse a scalar type name, returns `None` for non-scalar types
+    pub fn from_name(name: &str) -> Option<ElemType> {
+        match name {
+            "i8" => Some(ElemType::I8),
+            "i16" => Some(ElemType::I16),
+            "i32" => Some(ElemType::I32),
+            "i64" => Some(ElemType::I64),
+            "u8" => Some(ElemType::U8),
+            "u16" => Some(ElemType::U16),
+            "u32" => Some(ElemType::U32),
+            "u64" => Some(ElemType::U64),
+            "f16" => Some(ElemType::F16),
+            "f32" => Some(ElemType::F32),
+            "f64" => Some(ElemType::F64),
+            "bool" => Some(ElemType::Bool),
+            _ => None,
+        }
+    }
+
+    /// Get the C type used by the Futhark C API, `f16` values are passed using their bit
+    /// representation
+    pub fn c_type(&self) -> &'static str {
+        match self {
+            ElemType::I8 => "int8_t",
+            ElemType::I16 => "int16_t",
+            ElemType::I32 => "int32_t",
+            ElemType::I64 => "int64_t",
+            ElemType::U8 => "uint8_t",
+            ElemType::U16 | ElemType::F16 => "uint16_t",
+            ElemType::U32 => "uint32_t",
+            ElemType::U64 => "uint64_t",
+            ElemType::F32 => "float",
+            ElemType::F64 => "double",
+            ElemType::Bool => "bool",
+        }
+    }
+
+    /// Size of a value in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            ElemType::I8 | ElemType::U8 | ElemType::Bool => 1,
+            ElemType::I16 | ElemType::U16 | ElemType::F16 => 2,
+            ElemType::I32 | ElemType::U32 | ElemType::F32 => 4,
+            ElemType::I64 | ElemType::U64 | ElemType::F64 => 8,
+        }
+    }
+
+    /// Type name used by the Futhark binary data format, padded to 4 bytes
+    pub fn binary_name(&self) -> &'static str {
+        match self {
+            ElemType::I8 => "  i8",
+            ElemType::I16 => " i16",
+            ElemType::I32 => " i32",
+            ElemType::I64 => " i64",
+            ElemType::U8 => "  u8",
+            ElemType::U16 => " u16",
+            ElemType::U32 => " u32",
+            ElemType::U64 => " u64",
+            ElemType::F16 => " f16",
+            ElemType::F32 => " f32",
+            ElemType::F64 => " f64",
+            ElemType::Bool => "bool",
+        }
+    }
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Output {
     pub r#type: String,
     pub unique: bool,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Input {
     pub name: String,
     pub r#type: String,
     pub unique: bool,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Entry {
     pub cfun: String,
     pub outputs: Vec<Output>,
     pub inputs: Vec<Input>,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct ArrayOps {
     pub free: String,
     pub shape: String,
     pub values: String,
     pub new: String,
+
+    /// Create an array from existing device memory, only available in newer versions of Futhark
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_raw: Option<String>,
+
+    /// Get the device memory backing an array, only available in newer versions of Futhark
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values_raw: Option<String>,
+
+    /// Read a single element, only available in newer versions of Futhark
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct ArrayType {
     pub ctype: String,
     pub rank: i32,
@@ -107,34 +184,54 @@ pub struct ArrayType {
     pub ops: ArrayOps,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct OpaqueOps {
     pub free: String,
     pub store: String,
     pub restore: String,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Field {
     pub name: String,
     pub project: String,
     pub r#type: String,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Record {
     pub new: String,
     pub fields: Vec<Field>,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Variant {
+    pub name: String,
+    pub construct: String,
+    pub destruct: String,
+    pub payload: Vec<String>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Sum {
+    /// Function returning the index of the active variant
+    pub variant: String,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct OpaqueType {
     pub ctype: String,
     pub ops: OpaqueOps,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub record: Option<Record>,
+
+    /// Sum type constructors and destructors, only available in newer versions of Futhark
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sum: Option<Sum>,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(tag = "kind")]
 pub enum Type {
     #[serde(rename = "array")]
@@ -144,7 +241,7 @@ pub enum Type {
 }
 
 /// A Rust encoding of the Futhark manifest file
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Manifest {
     pub backend: Backend,
     pub version: String,
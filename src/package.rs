@@ -1,7 +1,8 @@
+use crate::target::Target;
 use crate::*;
 
 /// Compiled Futhark package
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct Package {
     /// Manifest, parsed from the manifest file
     pub manifest: Manifest,
@@ -14,63 +15,305 @@ pub struct Package {
 
     /// Source file
     pub src: std::path::PathBuf,
+
+    /// Path to the server executable, only set when it's built using [`Compiler::with_server`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<std::path::PathBuf>,
+
+    /// Types resolved from the manifest
+    #[serde(skip)]
+    types: codegen::Types,
 }
 
 impl Package {
+    /// Create a new package, the types in the manifest are resolved once here and are available
+    /// using [`Package::types`]
+    pub fn new(
+        manifest: Manifest,
+        c_file: impl Into<std::path::PathBuf>,
+        h_file: impl Into<std::path::PathBuf>,
+        src: impl Into<std::path::PathBuf>,
+    ) -> Result<Package, Error> {
+        let types = codegen::Types::new(&manifest)?;
+        Ok(Package {
+            manifest,
+            c_file: c_file.into(),
+            h_file: h_file.into(),
+            src: src.into(),
+            server: None,
+            types,
+        })
+    }
+
+    /// Get the types and entry points resolved from the manifest when the package was created
+    pub fn types(&self) -> &codegen::Types {
+        &self.types
+    }
+
+    /// Returns an error when writing generated code to `output` would replace the C file or
+    /// header generated by Futhark, this should be checked before the output file is created
+    ///
+    /// The full paths are compared after resolving symlinks and relative components, so the same
+    /// file reached using a different path is detected
+    pub fn check_output(&self, output: impl AsRef<std::path::Path>) -> Result<(), Error> {
+        fn canonicalize(path: &std::path::Path) -> Option<std::path::PathBuf> {
+            if let Ok(path) = path.canonicalize() {
+                return Some(path);
+            }
+
+            // The output file usually doesn't exist yet, so only its directory is resolved
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => std::path::Path::new("."),
+            };
+            Some(dir.canonicalize().ok()?.join(path.file_name()?))
+        }
+
+        let output = match canonicalize(output.as_ref()) {
+            Some(output) => output,
+            None => return Ok(()),
+        };
+        for file in [&self.c_file, &self.h_file] {
+            if canonicalize(file).as_ref() == Some(&output) {
+                return Err(Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "The output file would replace {}, which is generated by Futhark",
+                        file.display()
+                    ),
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the version of Futhark used to compile the package, `None` if the version in the
+    /// manifest can't be parsed
+    pub fn version(&self) -> Option<Version> {
+        Version::parse(&self.manifest.version)
+    }
+
+    /// Returns true when the version of Futhark used to compile the package provides `feature`,
+    /// unknown versions are assumed to support everything
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.version()
+            .is_none_or(|version| version.supports(feature))
+    }
+
+    /// Compile the ISPC kernels using the `ispc` executable from `$ISPC` or `$PATH`, returns the
+    /// paths of the object files
+    fn build_ispc(&self, config: &BuildConfig) -> Result<Vec<std::path::PathBuf>, Error> {
+        let exe = std::env::var("ISPC").unwrap_or_else(|_| "ispc".to_string());
+        let kernels = self.c_file.with_extension("kernels.ispc");
+        let dest = kernels.with_extension("o");
+
+        // With multiple targets `ispc` writes a dispatch object to `dest` and an object named
+        // `<name>_<isa>.o` for each target, objects left over from previous builds are removed
+        // first so they aren't linked
+        let dir = dest.parent().unwrap();
+        let prefix = format!("{}_", dest.file_stem().unwrap().to_string_lossy());
+        let target_objects = || -> Result<Vec<std::path::PathBuf>, Error> {
+            let mut objects = Vec::new();
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                let name = path.file_name().unwrap().to_string_lossy();
+                if name.starts_with(&prefix) && name.ends_with(".o") {
+                    objects.push(path);
+                }
+            }
+            objects.sort();
+            Ok(objects)
+        };
+        for object in target_objects()? {
+            std::fs::remove_file(object)?;
+        }
+
+        let output = std::process::Command::new(&exe)
+            .arg(&kernels)
+            .arg("-o")
+            .arg(&dest)
+            .arg("--pic")
+            .arg("--addressing=64")
+            .args(config.ispc_args())
+            .output()
+            .map_err(|e| Error::Ispc(format!("unable to run {exe}: {e}")))?;
+        if !output.status.success() {
+            return Err(Error::Ispc(format!(
+                "{exe} failed: {}\n{}{}",
+                output.status,
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        let mut objects = vec![dest.clone()];
+        objects.extend(target_objects()?);
+        Ok(objects)
+    }
+
     #[cfg(feature = "build")]
-    fn build(&self, libname: &str) {
+    fn build(&self, libname: &str, config: &BuildConfig, sdk: &Sdk) {
+        let mut build = cc::Build::new();
+        build
+            .file(&self.c_file)
+            .includes(&sdk.include_dirs)
+            .flag("-std=c99");
+
         if self.manifest.backend == Backend::ISPC {
-            let kernels = self.c_file.with_extension("kernels.ispc");
-            let dest = kernels.with_extension("o");
-            std::process::Command::new("ispc")
-                .arg(&kernels)
+            println!("cargo:rerun-if-env-changed=ISPC");
+            let objects = match self.build_ispc(config) {
+                Ok(objects) => objects,
+                Err(Error::Ispc(msg)) => panic!("{msg}"),
+                Err(e) => panic!("Unable to run ispc: {e:?}"),
+            };
+
+            build.objects(objects);
+            if !Target::current().is_windows() {
+                build.flag("-fPIC").flag("-pthread");
+            }
+        } else {
+            build.flag("-Wno-unused-parameter");
+        }
+
+        config.apply(&mut build);
+        build.compile(libname);
+    }
+
+    /// Compile the C code into a library at `output` using the C compiler from `$CC` (`cc` by
+    /// default), a shared library is created when [`BuildConfig::with_shared`] is enabled,
+    /// otherwise a static archive is created using `$AR` (`ar` by default)
+    pub fn build_library(
+        &self,
+        output: impl AsRef<std::path::Path>,
+        config: &BuildConfig,
+    ) -> Result<(), Error> {
+        let sdk = Sdk::find(self.manifest.backend)?;
+        self.build_library_with_sdk(output.as_ref(), config, &sdk)
+    }
+
+    fn build_library_with_sdk(
+        &self,
+        output: &std::path::Path,
+        config: &BuildConfig,
+        sdk: &Sdk,
+    ) -> Result<(), Error> {
+        let ispc = if self.manifest.backend == Backend::ISPC {
+            self.build_ispc(config)?
+        } else {
+            Vec::new()
+        };
+
+        let target = Target::current();
+        let mut cmd = std::process::Command::new(target.tool("CC", "cc"));
+        cmd.arg("-std=c99")
+            .arg("-fPIC")
+            .args(config.cc_args())
+            .args(
+                sdk.include_dirs
+                    .iter()
+                    .map(|dir| format!("-I{}", dir.display())),
+            );
+
+        if config.is_shared() {
+            cmd.arg("-shared")
+                .arg(&self.c_file)
+                .args(&ispc)
                 .arg("-o")
-                .arg(&dest)
-                .arg("--pic")
-                .arg("--addressing=64")
-                .arg("--target=host")
-                .arg("-O3")
-                .status()
-                .expect("Unable to run ispc");
-
-            cc::Build::new()
-                .file(&self.c_file)
-                .object(&dest)
-                .flag("-fPIC")
-                .flag("-pthread")
-                .flag("-lm")
-                .flag("-std=c99")
-                .flag("-O3")
-                .extra_warnings(false)
-                .warnings(false)
-                .compile(libname);
+                .arg(output)
+                .args(config.link_args())
+                .args(
+                    sdk.lib_dirs
+                        .iter()
+                        .map(|dir| format!("-L{}", dir.display())),
+                );
+            for lib in target.filter_libs(self.manifest.backend.required_c_libs()) {
+                if target.is_macos() && lib == "OpenCL" {
+                    cmd.args(["-framework", lib]);
+                } else {
+                    cmd.arg(format!("-l{lib}"));
+                }
+            }
+            if !cmd.status()?.success() {
+                return Err(Error::CompilationFailed);
+            }
         } else {
-            cc::Build::new()
-                .flag("-std=c99")
-                .flag("-Wno-unused-parameter")
-                .flag("-O3")
-                .file(&self.c_file)
-                .extra_warnings(false)
-                .warnings(false)
-                .compile(libname);
+            let object = output.with_extension("o");
+            cmd.arg("-c").arg(&self.c_file).arg("-o").arg(&object);
+            if !cmd.status()?.success() {
+                return Err(Error::CompilationFailed);
+            }
+
+            let _ = std::fs::remove_file(output);
+            let ok = std::process::Command::new(target.tool("AR", "ar"))
+                .arg("crs")
+                .arg(output)
+                .arg(&object)
+                .args(&ispc)
+                .status()?
+                .success();
+            if !ok {
+                return Err(Error::CompilationFailed);
+            }
+            std::fs::remove_file(object)?;
         }
+        Ok(())
     }
+
     /// Link the package
     ///
     /// Note: This should only be used in `build.rs`
     #[cfg(feature = "build")]
     pub fn link(&self) {
+        self.link_with_config(&BuildConfig::default())
+    }
+
+    /// Link the package, using `config` to compile the C code
+    ///
+    /// When [`BuildConfig::with_shared`] is enabled the package is linked dynamically, the
+    /// shared library is created in `$OUT_DIR`
+    ///
+    /// Note: This should only be used in `build.rs`
+    #[cfg(feature = "build")]
+    pub fn link_with_config(&self, config: &BuildConfig) {
         let project = std::env::var("CARGO_PKG_NAME").unwrap();
         let name = format!("futhark_generate_{project}");
-        self.build(&name);
+        // The SDK may still be in the default compiler and linker search paths when it can't be
+        // found, so the build is attempted without the extra paths
+        let sdk = match Sdk::find(self.manifest.backend) {
+            Ok(sdk) => sdk,
+            Err(e) => {
+                let msg = match e {
+                    Error::SdkNotFound(msg) => msg,
+                    e => format!("{e:?}"),
+                };
+                println!("cargo:warning=Using the default search paths for the GPU SDK: {msg}");
+                Sdk::default()
+            }
+        };
+        let target = Target::current();
+
+        if config.is_shared() {
+            let out = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+            let lib = out.join(target.dylib_name(&name));
+            self.build_library_with_sdk(&lib, config, &sdk)
+                .expect("Unable to build shared library");
+            println!("cargo:rustc-link-search=native={}", out.display());
+            println!("cargo:rustc-link-lib=dylib={name}");
+        } else {
+            self.build(&name, config, &sdk);
+            println!("cargo:rustc-link-lib={name}");
+        }
 
         println!("cargo:rerun-if-changed={}", self.src.display());
-        println!("cargo:rustc-link-lib={name}");
 
-        let libs = self.manifest.backend.required_c_libs();
+        for dir in &sdk.lib_dirs {
+            println!("cargo:rustc-link-search=native={}", dir.display());
+        }
+
+        let libs = target.filter_libs(self.manifest.backend.required_c_libs());
 
         for lib in libs {
-            if cfg!(target_os = "macos") && lib == &"OpenCL" {
+            if target.is_macos() && lib == "OpenCL" {
                 println!("cargo:rustc-link-lib=framework={}", lib);
             } else {
                 println!("cargo:rustc-link-lib={}", lib);
@@ -0,0 +1,145 @@
+use crate::*;
+
+/// Include and library paths for the SDK required by a GPU backend
+#[derive(Debug, Clone, Default)]
+pub struct Sdk {
+    /// Install location, `None` when the paths were found using `pkg-config` or when the SDK is
+    /// installed in the default system paths
+    pub root: Option<std::path::PathBuf>,
+
+    /// Directories to add to the C include path
+    pub include_dirs: Vec<std::path::PathBuf>,
+
+    /// Directories to add to the library search path
+    pub lib_dirs: Vec<std::path::PathBuf>,
+}
+
+struct Search {
+    name: &'static str,
+    env: &'static [&'static str],
+    pkg_config: &'static [&'static str],
+    prefixes: &'static [&'static str],
+    header: &'static str,
+}
+
+fn search(backend: Backend) -> Option<Search> {
+    match backend {
+        Backend::CUDA => Some(Search {
+            name: "CUDA",
+            env: &["CUDA_PATH", "CUDA_HOME", "CUDA_ROOT"],
+            pkg_config: &["cuda", "cudart"],
+            prefixes: &["/usr/local/cuda", "/opt/cuda"],
+            header: "cuda.h",
+        }),
+        Backend::HIP => Some(Search {
+            name: "HIP",
+            env: &["ROCM_PATH", "HIP_PATH"],
+            pkg_config: &["hip"],
+            prefixes: &["/opt/rocm"],
+            header: "hip/hip_runtime.h",
+        }),
+        // OpenCL is provided by a framework on macOS
+        Backend::OpenCL if target::Target::current().is_macos() => None,
+        Backend::OpenCL => Some(Search {
+            name: "OpenCL",
+            env: &["OPENCL_PATH", "CUDA_PATH", "ROCM_PATH"],
+            pkg_config: &["OpenCL"],
+            prefixes: &["/usr/local/cuda", "/opt/rocm"],
+            header: "CL/cl.h",
+        }),
+        _ => None,
+    }
+}
+
+impl Sdk {
+    fn from_root(root: &std::path::Path) -> Sdk {
+        let lib_dirs = ["lib64", "lib", "lib/x64"]
+            .iter()
+            .map(|dir| root.join(dir))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        Sdk {
+            root: Some(root.to_path_buf()),
+            include_dirs: vec![root.join("include")],
+            lib_dirs,
+        }
+    }
+
+    fn from_pkg_config(name: &str) -> Option<Sdk> {
+        let output = std::process::Command::new("pkg-config")
+            .args(["--cflags-only-I", "--libs-only-L", name])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let mut sdk = Sdk::default();
+        for arg in String::from_utf8_lossy(&output.stdout).split_ascii_whitespace() {
+            if let Some(dir) = arg.strip_prefix("-I") {
+                sdk.include_dirs.push(dir.into());
+            } else if let Some(dir) = arg.strip_prefix("-L") {
+                sdk.lib_dirs.push(dir.into());
+            }
+        }
+        Some(sdk)
+    }
+
+    /// Find the SDK used by `backend`
+    ///
+    /// The install location is taken from the first environment variable that is set
+    /// (`CUDA_PATH`, `CUDA_HOME` or `CUDA_ROOT` for CUDA, `ROCM_PATH` or `HIP_PATH` for HIP and
+    /// `OPENCL_PATH` for OpenCL), otherwise `pkg-config` and well-known prefixes like
+    /// `/usr/local/cuda` and `/opt/rocm` are searched. An empty `Sdk` is returned when the headers
+    /// are installed in `/usr/include` or the backend doesn't require an SDK.
+    pub fn find(backend: Backend) -> Result<Sdk, Error> {
+        let search = match search(backend) {
+            Some(search) => search,
+            None => return Ok(Sdk::default()),
+        };
+
+        for var in search.env {
+            if let Some(root) = std::env::var_os(var) {
+                let root = std::path::PathBuf::from(root);
+                if root.join("include").join(search.header).exists() {
+                    return Ok(Sdk::from_root(&root));
+                }
+
+                // Only the primary variable is required to point at this SDK, OpenCL can also
+                // be provided by CUDA or ROCm
+                if var == &search.env[0] {
+                    return Err(Error::SdkNotFound(format!(
+                        "{var} is set to {}, but {} doesn't exist",
+                        root.display(),
+                        root.join("include").join(search.header).display()
+                    )));
+                }
+            }
+        }
+
+        for name in search.pkg_config {
+            if let Some(sdk) = Sdk::from_pkg_config(name) {
+                return Ok(sdk);
+            }
+        }
+
+        for prefix in search.prefixes {
+            let root = std::path::Path::new(prefix);
+            if root.join("include").join(search.header).exists() {
+                return Ok(Sdk::from_root(root));
+            }
+        }
+
+        if std::path::Path::new("/usr/include")
+            .join(search.header)
+            .exists()
+        {
+            return Ok(Sdk::default());
+        }
+
+        Err(Error::SdkNotFound(format!(
+            "unable to find the {} SDK ({} not found), set {} to its install location",
+            search.name, search.header, search.env[0]
+        )))
+    }
+}
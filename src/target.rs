@@ -0,0 +1,197 @@
+/// Platform the C code is compiled for
+///
+/// Build scripts run on the host, so the `CARGO_CFG_TARGET_*` variables set by cargo are used
+/// instead of `cfg!` checks, the host platform is used when they aren't set
+#[derive(Debug, Clone)]
+pub(crate) struct Target {
+    pub os: String,
+    pub arch: String,
+    pub env: String,
+    triple: Option<String>,
+    cross: bool,
+}
+
+fn var_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+impl Target {
+    pub fn current() -> Target {
+        let triple = std::env::var("TARGET").ok();
+        let cross = match (&triple, std::env::var("HOST")) {
+            (Some(target), Ok(host)) => target != &host,
+            _ => false,
+        };
+        Target {
+            os: var_or("CARGO_CFG_TARGET_OS", std::env::consts::OS),
+            arch: var_or("CARGO_CFG_TARGET_ARCH", std::env::consts::ARCH),
+            env: var_or("CARGO_CFG_TARGET_ENV", ""),
+            triple,
+            cross,
+        }
+    }
+
+    pub fn is_macos(&self) -> bool {
+        self.os == "macos" || self.os == "ios"
+    }
+
+    pub fn is_windows(&self) -> bool {
+        self.os == "windows"
+    }
+
+    /// Get a tool like `CC` or `AR` from the environment, the target specific variables
+    /// `CC_<target>` and `TARGET_CC` are checked before `CC`
+    pub fn tool(&self, name: &str, default: &str) -> String {
+        let mut vars = Vec::new();
+        if let Some(triple) = &self.triple {
+            vars.push(format!("{name}_{triple}"));
+            vars.push(format!("{name}_{}", triple.replace('-', "_")));
+            if self.cross {
+                vars.push(format!("TARGET_{name}"));
+            }
+        }
+        vars.push(name.to_string());
+        vars.iter()
+            .find_map(|var| std::env::var(var).ok())
+            .unwrap_or_else(|| default.to_string())
+    }
+
+    /// File name of a shared library
+    #[cfg(feature = "build")]
+    pub fn dylib_name(&self, name: &str) -> String {
+        if self.is_windows() {
+            format!("{name}.dll")
+        } else if self.is_macos() {
+            format!("lib{name}.dylib")
+        } else {
+            format!("lib{name}.so")
+        }
+    }
+
+    /// Remove libraries that don't exist on the target, `libm` and `libpthread` are part of the
+    /// C runtime on Windows and Android has no separate `libpthread`
+    pub fn filter_libs(&self, libs: &[&'static str]) -> Vec<&'static str> {
+        libs.iter()
+            .copied()
+            .filter(|lib| match *lib {
+                "m" => !(self.is_windows() && self.env == "msvc"),
+                "pthread" => !(self.is_windows() && self.env == "msvc") && self.os != "android",
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Default ISPC target, `host` unless cross-compiling
+    pub fn ispc_target(&self) -> &'static str {
+        if !self.cross {
+            return "host";
+        }
+
+        match self.arch.as_str() {
+            "x86_64" => "sse4-i32x4",
+            "x86" => "sse2-i32x4",
+            "aarch64" | "arm" => "neon-i32x4",
+            _ => "host",
+        }
+    }
+
+    /// `--arch` and `--target-os` arguments for ISPC when cross-compiling
+    pub fn ispc_args(&self) -> Vec<String> {
+        if !self.cross {
+            return Vec::new();
+        }
+
+        let mut args = Vec::new();
+        let arch = match self.arch.as_str() {
+            "x86_64" => Some("x86-64"),
+            "x86" => Some("x86"),
+            "aarch64" => Some("aarch64"),
+            "arm" => Some("arm"),
+            _ => None,
+        };
+        if let Some(arch) = arch {
+            args.push(format!("--arch={arch}"));
+        }
+
+        let os = match self.os.as_str() {
+            "linux" => Some("linux"),
+            "macos" => Some("macos"),
+            "windows" => Some("windows"),
+            "android" => Some("android"),
+            "ios" => Some("ios"),
+            "freebsd" => Some("freebsd"),
+            _ => None,
+        };
+        if let Some(os) = os {
+            args.push(format!("--target-os={os}"));
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(os: &str, arch: &str, env: &str, cross: bool) -> Target {
+        Target {
+            os: os.to_string(),
+            arch: arch.to_string(),
+            env: env.to_string(),
+            triple: None,
+            cross,
+        }
+    }
+
+    #[test]
+    fn ispc_native() {
+        let t = target("linux", "aarch64", "gnu", false);
+        assert_eq!(t.ispc_target(), "host");
+        assert!(t.ispc_args().is_empty());
+    }
+
+    #[test]
+    fn ispc_cross() {
+        let t = target("linux", "x86_64", "gnu", true);
+        assert_eq!(t.ispc_target(), "sse4-i32x4");
+        assert_eq!(t.ispc_args(), ["--arch=x86-64", "--target-os=linux"]);
+
+        let t = target("windows", "x86", "msvc", true);
+        assert_eq!(t.ispc_target(), "sse2-i32x4");
+        assert_eq!(t.ispc_args(), ["--arch=x86", "--target-os=windows"]);
+
+        let t = target("macos", "aarch64", "", true);
+        assert_eq!(t.ispc_target(), "neon-i32x4");
+        assert_eq!(t.ispc_args(), ["--arch=aarch64", "--target-os=macos"]);
+
+        let t = target("android", "arm", "", true);
+        assert_eq!(t.ispc_target(), "neon-i32x4");
+        assert_eq!(t.ispc_args(), ["--arch=arm", "--target-os=android"]);
+
+        // Unknown targets leave the choice to ISPC
+        let t = target("none", "riscv64", "", true);
+        assert_eq!(t.ispc_target(), "host");
+        assert!(t.ispc_args().is_empty());
+    }
+
+    #[test]
+    fn filter_libs() {
+        let libs = &["cuda", "m", "pthread"];
+        assert_eq!(
+            target("linux", "x86_64", "gnu", false).filter_libs(libs),
+            libs
+        );
+        assert_eq!(
+            target("windows", "x86_64", "msvc", true).filter_libs(libs),
+            ["cuda"]
+        );
+        assert_eq!(
+            target("windows", "x86_64", "gnu", true).filter_libs(libs),
+            libs
+        );
+        assert_eq!(
+            target("android", "aarch64", "", true).filter_libs(libs),
+            ["cuda", "m"]
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use crate::*;
+
+/// Futhark compiler version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+/// C API functionality that isn't available in every version of Futhark
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// `futhark_context_config_set_cache_file`
+    CacheFile,
+
+    /// `futhark_context_pause_profiling` and `futhark_context_unpause_profiling`
+    PauseProfiling,
+
+    /// `futhark_context_set_logging_file`
+    LoggingFile,
+
+    /// `futhark_new_raw_*` and `futhark_values_raw_*` in the manifest
+    RawArrays,
+
+    /// `futhark_index_*` in the manifest
+    ArrayIndex,
+
+    /// Sum type constructors and destructors in the manifest
+    SumTypes,
+}
+
+/// The first release of Futhark providing each feature
+///
+/// Versions refer to the first release whose entry in the Futhark changelog
+/// (<https://github.com/diku-dk/futhark/blob/master/CHANGES.md>) lists the function, new
+/// features should be added the same way
+const FEATURES: &[(Feature, Version)] = &[
+    (Feature::CacheFile, Version::new(0, 22, 0)),
+    (Feature::PauseProfiling, Version::new(0, 23, 0)),
+    (Feature::LoggingFile, Version::new(0, 24, 0)),
+    (Feature::RawArrays, Version::new(0, 25, 0)),
+    (Feature::ArrayIndex, Version::new(0, 25, 0)),
+    (Feature::SumTypes, Version::new(0, 25, 10)),
+];
+
+impl Feature {
+    /// All features, in the order they were added to Futhark
+    pub fn all() -> impl Iterator<Item = Feature> {
+        FEATURES.iter().map(|(feature, _)| *feature)
+    }
+
+    /// The first version of Futhark providing this feature
+    pub fn since(self) -> Version {
+        FEATURES
+            .iter()
+            .find(|(feature, _)| *feature == self)
+            .map(|(_, version)| *version)
+            .unwrap()
+    }
+
+    /// The C API functions this feature refers to
+    pub fn description(self) -> &'static str {
+        match self {
+            Feature::CacheFile => "futhark_context_config_set_cache_file",
+            Feature::PauseProfiling => {
+                "futhark_context_pause_profiling/futhark_context_unpause_profiling"
+            }
+            Feature::LoggingFile => "futhark_context_set_logging_file",
+            Feature::RawArrays => "futhark_new_raw_*/futhark_values_raw_*",
+            Feature::ArrayIndex => "futhark_index_*",
+            Feature::SumTypes => "sum types",
+        }
+    }
+}
+
+impl Version {
+    /// Oldest version of Futhark that writes a manifest file
+    pub const MINIMUM: Version = Version::new(0, 21, 0);
+
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a version from a manifest or the output of `futhark --version`, the first word
+    /// starting with a digit is used, so `0.25.13`, `Futhark 0.25.13` and `0.26.0-dev` are all
+    /// accepted
+    pub fn parse(s: &str) -> Option<Version> {
+        let word = s
+            .split_whitespace()
+            .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?;
+        let mut parts = word
+            .split(|c: char| !c.is_ascii_digit())
+            .take(3)
+            .map(|part| part.parse::<u32>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).unwrap_or(0);
+        Some(Version::new(major, minor, patch))
+    }
+
+    /// Returns true when `feature` is available in this version
+    pub fn supports(&self, feature: Feature) -> bool {
+        *self >= feature.since()
+    }
+
+    /// Returns an error if this version is older than `Version::MINIMUM` or doesn't provide one
+    /// of `features`
+    pub fn check(&self, features: &[Feature]) -> Result<(), Error> {
+        if *self < Version::MINIMUM {
+            return Err(Error::UnsupportedVersion(format!(
+                "futhark {self} is not supported, version {} or newer is required",
+                Version::MINIMUM
+            )));
+        }
+
+        for feature in features {
+            if !self.supports(*feature) {
+                return Err(Error::UnsupportedVersion(format!(
+                    "futhark {self} doesn't provide {}, version {} or newer is required",
+                    feature.description(),
+                    feature.since()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Version, Error> {
+        Version::parse(s)
+            .ok_or_else(|| Error::UnsupportedVersion(format!("invalid futhark version: {s}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Version::parse("0.25.13"), Some(Version::new(0, 25, 13)));
+        assert_eq!(
+            Version::parse("Futhark 0.25.13\ngit: abc"),
+            Some(Version::new(0, 25, 13))
+        );
+        assert_eq!(Version::parse("0.26.0-dev"), Some(Version::new(0, 26, 0)));
+        assert_eq!(
+            Version::parse("Futhark 0.26.0-dev (prerelease)"),
+            Some(Version::new(0, 26, 0))
+        );
+        assert_eq!(Version::parse("0.22"), Some(Version::new(0, 22, 0)));
+        assert_eq!("0.25.1".parse::<Version>().unwrap(), Version::new(0, 25, 1));
+    }
+
+    #[test]
+    fn parse_malformed() {
+        assert_eq!(Version::parse(""), None);
+        assert_eq!(Version::parse("Futhark"), None);
+        assert_eq!(Version::parse("v0.25.13"), None);
+        assert_eq!(Version::parse("0.x.1"), None);
+        assert_eq!(Version::parse("99999999999.1.0"), None);
+        assert!(matches!(
+            "dev".parse::<Version>(),
+            Err(Error::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn compare() {
+        assert!(Version::new(0, 25, 10) > Version::new(0, 25, 9));
+        assert!(Version::new(0, 26, 0) > Version::new(0, 25, 13));
+        assert!(Version::new(1, 0, 0) > Version::new(0, 99, 99));
+        assert_eq!(Version::parse("0.26.0-dev"), Version::parse("0.26.0"));
+        assert_eq!(Version::new(0, 25, 13).to_string(), "0.25.13");
+    }
+
+    #[test]
+    fn check() {
+        let old = Version::new(0, 20, 0);
+        assert!(matches!(old.check(&[]), Err(Error::UnsupportedVersion(_))));
+        assert!(Version::MINIMUM.check(&[]).is_ok());
+
+        let version = Version::new(0, 25, 9);
+        assert!(version.supports(Feature::ArrayIndex));
+        assert!(!version.supports(Feature::SumTypes));
+        assert!(version
+            .check(&[Feature::CacheFile, Feature::RawArrays])
+            .is_ok());
+        assert!(matches!(
+            version.check(&[Feature::SumTypes]),
+            Err(Error::UnsupportedVersion(_))
+        ));
+        assert!(Version::new(0, 25, 10).check(&[Feature::SumTypes]).is_ok());
+
+        for feature in Feature::all() {
+            assert!(feature.since() >= Version::MINIMUM);
+            assert!(feature.since().supports(feature));
+        }
+    }
+}